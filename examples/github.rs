@@ -4,6 +4,7 @@ use std::sync::Arc;
 use axum::extract::Query;
 use axum::Router;
 use axum::{routing::get, Extension};
+use oauth_axum::flow::PendingAuthorization;
 use oauth_axum::providers::github::GithubProvider;
 use oauth_axum::{CustomProvider, OAuthClient};
 
@@ -21,10 +22,13 @@ async fn main() {
     println!("Starting server...");
 
     let state = Arc::new(AxumState::new());
+    // The provider is created once and shared, so the oauth2 client is reused in every request
+    let provider = Arc::new(get_client());
     let app = Router::new()
         .route("/", get(create_url))
         .route("/api/v1/github/callback", get(callback))
-        .layer(Extension(state.clone()));
+        .layer(Extension(state.clone()))
+        .layer(Extension(provider));
 
     println!("🚀 Server started successfully");
     let listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
@@ -41,29 +45,31 @@ fn get_client() -> CustomProvider {
     )
 }
 
-pub async fn create_url(Extension(state): Extension<Arc<AxumState>>) -> String {
-    let state_oauth = get_client()
-        .generate_url(Vec::from(["read:user".to_string()]), |state_e| async move {
-            state.set(state_e.state, state_e.verifier);
-        })
+pub async fn create_url(
+    Extension(state): Extension<Arc<AxumState>>,
+    Extension(provider): Extension<Arc<CustomProvider>>,
+) -> String {
+    let pending = provider
+        .authorize(Vec::from(["read:user".to_string()]))
         .await
-        .ok()
-        .unwrap()
-        .state
         .unwrap();
-
-    state_oauth.url_generated.unwrap()
+    let url = pending.url().to_string();
+    state.set(pending.state().to_string(), pending.into_state());
+    url
 }
 
 pub async fn callback(
     Extension(state): Extension<Arc<AxumState>>,
+    Extension(provider): Extension<Arc<CustomProvider>>,
     Query(queries): Query<QueryAxumCallback>,
 ) -> String {
     println!("{:?}", state.clone().get_all_items());
     let item = state.get(queries.state.clone());
-    get_client()
-        .generate_token(queries.code, item.unwrap())
+    PendingAuthorization::resume(provider.as_ref().clone(), item.unwrap())
+        .unwrap()
+        .exchange(queries.code)
         .await
         .ok()
         .unwrap()
+        .access_token
 }
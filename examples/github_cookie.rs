@@ -0,0 +1,72 @@
+use axum::extract::{FromRef, Query};
+use axum::http::StatusCode;
+use axum::response::Redirect;
+use axum::routing::get;
+use axum::Router;
+use axum_extra::extract::cookie::{Key, PrivateCookieJar};
+use oauth_axum::cookie::{generate_token_from_cookies, generate_url_with_cookies};
+use oauth_axum::providers::github::GithubProvider;
+use oauth_axum::CustomProvider;
+
+#[derive(Clone, serde::Deserialize)]
+pub struct QueryAxumCallback {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Clone)]
+struct AppState {
+    key: Key,
+}
+
+impl FromRef<AppState> for Key {
+    fn from_ref(state: &AppState) -> Self {
+        state.key.clone()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv::from_filename("examples/.env").ok();
+    println!("Starting server...");
+
+    // In production the key needs to be loaded from a secret, so restarts don't invalidate the cookies
+    let state = AppState {
+        key: Key::generate(),
+    };
+    let app = Router::new()
+        .route("/", get(create_url))
+        .route("/api/v1/github/callback", get(callback))
+        .with_state(state);
+
+    println!("🚀 Server started successfully");
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
+        .await
+        .unwrap();
+    axum::serve(listener, app).await.unwrap();
+}
+
+fn get_client() -> CustomProvider {
+    GithubProvider::new(
+        std::env::var("GITHUB_CLIENT_ID").expect("GITHUB_CLIENT_ID must be set"),
+        std::env::var("GITHUB_SECRET").expect("GITHUB_SECRET must be set"),
+        "http://localhost:3000/api/v1/github/callback".to_string(),
+    )
+}
+
+pub async fn create_url(jar: PrivateCookieJar) -> (PrivateCookieJar, Redirect) {
+    generate_url_with_cookies(get_client(), Vec::from(["read:user".to_string()]), jar)
+        .await
+        .ok()
+        .unwrap()
+}
+
+pub async fn callback(
+    jar: PrivateCookieJar,
+    Query(queries): Query<QueryAxumCallback>,
+) -> (PrivateCookieJar, Result<String, StatusCode>) {
+    // The jar is returned also in the error, so the cookies of the failed login are removed
+    let (jar, token) =
+        generate_token_from_cookies(&get_client(), jar, queries.code, &queries.state).await;
+    (jar, token.map_err(|_| StatusCode::BAD_REQUEST))
+}
@@ -3,8 +3,9 @@ use std::sync::Arc;
 use axum::extract::{Query, State};
 use axum::routing::get;
 use axum::Router;
+use oauth_axum::flow::PendingAuthorization;
 use oauth_axum::providers::github::GithubProvider;
-use oauth_axum::{CustomProvider, OAuthClient};
+use oauth_axum::{CustomProvider, OAuthClient, StateAuth};
 
 #[derive(Clone, serde::Deserialize)]
 pub struct QueryAxumCallback {
@@ -73,7 +74,7 @@ pub async fn create_url(State(state): State<Arc<Client>>) -> String {
             state
                 .execute(
                     "INSERT INTO oauth (state, verifier) VALUES ($1, $2)",
-                    &[&state_e.state, &state_e.verifier],
+                    &[&state_e.state(), &state_e.verifier()],
                 )
                 .await
                 .unwrap();
@@ -84,7 +85,7 @@ pub async fn create_url(State(state): State<Arc<Client>>) -> String {
         .state
         .unwrap();
 
-    state_oauth.url_generated.unwrap()
+    state_oauth.url_generated().unwrap().to_string()
 }
 
 pub async fn callback(
@@ -99,9 +100,12 @@ pub async fn callback(
         .await
         .unwrap();
 
-    get_client()
-        .generate_token(queries.code, row.get(0))
+    let state_auth = StateAuth::restore(queries.state, row.get(0)).unwrap();
+    PendingAuthorization::resume(get_client(), state_auth)
+        .unwrap()
+        .exchange(queries.code)
         .await
         .ok()
         .unwrap()
+        .access_token
 }
@@ -4,7 +4,8 @@ use std::sync::Arc;
 use axum::extract::Query;
 use axum::Router;
 use axum::{routing::get, Extension};
-use oauth_axum::providers::microsoft::MicrosoftProvider;
+use oauth_axum::flow::PendingAuthorization;
+use oauth_axum::providers::microsoft::{MicrosoftProvider, Tenant};
 use oauth_axum::{CustomProvider, OAuthClient};
 
 use crate::utils::memory_db_util::AxumState;
@@ -35,17 +36,20 @@ async fn main() {
 
 fn get_client() -> CustomProvider {
     MicrosoftProvider::new(
-        std::env::var("MICROSOFT_TENANT_ID").expect("MICROSOFT_TENANT_ID must be set"),
+        Tenant::from(
+            std::env::var("MICROSOFT_TENANT_ID").expect("MICROSOFT_TENANT_ID must be set"),
+        ),
         std::env::var("MICROSOFT_CLIENT_ID").expect("MICROSOFT_CLIENT_ID must be set"),
         std::env::var("MICROSOFT_SECRET").expect("MICROSOFT_SECRET must be set"),
         "http://localhost:3000/api/v1/microsoft/callback".to_string(),
     )
+    .expect("MICROSOFT_TENANT_ID must be a tenant id or domain")
 }
 
 pub async fn create_url(Extension(state): Extension<Arc<AxumState>>) -> String {
     let state_oauth = get_client()
         .generate_url(Vec::from(["User.Read".to_string()]), |state_e| async move {
-            state.set(state_e.state, state_e.verifier);
+            state.set(state_e.state().to_string(), state_e);
         })
         .await
         .ok()
@@ -53,7 +57,7 @@ pub async fn create_url(Extension(state): Extension<Arc<AxumState>>) -> String {
         .state
         .unwrap();
 
-    state_oauth.url_generated.unwrap()
+    state_oauth.url_generated().unwrap().to_string()
 }
 
 pub async fn callback(
@@ -62,9 +66,11 @@ pub async fn callback(
 ) -> String {
     println!("{:?}", state.clone().get_all_items());
     let item = state.get(queries.state.clone());
-    get_client()
-        .generate_token(queries.code, item.unwrap())
+    PendingAuthorization::resume(get_client(), item.unwrap())
+        .unwrap()
+        .exchange(queries.code)
         .await
         .ok()
         .unwrap()
+        .access_token
 }
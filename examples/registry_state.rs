@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use axum::extract::{FromRef, Query, State};
+use axum::routing::get;
+use axum::Router;
+use oauth_axum::flow::PendingAuthorization;
+use oauth_axum::registry::{ProviderRegistry, SelectedProvider};
+use oauth_axum::store::{MemoryStateStore, StateStore};
+use oauth_axum::OAuthClient;
+
+#[derive(Clone, serde::Deserialize)]
+pub struct QueryAxumCallback {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Clone, FromRef)]
+struct AppState {
+    registry: Arc<ProviderRegistry>,
+    store: Arc<dyn StateStore>,
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv::from_filename("examples/.env").ok();
+    println!("Starting server...");
+
+    let registry = ProviderRegistry::from_config(
+        r#"{
+            "providers": {
+                "github": {
+                    "kind": "github",
+                    "client_id": "${GITHUB_CLIENT_ID}",
+                    "client_secret": "${GITHUB_SECRET}",
+                    "redirect_url": "http://localhost:3000/api/v1/github/callback",
+                    "scopes": ["read:user"]
+                }
+            }
+        }"#,
+    )
+    .expect("invalid providers config");
+    let state = AppState {
+        registry: Arc::new(registry),
+        store: Arc::new(MemoryStateStore::default()),
+    };
+    let app = Router::new()
+        .route("/login/:provider", get(create_url))
+        .route("/api/v1/:provider/callback", get(callback))
+        .with_state(state);
+
+    println!("🚀 Server started successfully");
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000")
+        .await
+        .unwrap();
+    axum::serve(listener, app).await.unwrap();
+}
+
+pub async fn create_url(
+    selected: SelectedProvider,
+    State(store): State<Arc<dyn StateStore>>,
+) -> String {
+    let state_oauth = selected
+        .provider
+        .generate_url(selected.scopes, |state_e| async move {
+            store.save(&selected.name, state_e).await.unwrap();
+        })
+        .await
+        .ok()
+        .unwrap()
+        .state
+        .unwrap();
+
+    state_oauth.url_generated().unwrap().to_string()
+}
+
+pub async fn callback(
+    selected: SelectedProvider,
+    State(store): State<Arc<dyn StateStore>>,
+    Query(queries): Query<QueryAxumCallback>,
+) -> String {
+    let item = store.take(&queries.state).await.unwrap().unwrap();
+    PendingAuthorization::resume(selected.provider, item.state)
+        .unwrap()
+        .exchange(queries.code)
+        .await
+        .ok()
+        .unwrap()
+        .access_token
+}
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use axum::extract::Query;
 use axum::Router;
 use axum::{routing::get, Extension};
+use oauth_axum::flow::PendingAuthorization;
 use oauth_axum::providers::spotify::SpotifyProvider;
 use oauth_axum::{CustomProvider, OAuthClient};
 
@@ -49,7 +50,7 @@ pub async fn create_url(Extension(state): Extension<Arc<AxumState>>) -> String {
                 "user-read-private".to_string(),
             ]),
             |state_e| async move {
-                state.set(state_e.state, state_e.verifier);
+                state.set(state_e.state().to_string(), state_e);
             },
         )
         .await
@@ -58,7 +59,7 @@ pub async fn create_url(Extension(state): Extension<Arc<AxumState>>) -> String {
         .state
         .unwrap();
 
-    state_oauth.url_generated.unwrap()
+    state_oauth.url_generated().unwrap().to_string()
 }
 
 pub async fn callback(
@@ -67,9 +68,11 @@ pub async fn callback(
 ) -> String {
     println!("{:?}", state.clone().get_all_items());
     let item = state.get(queries.state.clone());
-    get_client()
-        .generate_token(queries.code, item.unwrap())
+    PendingAuthorization::resume(get_client(), item.unwrap())
+        .unwrap()
+        .exchange(queries.code)
         .await
         .ok()
         .unwrap()
+        .access_token
 }
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use axum::extract::Query;
 use axum::Router;
 use axum::{routing::get, Extension};
+use oauth_axum::flow::PendingAuthorization;
 use oauth_axum::providers::twitter::TwitterProvider;
 use oauth_axum::{CustomProvider, OAuthClient};
 
@@ -46,7 +47,7 @@ pub async fn create_url(Extension(state): Extension<Arc<AxumState>>) -> String {
         .generate_url(
             Vec::from(["users.read".to_string()]),
             |state_e| async move {
-                state.set(state_e.state, state_e.verifier);
+                state.set(state_e.state().to_string(), state_e);
             },
         )
         .await
@@ -55,7 +56,7 @@ pub async fn create_url(Extension(state): Extension<Arc<AxumState>>) -> String {
         .state
         .unwrap();
 
-    state_oauth.url_generated.unwrap()
+    state_oauth.url_generated().unwrap().to_string()
 }
 
 pub async fn callback(
@@ -64,9 +65,11 @@ pub async fn callback(
 ) -> String {
     println!("{:?}", state.clone().get_all_items());
     let item = state.get(queries.state.clone());
-    get_client()
-        .generate_token(queries.code, item.unwrap())
+    PendingAuthorization::resume(get_client(), item.unwrap())
+        .unwrap()
+        .exchange(queries.code)
         .await
         .ok()
         .unwrap()
+        .access_token
 }
@@ -5,6 +5,8 @@ use std::{
     time::SystemTime,
 };
 
+use oauth_axum::StateAuth;
+
 #[derive(Clone)]
 pub struct AxumState {
     db: Arc<Mutex<HashMap<String, ItemOauthAxum>>>,
@@ -12,7 +14,7 @@ pub struct AxumState {
 
 #[derive(Clone, Debug)]
 pub struct ItemOauthAxum {
-    pub verifier: String,
+    pub state: StateAuth,
     pub created_at: SystemTime,
 }
 
@@ -31,21 +33,22 @@ impl AxumState {
         }
     }
 
-    pub fn get(&self, key: String) -> Option<String> {
+    #[allow(clippy::manual_map)]
+    pub fn get(&self, key: String) -> Option<StateAuth> {
         let db = self.db.lock().unwrap();
         if let Some(item) = db.get(&key) {
-            Some(item.verifier.clone())
+            Some(item.state.clone())
         } else {
             None
         }
     }
 
-    pub fn set(&self, key: String, value: String) {
+    pub fn set(&self, key: String, value: StateAuth) {
         let mut db = self.db.lock().unwrap();
         db.insert(
             key,
             ItemOauthAxum {
-                verifier: value,
+                state: value,
                 created_at: SystemTime::now(),
             },
         );
@@ -0,0 +1,138 @@
+//! Parameters received in the callback
+
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::redact::redacted;
+
+/// How the provider returns the parameters to the callback
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResponseMode {
+    /// In the query of the redirect
+    #[default]
+    Query,
+    /// In a form posted to the callback, the callback route needs to accept POST
+    FormPost,
+    /// In the fragment of the redirect, only the browser can read it, so the front end needs to send it to the backend
+    Fragment,
+}
+
+impl ResponseMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResponseMode::Query => "query",
+            ResponseMode::FormPost => "form_post",
+            ResponseMode::Fragment => "fragment",
+        }
+    }
+}
+
+/// Parameters sent by the provider to the redirect URL.
+/// With the `axum` feature it is an extractor that reads the query in GET and the form in POST (`form_post`)
+#[derive(Clone, Default, Deserialize)]
+pub struct CallbackParams {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// Returned in the hybrid flow (`code id_token`)
+    pub id_token: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+/// The code and the `id_token` are masked, so a callback logged with `{:?}` can't be used to login
+impl fmt::Debug for CallbackParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackParams")
+            .field("code", &redacted(&self.code))
+            .field("state", &self.state)
+            .field("id_token", &redacted(&self.id_token))
+            .field("error", &self.error)
+            .field("error_description", &self.error_description)
+            .finish()
+    }
+}
+
+/// Result of a silent authentication (`prompt=none`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SilentAuthResult {
+    /// The user still has a session in the provider, exchange the code as usual
+    Authorized { code: String, state: String },
+    /// The user needs to login again
+    LoginRequired,
+    /// The user has a session, but needs to interact with the provider (select account, MFA, ...)
+    InteractionRequired,
+    /// The user needs to approve the scopes again
+    ConsentRequired,
+    /// Any other error returned by the provider
+    Failed {
+        error: String,
+        description: Option<String>,
+    },
+}
+
+impl SilentAuthResult {
+    /// True when the app needs to fall back to the normal login, with the user interaction
+    pub fn needs_interaction(&self) -> bool {
+        !matches!(self, SilentAuthResult::Authorized { .. })
+    }
+}
+
+impl CallbackParams {
+    /// Map the callback of a silent authentication to a typed result
+    pub fn silent_result(&self) -> SilentAuthResult {
+        if let Some(error) = &self.error {
+            return match error.as_str() {
+                "login_required" => SilentAuthResult::LoginRequired,
+                "interaction_required" | "account_selection_required" => {
+                    SilentAuthResult::InteractionRequired
+                }
+                "consent_required" => SilentAuthResult::ConsentRequired,
+                _ => SilentAuthResult::Failed {
+                    error: error.clone(),
+                    description: self.error_description.clone(),
+                },
+            };
+        }
+        match (&self.code, &self.state) {
+            (Some(code), Some(state)) => SilentAuthResult::Authorized {
+                code: code.clone(),
+                state: state.clone(),
+            },
+            _ => SilentAuthResult::Failed {
+                error: "invalid_request".to_string(),
+                description: Some("missing code or state".to_string()),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "axum")]
+mod extract {
+    use async_trait::async_trait;
+    use axum::extract::{Form, FromRequest, Query, Request};
+    use axum::http::{Method, StatusCode};
+
+    use super::CallbackParams;
+
+    #[async_trait]
+    impl<S> FromRequest<S> for CallbackParams
+    where
+        S: Send + Sync,
+    {
+        type Rejection = StatusCode;
+
+        async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+            if request.method() == Method::POST {
+                let Form(params) = Form::<CallbackParams>::from_request(request, state)
+                    .await
+                    .map_err(|_| StatusCode::BAD_REQUEST)?;
+                Ok(params)
+            } else {
+                let Query(params) = Query::<CallbackParams>::try_from_uri(request.uri())
+                    .map_err(|_| StatusCode::BAD_REQUEST)?;
+                Ok(params)
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Encrypted-cookie PKCE flow
+//!
+//! Small apps that run without a DB can keep the state and verifier in a [`PrivateCookieJar`]
+//! instead of saving them in memory or in a table. The cookies are encrypted with the app
+//! [`Key`](axum_extra::extract::cookie::Key), so the user can't read or change the verifier.
+//!
+//! The jar returned by the helpers needs to be sent back in the response, otherwise the cookies
+//! will not be set/removed in the browser.
+
+use axum::response::Redirect;
+use axum_extra::extract::cookie::{Cookie, PrivateCookieJar, SameSite};
+
+use crate::callback::ResponseMode;
+use crate::error::OauthError;
+use crate::flow::PendingAuthorization;
+use crate::scopes::Scopes;
+use crate::{CustomProvider, OAuthClient, StateAuth};
+
+/// Name of the cookie that stores the state generated in the first step
+pub const STATE_COOKIE: &str = "oauth_axum_state";
+/// Name of the cookie that stores the verifier generated in the first step
+pub const VERIFIER_COOKIE: &str = "oauth_axum_verifier";
+
+/// The `form_post` callback is a cross site POST, the browser only sends the cookies with `SameSite=None`
+fn build_cookie(name: &'static str, value: String, response_mode: ResponseMode) -> Cookie<'static> {
+    let same_site = match response_mode {
+        ResponseMode::FormPost => SameSite::None,
+        _ => SameSite::Lax,
+    };
+    Cookie::build((name, value))
+        .path("/")
+        .http_only(true)
+        .secure(true)
+        .same_site(same_site)
+        .build()
+}
+
+/// Generate the URL and save the state and verifier in the encrypted jar
+/// # Arguments
+/// * `provider` - CustomProvider - The provider used to generate the URL
+/// * `scopes` - impl Into<Scopes> - The scopes that you want to access in the provider
+/// * `jar` - PrivateCookieJar - The jar from the request
+/// # Return
+/// The jar with the cookies and the redirect to the provider, both need to be returned by the handler
+pub async fn generate_url_with_cookies(
+    provider: CustomProvider,
+    scopes: impl Into<Scopes> + Send,
+    jar: PrivateCookieJar,
+) -> Result<(PrivateCookieJar, Redirect), OauthError> {
+    let response_mode = provider.response_mode.unwrap_or_default();
+    let state = provider
+        .generate_url(scopes, |_| async {})
+        .await?
+        .get_state()
+        .ok_or(OauthError::AuthUrlCreationFailed)?;
+    let url = state
+        .url_generated
+        .ok_or(OauthError::AuthUrlCreationFailed)?;
+
+    let jar = jar
+        .add(build_cookie(STATE_COOKIE, state.state, response_mode))
+        .add(build_cookie(VERIFIER_COOKIE, state.verifier, response_mode));
+
+    Ok((jar, Redirect::to(&url)))
+}
+
+/// Remove the state and verifier cookies, so the same login can't be finished twice
+fn remove_cookies(jar: PrivateCookieJar) -> PrivateCookieJar {
+    jar.remove(Cookie::build(STATE_COOKIE).path("/"))
+        .remove(Cookie::build(VERIFIER_COOKIE).path("/"))
+}
+
+/// Read the verifier saved in the jar, validating the state returned by the provider
+fn saved_verifier(jar: &PrivateCookieJar, state: &str) -> Result<String, OauthError> {
+    let saved_state = jar.get(STATE_COOKIE).ok_or(OauthError::StateMismatch)?;
+    if saved_state.value() != state {
+        return Err(OauthError::StateMismatch);
+    }
+    Ok(jar
+        .get(VERIFIER_COOKIE)
+        .ok_or(OauthError::VerifierNotFound)?
+        .value()
+        .to_string())
+}
+
+/// Get the verifier saved in the jar, validating the state returned by the provider
+/// # Arguments
+/// * `jar` - PrivateCookieJar - The jar from the callback request
+/// * `state` - &str - The state query parameter returned by the provider
+/// # Return
+/// The jar without the oauth cookies and the verifier
+pub fn take_verifier_from_cookies(
+    jar: PrivateCookieJar,
+    state: &str,
+) -> Result<(PrivateCookieJar, String), OauthError> {
+    let verifier = saved_verifier(&jar, state)?;
+    Ok((remove_cookies(jar), verifier))
+}
+
+/// Generate the token using the verifier saved in the jar
+/// # Arguments
+/// * `provider` - &CustomProvider - The provider used to generate the URL
+/// * `jar` - PrivateCookieJar - The jar from the callback request
+/// * `code` - String - The code query parameter returned by the provider
+/// * `state` - &str - The state query parameter returned by the provider
+/// # Return
+/// The jar without the oauth cookies and the token generated. The cookies are removed also when it fails,
+/// so the jar needs to be returned in the error response too
+pub async fn generate_token_from_cookies(
+    provider: &CustomProvider,
+    jar: PrivateCookieJar,
+    code: String,
+    state: &str,
+) -> (PrivateCookieJar, Result<String, OauthError>) {
+    let token = exchange(provider, &jar, code, state).await;
+    (remove_cookies(jar), token)
+}
+
+async fn exchange(
+    provider: &CustomProvider,
+    jar: &PrivateCookieJar,
+    code: String,
+    state: &str,
+) -> Result<String, OauthError> {
+    let pending = PendingAuthorization::resume(
+        provider.clone(),
+        StateAuth::restore(state.to_string(), saved_verifier(jar, state)?)?,
+    )?;
+    Ok(pending.exchange(code).await?.access_token)
+}
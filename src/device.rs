@@ -0,0 +1,222 @@
+//! Device authorization flow
+//!
+//! [RFC 8628](https://tools.ietf.org/html/rfc8628) flow for devices without a browser. The lib polls the
+//! provider in background and keeps the status of every session by the user code, so a web UI can show
+//! the progress while the user approves the device.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use oauth2::basic::BasicErrorResponseType;
+use oauth2::devicecode::{DeviceCodeErrorResponseType, StandardDeviceAuthorizationResponse};
+use oauth2::{RequestTokenError, Scope};
+use serde::Serialize;
+
+use crate::error::OauthError;
+use crate::scopes::Scopes;
+use crate::task::BackgroundTask;
+use crate::token::TokenSet;
+use crate::{CustomProvider, OAuthClient};
+
+/// Status of a device flow session
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceStatus {
+    Pending,
+    Complete,
+    Denied,
+    Expired,
+    Failed,
+}
+
+/// Data that needs to be shown to the user to approve the device
+#[derive(Clone, Debug, Serialize)]
+pub struct DeviceAuthorization {
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+}
+
+struct DeviceSession {
+    status: DeviceStatus,
+    token: Option<TokenSet>,
+    /// The session is removed after the device code expires
+    expires_at: Instant,
+    /// Polling task, it stops when the session is removed
+    task: Option<BackgroundTask>,
+}
+
+/// Sessions of the device flow, keyed by the user code
+#[derive(Clone, Default)]
+pub struct DeviceSessions {
+    items: Arc<Mutex<HashMap<String, DeviceSession>>>,
+}
+
+impl DeviceSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lock the sessions, removing the ones whose device code expired
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, DeviceSession>> {
+        let mut items = self
+            .items
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        items.retain(|_, session| session.expires_at > now);
+        items
+    }
+
+    fn finish(&self, user_code: &str, status: DeviceStatus, token: Option<TokenSet>) {
+        if let Some(session) = self.lock().get_mut(user_code) {
+            session.status = status;
+            session.token = token;
+        }
+    }
+
+    /// Request the device code and poll the provider in background until the user approves or denies.
+    /// The polling task belongs to the session, it stops when the session is removed or the device code expires
+    /// # Arguments
+    /// * `provider` - CustomProvider - The provider with the device authorization url
+    /// * `scopes` - impl Into<Scopes> - The scopes that you want to access in the provider
+    /// # Return
+    /// The data to show to the user
+    pub async fn start(
+        &self,
+        provider: CustomProvider,
+        scopes: impl Into<Scopes>,
+    ) -> Result<DeviceAuthorization, OauthError> {
+        let scopes: Scopes = scopes.into();
+        scopes.validate(provider.scope_delimiter)?;
+
+        let mut request = provider
+            .get_client()?
+            .exchange_device_code()
+            .map_err(|_| OauthError::DeviceAuthorizationFailed)?;
+        if !scopes.is_empty() {
+            request = request.add_scope(Scope::new(scopes.join(provider.scope_delimiter)));
+        }
+        let details: StandardDeviceAuthorizationResponse = request
+            .request_async(|request| provider.send(request))
+            .await
+            .map_err(|_| OauthError::DeviceAuthorizationFailed)?;
+
+        let authorization = DeviceAuthorization {
+            user_code: details.user_code().secret().to_string(),
+            verification_uri: details.verification_uri().to_string(),
+            verification_uri_complete: details
+                .verification_uri_complete()
+                .map(|uri| uri.secret().to_string()),
+            expires_in: details.expires_in().as_secs(),
+        };
+        self.lock().insert(
+            authorization.user_code.clone(),
+            DeviceSession {
+                status: DeviceStatus::Pending,
+                token: None,
+                expires_at: Instant::now() + details.expires_in(),
+                task: None,
+            },
+        );
+
+        let sessions = self.clone();
+        let user_code = authorization.user_code.clone();
+        let task = BackgroundTask::spawn(|mut signal| async move {
+            let client = match provider.get_client() {
+                Ok(client) => client,
+                Err(_) => return sessions.finish(&user_code, DeviceStatus::Failed, None),
+            };
+            let polling = client.exchange_device_access_token(&details).request_async(
+                |request| provider.send(request),
+                tokio::time::sleep,
+                None,
+            );
+            tokio::select! {
+                result = polling => match result {
+                    Ok(token) => sessions.finish(&user_code, DeviceStatus::Complete, Some(token.into())),
+                    Err(RequestTokenError::ServerResponse(response)) => {
+                        let status = match response.error() {
+                            DeviceCodeErrorResponseType::AccessDenied => DeviceStatus::Denied,
+                            DeviceCodeErrorResponseType::ExpiredToken => DeviceStatus::Expired,
+                            DeviceCodeErrorResponseType::Basic(BasicErrorResponseType::InvalidGrant) => {
+                                DeviceStatus::Expired
+                            }
+                            _ => DeviceStatus::Failed,
+                        };
+                        sessions.finish(&user_code, status, None)
+                    }
+                    Err(_) => sessions.finish(&user_code, DeviceStatus::Failed, None),
+                },
+                _ = signal.wait() => {}
+            }
+        });
+
+        if let Some(session) = self.lock().get_mut(&authorization.user_code) {
+            session.task = Some(task);
+        }
+
+        Ok(authorization)
+    }
+
+    /// Get the status of the session, None when the user code is unknown
+    pub fn status(&self, user_code: &str) -> Option<DeviceStatus> {
+        self.lock().get(user_code).map(|session| session.status)
+    }
+
+    /// Remove the session and get the token, when the session is complete
+    pub fn take_token(&self, user_code: &str) -> Option<TokenSet> {
+        let mut items = self.lock();
+        if items.get(user_code)?.status != DeviceStatus::Complete {
+            return None;
+        }
+        items.remove(user_code)?.token
+    }
+
+    /// Remove the session, used after the UI shows the final status. A pending session stops polling
+    pub fn remove(&self, user_code: &str) {
+        self.lock().remove(user_code);
+    }
+}
+
+#[cfg(feature = "axum")]
+#[derive(Serialize)]
+struct StatusResponse {
+    user_code: String,
+    status: DeviceStatus,
+}
+
+/// Router with the status endpoint, `GET /:user_code` returns `{"user_code": "...", "status": "pending"}`
+/// or 404 when the session is unknown
+/// ```ignore
+/// let app = Router::new().nest("/device", device::status_router(sessions.clone()));
+/// ```
+#[cfg(feature = "axum")]
+pub fn status_router(sessions: DeviceSessions) -> axum::Router {
+    status_routes().with_state(sessions)
+}
+
+/// Same route of `status_router`, reading the sessions from the app state with `FromRef`
+#[cfg(feature = "axum")]
+pub fn status_routes<S>() -> axum::Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    DeviceSessions: axum::extract::FromRef<S>,
+{
+    use axum::extract::{Path, State};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Json;
+
+    async fn status(
+        State(sessions): State<DeviceSessions>,
+        Path(user_code): Path<String>,
+    ) -> Result<Json<StatusResponse>, StatusCode> {
+        let status = sessions.status(&user_code).ok_or(StatusCode::NOT_FOUND)?;
+        Ok(Json(StatusResponse { user_code, status }))
+    }
+
+    axum::Router::new().route("/:user_code", get(status))
+}
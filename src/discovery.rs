@@ -0,0 +1,221 @@
+//! OpenID Connect discovery
+//!
+//! Fetch the provider metadata from `{issuer}/.well-known/openid-configuration`. The [`DiscoveryCache`] keeps
+//! the metadata for a TTL and revalidates it with the ETag, so the document is not fetched in every provider creation.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::OauthError;
+use crate::{http, CustomProvider};
+
+/// Metadata of the provider, only the fields used by the lib are typed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: Option<String>,
+    pub userinfo_endpoint: Option<String>,
+    pub jwks_uri: Option<String>,
+    pub end_session_endpoint: Option<String>,
+    pub device_authorization_endpoint: Option<String>,
+    pub registration_endpoint: Option<String>,
+    pub scopes_supported: Option<Vec<String>>,
+    /// Fields that are not typed
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl ProviderMetadata {
+    /// Fetch the metadata of the issuer, without cache
+    pub async fn discover(issuer: &str) -> Result<Self, OauthError> {
+        fetch(issuer, None, same_issuer)
+            .await?
+            .map(|(metadata, _)| metadata)
+            .ok_or(OauthError::DiscoveryFailed)
+    }
+
+    /// Same as `discover`, but the templated issuer of the multi-tenant documents is accepted.
+    /// Only used to compare the endpoints, the tokens are verified with the exact issuer
+    pub(crate) async fn discover_template(issuer: &str) -> Result<Self, OauthError> {
+        fetch(issuer, None, ProviderMetadata::matches_issuer)
+            .await?
+            .map(|(metadata, _)| metadata)
+            .ok_or(OauthError::DiscoveryFailed)
+    }
+
+    /// Check that the metadata is from the issuer, used by the drift check. The multi-tenant documents, like
+    /// the `common` one of Microsoft, publish a templated issuer (`https://login.microsoftonline.com/{tenantid}/v2.0`),
+    /// where a `{...}` segment matches any segment of the issuer
+    pub fn matches_issuer(&self, issuer: &str) -> bool {
+        let published = self.issuer.trim_end_matches('/').split('/');
+        let expected = issuer.trim_end_matches('/').split('/');
+        published.clone().count() == expected.clone().count()
+            && published.zip(expected).all(|(published, expected)| {
+                published == expected
+                    || (published.starts_with('{')
+                        && published.ends_with('}')
+                        && !expected.is_empty())
+            })
+    }
+
+    /// Create the provider with the endpoints of the metadata
+    /// # Arguments
+    /// * `client_id` - String - The client id of the application
+    /// * `client_secret` - String - The client secret of the application
+    /// * `redirect_url` - String - The redirect url of the application
+    pub fn to_provider(
+        &self,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        let provider = CustomProvider::new(
+            self.authorization_endpoint.clone(),
+            self.token_endpoint
+                .clone()
+                .ok_or(OauthError::TokenUrlCreationFailed)?,
+            client_id,
+            client_secret,
+            redirect_url,
+        );
+        Ok(match &self.device_authorization_endpoint {
+            Some(device_authorization_endpoint) => {
+                provider.set_device_authorization_url(device_authorization_endpoint)
+            }
+            None => provider,
+        })
+    }
+}
+
+/// The spec requires the same issuer, otherwise the document can be from another provider
+fn same_issuer(metadata: &ProviderMetadata, issuer: &str) -> bool {
+    metadata.issuer.trim_end_matches('/') == issuer.trim_end_matches('/')
+}
+
+/// Fetch the metadata and the ETag, None when the document was not modified since the ETag
+async fn fetch(
+    issuer: &str,
+    etag: Option<&str>,
+    issuer_matches: fn(&ProviderMetadata, &str) -> bool,
+) -> Result<Option<(ProviderMetadata, Option<String>)>, OauthError> {
+    let url = format!(
+        "{}/.well-known/openid-configuration",
+        issuer.trim_end_matches('/')
+    );
+    let mut request = http::client()?.get(url);
+    if let Some(etag) = etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    let response = http::execute("oauth.discovery", request)
+        .await
+        .map_err(|_| OauthError::DiscoveryFailed)?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(OauthError::DiscoveryFailed);
+    }
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_string);
+    let metadata = response
+        .json::<ProviderMetadata>()
+        .await
+        .map_err(|_| OauthError::DiscoveryFailed)?;
+    if !issuer_matches(&metadata, issuer) {
+        return Err(OauthError::DiscoveryFailed);
+    }
+    Ok(Some((metadata, etag)))
+}
+
+struct CachedMetadata {
+    metadata: ProviderMetadata,
+    etag: Option<String>,
+    fetched_at: Instant,
+}
+
+/// Cache of the metadata by issuer, clones share the same cache
+#[derive(Clone)]
+pub struct DiscoveryCache {
+    ttl: Duration,
+    items: Arc<Mutex<HashMap<String, CachedMetadata>>>,
+}
+
+impl Default for DiscoveryCache {
+    fn default() -> Self {
+        DiscoveryCache::new(Duration::from_secs(3600))
+    }
+}
+
+impl DiscoveryCache {
+    /// Cache that revalidates the metadata after the TTL
+    pub fn new(ttl: Duration) -> Self {
+        DiscoveryCache {
+            ttl,
+            items: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, CachedMetadata>> {
+        self.items
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Get the metadata of the issuer, fetching it when it is not in the cache or the TTL expired
+    pub async fn get(&self, issuer: &str) -> Result<ProviderMetadata, OauthError> {
+        let etag = match self.lock().get(issuer) {
+            Some(cached) if cached.fetched_at.elapsed() < self.ttl => {
+                return Ok(cached.metadata.clone())
+            }
+            Some(cached) => cached.etag.clone(),
+            None => None,
+        };
+        self.refresh(issuer, etag).await
+    }
+
+    /// Fetch the metadata again ignoring the TTL and the ETag, used when the provider changed the endpoints or keys
+    pub async fn force_refresh(&self, issuer: &str) -> Result<ProviderMetadata, OauthError> {
+        self.refresh(issuer, None).await
+    }
+
+    /// Remove the metadata of the issuer from the cache
+    pub fn invalidate(&self, issuer: &str) {
+        self.lock().remove(issuer);
+    }
+
+    async fn refresh(
+        &self,
+        issuer: &str,
+        etag: Option<String>,
+    ) -> Result<ProviderMetadata, OauthError> {
+        match fetch(issuer, etag.as_deref(), same_issuer).await? {
+            Some((metadata, etag)) => {
+                self.lock().insert(
+                    issuer.to_string(),
+                    CachedMetadata {
+                        metadata: metadata.clone(),
+                        etag,
+                        fetched_at: Instant::now(),
+                    },
+                );
+                Ok(metadata)
+            }
+            None => {
+                let mut items = self.lock();
+                let cached = items.get_mut(issuer).ok_or(OauthError::DiscoveryFailed)?;
+                cached.fetched_at = Instant::now();
+                Ok(cached.metadata.clone())
+            }
+        }
+    }
+}
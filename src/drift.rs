@@ -0,0 +1,138 @@
+//! Endpoint drift detection
+//!
+//! The endpoints of the presets are hard-coded, so a change in the provider only shows up when the logins fail.
+//! The presets with a discovery document (`ProviderPreset::ISSUER`) can be checked against it at startup
+//! or periodically with `spawn_drift_check`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::discovery::ProviderMetadata;
+use crate::error::OauthError;
+use crate::providers::adobe::AdobeProvider;
+use crate::providers::google::GoogleProvider;
+use crate::providers::jumpcloud::JumpCloudProvider;
+use crate::providers::microsoft::MicrosoftProvider;
+use crate::providers::salesforce::SalesforceProvider;
+use crate::providers::zoho::ZohoProvider;
+use crate::providers::ProviderPreset;
+use crate::task::BackgroundTask;
+
+/// Endpoint of a preset that is different from the one published by the provider
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndpointDrift {
+    /// Name of the preset
+    pub preset: &'static str,
+    /// `authorization_endpoint` or `token_endpoint`
+    pub endpoint: &'static str,
+    /// URL used by the preset
+    pub expected: String,
+    /// URL in the discovery document
+    pub published: String,
+}
+
+/// Result of the check of the built-in presets
+#[derive(Clone, Debug, Default)]
+pub struct DriftReport {
+    pub drifts: Vec<EndpointDrift>,
+    /// Presets whose discovery document could not be fetched
+    pub unreachable: Vec<&'static str>,
+}
+
+impl DriftReport {
+    /// Return true when every preset was checked and no endpoint changed
+    pub fn is_clean(&self) -> bool {
+        self.drifts.is_empty() && self.unreachable.is_empty()
+    }
+}
+
+fn same_url(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+/// Compare the endpoints of the preset, with the default config, with its discovery document
+/// # Return
+/// The endpoints that changed, empty when the preset has no `ISSUER`
+pub async fn check_preset<P>() -> Result<Vec<EndpointDrift>, OauthError>
+where
+    P: ProviderPreset,
+    P::Config: Default,
+{
+    let Some(issuer) = P::ISSUER else {
+        return Ok(Vec::new());
+    };
+    compare_endpoints::<P>(ProviderMetadata::discover_template(issuer).await?)
+}
+
+/// Compare the endpoints of the preset, with the default config, with the discovery document
+/// # Arguments
+/// * `metadata` - ProviderMetadata - The discovery document of the provider
+/// # Return
+/// The endpoints that changed
+pub fn compare_endpoints<P>(metadata: ProviderMetadata) -> Result<Vec<EndpointDrift>, OauthError>
+where
+    P: ProviderPreset,
+    P::Config: Default,
+{
+    let (auth_url, token_url) = P::endpoints(&P::Config::default())?;
+    let mut drifts = Vec::new();
+    if !same_url(&auth_url, &metadata.authorization_endpoint) {
+        drifts.push(EndpointDrift {
+            preset: P::NAME,
+            endpoint: "authorization_endpoint",
+            expected: auth_url,
+            published: metadata.authorization_endpoint,
+        });
+    }
+    if let Some(token_endpoint) = metadata.token_endpoint {
+        if !same_url(&token_url, &token_endpoint) {
+            drifts.push(EndpointDrift {
+                preset: P::NAME,
+                endpoint: "token_endpoint",
+                expected: token_url,
+                published: token_endpoint,
+            });
+        }
+    }
+    Ok(drifts)
+}
+
+async fn check_into<P>(report: &mut DriftReport)
+where
+    P: ProviderPreset,
+    P::Config: Default,
+{
+    match check_preset::<P>().await {
+        Ok(drifts) => report.drifts.extend(drifts),
+        Err(_) => report.unreachable.push(P::NAME),
+    }
+}
+
+/// Check every built-in preset that has a discovery document
+pub async fn check_builtin_presets() -> DriftReport {
+    let mut report = DriftReport::default();
+    check_into::<AdobeProvider>(&mut report).await;
+    check_into::<GoogleProvider>(&mut report).await;
+    check_into::<JumpCloudProvider>(&mut report).await;
+    check_into::<MicrosoftProvider>(&mut report).await;
+    check_into::<SalesforceProvider>(&mut report).await;
+    check_into::<ZohoProvider>(&mut report).await;
+    report
+}
+
+/// Spawn a task that checks the built-in presets in every interval and calls the hook for every endpoint that changed.
+/// The presets that can't be reached are ignored, the next check tries again
+pub fn spawn_drift_check<F>(interval: Duration, on_drift: F) -> BackgroundTask
+where
+    F: Fn(&EndpointDrift) + Send + Sync + 'static,
+{
+    let on_drift = Arc::new(on_drift);
+    BackgroundTask::spawn_interval_async(interval, move || {
+        let on_drift = on_drift.clone();
+        async move {
+            for drift in check_builtin_presets().await.drifts {
+                on_drift(&drift);
+            }
+        }
+    })
+}
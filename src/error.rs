@@ -1,4 +1,40 @@
+#[derive(Clone, Debug)]
 pub enum OauthError {
     TokenRequestFailed,
     AuthUrlCreationFailed,
+    TokenUrlCreationFailed,
+    RedirectUrlCreationFailed,
+    StateMismatch,
+    VerifierNotFound,
+    HttpClientCreationFailed,
+    RegistrationFailed,
+    RegistrationManagementNotSupported,
+    InvalidScope,
+    UnsafeRedirectUrl,
+    InvalidPkceVerifierLength,
+    FingerprintMismatch,
+    StateStoreFailed,
+    DeviceAuthorizationUrlCreationFailed,
+    DeviceAuthorizationFailed,
+    InvalidIdToken,
+    AuthenticationTooOld,
+    AcrNotSatisfied,
+    EndSessionUrlCreationFailed,
+    DiscoveryFailed,
+    InvalidConfig,
+    UnsupportedFlow,
+    UserInfoFailed,
+    UserInfoNotSupported,
+    UserInfoSubjectMismatch,
+    InvalidIdTokenSignature,
+    JwksFetchFailed,
+    AuthorizationFailed,
+    UserUpsertFailed,
+    SessionIssueFailed,
+    InvalidAccessToken,
+    IntrospectionFailed,
+    ConfigReadFailed,
+    DuplicateCallback,
+    InvalidStateGenerator,
+    InvalidPkceVerifier,
 }
@@ -0,0 +1,111 @@
+//! Client fingerprint
+//!
+//! The fingerprint of the client that started the login is saved with the state, and compared in the callback,
+//! so a callback URL replayed from another client is rejected.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+use crate::error::OauthError;
+
+/// Attributes of the request used to create the fingerprint
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+/// Function used to create the fingerprint. The default is a SHA-256 of the IP prefix (/24 for IPv4, /48 for IPv6) and the User-Agent,
+/// the prefix is used so users changing the IP inside the same network are not rejected
+#[derive(Clone, Default)]
+pub enum Fingerprinter {
+    #[default]
+    Default,
+    Custom(Arc<dyn Fn(&ClientInfo) -> String + Send + Sync>),
+}
+
+impl Fingerprinter {
+    pub fn custom<F>(fingerprinter: F) -> Self
+    where
+        F: Fn(&ClientInfo) -> String + Send + Sync + 'static,
+    {
+        Fingerprinter::Custom(Arc::new(fingerprinter))
+    }
+
+    pub fn fingerprint(&self, client: &ClientInfo) -> String {
+        match self {
+            Fingerprinter::Default => {
+                let ip_prefix = match client.ip {
+                    Some(IpAddr::V4(ip)) => {
+                        let [a, b, c, _] = ip.octets();
+                        format!("{}.{}.{}", a, b, c)
+                    }
+                    Some(IpAddr::V6(ip)) => {
+                        let segments = ip.segments();
+                        format!("{:x}:{:x}:{:x}", segments[0], segments[1], segments[2])
+                    }
+                    None => String::new(),
+                };
+                let hash = Sha256::new()
+                    .chain_update(ip_prefix)
+                    .chain_update("|")
+                    .chain_update(client.user_agent.as_deref().unwrap_or_default())
+                    .finalize();
+                URL_SAFE_NO_PAD.encode(hash)
+            }
+            Fingerprinter::Custom(fingerprinter) => fingerprinter(client),
+        }
+    }
+
+    /// Compare the fingerprint saved with the state with the client of the callback, in constant time
+    pub fn verify(&self, expected: &str, client: &ClientInfo) -> Result<(), OauthError> {
+        ring::constant_time::verify_slices_are_equal(
+            self.fingerprint(client).as_bytes(),
+            expected.as_bytes(),
+        )
+        .map_err(|_| OauthError::FingerprintMismatch)
+    }
+}
+
+#[cfg(feature = "axum")]
+mod extract {
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+
+    use async_trait::async_trait;
+    use axum::extract::{ConnectInfo, FromRequestParts};
+    use axum::http::header::USER_AGENT;
+    use axum::http::request::Parts;
+
+    use super::ClientInfo;
+
+    /// The IP is only available when the app is served with `into_make_service_with_connect_info::<SocketAddr>()`
+    #[async_trait]
+    impl<S> FromRequestParts<S> for ClientInfo
+    where
+        S: Send + Sync,
+    {
+        type Rejection = Infallible;
+
+        async fn from_request_parts(
+            parts: &mut Parts,
+            _state: &S,
+        ) -> Result<Self, Self::Rejection> {
+            Ok(ClientInfo {
+                ip: parts
+                    .extensions
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|ConnectInfo(addr)| addr.ip()),
+                user_agent: parts
+                    .headers
+                    .get(USER_AGENT)
+                    .and_then(|value| value.to_str().ok())
+                    .map(String::from),
+            })
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Typed authorization flow
+//!
+//! `OAuthClient::authorize` returns a `PendingAuthorization`, the only type with `exchange`, so the code
+//! can't be exchanged without the verifier generated in the first step. Save it with `into_state` and
+//! load it in the callback with `PendingAuthorization::resume`:
+//!
+//! ```ignore
+//! let pending = provider.authorize(["read:user"]).await?;
+//! let url = pending.url().to_string();
+//! store.save("github", pending.into_state()).await?;
+//!
+//! // In the callback
+//! let stored = store.take_for("github", &state).await?.ok_or(OauthError::StateMismatch)?;
+//! let token = PendingAuthorization::resume(provider, stored.state)?.exchange(code).await?;
+//! ```
+
+use crate::callback::CallbackParams;
+use crate::error::OauthError;
+use crate::token::TokenSet;
+use crate::{CustomProvider, OAuthClient, StateAuth};
+
+/// Authorization started with `OAuthClient::authorize`, waiting for the code of the callback
+#[derive(Clone, Debug)]
+pub struct PendingAuthorization<P = CustomProvider> {
+    provider: P,
+    state: StateAuth,
+}
+
+impl<P: OAuthClient + Sync> PendingAuthorization<P> {
+    /// Load the authorization saved in the first step
+    /// # Arguments
+    /// * `provider` - P - The provider used to generate the URL
+    /// * `state` - StateAuth - The state saved with `into_state`
+    /// # Return
+    /// `VerifierNotFound` when the state has no state or verifier
+    pub fn resume(provider: P, state: StateAuth) -> Result<Self, OauthError> {
+        if state.state.is_empty() || state.verifier.is_empty() {
+            return Err(OauthError::VerifierNotFound);
+        }
+        Ok(PendingAuthorization { provider, state })
+    }
+
+    /// URL to redirect the user to the provider, empty when the state was saved without the URL
+    pub fn url(&self) -> &str {
+        self.state.url_generated.as_deref().unwrap_or_default()
+    }
+
+    /// The state sent in the URL, the key used to save the authorization
+    pub fn state(&self) -> &str {
+        &self.state.state
+    }
+
+    /// The state and verifier to save until the callback
+    pub fn into_state(self) -> StateAuth {
+        self.state
+    }
+
+    /// Exchange the code for the token with the verifier of this authorization
+    pub async fn exchange(self, code: String) -> Result<TokenSet, OauthError> {
+        self.provider.exchange_code(code, &self.state).await
+    }
+
+    /// Same of `exchange`, checking the state and the error of the callback first
+    pub async fn exchange_callback(self, callback: CallbackParams) -> Result<TokenSet, OauthError> {
+        if callback.state.as_deref() != Some(self.state.state.as_str()) {
+            return Err(OauthError::StateMismatch);
+        }
+        if callback.error.is_some() {
+            return Err(OauthError::AuthorizationFailed);
+        }
+        let code = callback.code.ok_or(OauthError::AuthorizationFailed)?;
+        self.exchange(code).await
+    }
+}
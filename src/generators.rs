@@ -0,0 +1,201 @@
+//! Generators of the random values used in the authorization URL
+
+use std::sync::Arc;
+
+use oauth2::{CsrfToken, PkceCodeChallenge, PkceCodeVerifier};
+use rand::Rng;
+
+use crate::error::OauthError;
+
+const ALPHANUMERIC: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const UNRESERVED: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~";
+const MIN_STATE_LENGTH: usize = 16;
+
+/// Function used to create the `state` value, the default is the same used by the oauth2 lib (16 random bytes in base64url)
+///
+/// A custom generator is useful when the state is also used as an ID, like a ULID that is the primary key of the table:
+/// ```
+/// use oauth_axum::generators::StateGenerator;
+///
+/// let generator = StateGenerator::custom(|| format!("login-{}", "some-unique-id"));
+/// ```
+#[derive(Clone, Default)]
+pub struct StateGenerator(StateKind);
+
+/// Kind of the generator, private so the random one is only created by the checked constructors
+#[derive(Clone, Default)]
+enum StateKind {
+    #[default]
+    Default,
+    /// Random value with the length and the characters from the alphabet
+    Random {
+        length: usize,
+        alphabet: Vec<char>,
+    },
+    Custom(Arc<dyn Fn() -> String + Send + Sync>),
+}
+
+impl StateGenerator {
+    /// Random alphanumeric value with the length, use at least 22 characters to keep the same entropy as the default
+    pub fn random(length: usize) -> Result<Self, OauthError> {
+        StateGenerator::with_alphabet(length, ALPHANUMERIC)
+    }
+
+    /// Random value with the length and the characters from the alphabet, the state needs at least 16 characters
+    /// and the alphabet at least 2 different characters to be hard to guess
+    pub fn with_alphabet(length: usize, alphabet: &str) -> Result<Self, OauthError> {
+        let mut alphabet: Vec<char> = alphabet.chars().collect();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        if length < MIN_STATE_LENGTH || alphabet.len() < 2 {
+            return Err(OauthError::InvalidStateGenerator);
+        }
+        Ok(StateGenerator(StateKind::Random { length, alphabet }))
+    }
+
+    /// Use your own function to create the state, it needs to be unique and hard to guess
+    pub fn custom<F>(generator: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        StateGenerator(StateKind::Custom(Arc::new(generator)))
+    }
+
+    /// Always the same state, only for tests that need to assert on the generated URL
+    #[cfg(feature = "test-utils")]
+    pub fn fixed(state: &str) -> Self {
+        let state = state.to_string();
+        StateGenerator::custom(move || state.clone())
+    }
+
+    /// Random alphanumeric state from a seeded RNG, the sequence is the same in every run of the tests
+    #[cfg(feature = "test-utils")]
+    pub fn seeded(length: usize, seed: u64) -> Self {
+        use rand::SeedableRng;
+
+        let alphabet: Vec<char> = ALPHANUMERIC.chars().collect();
+        let rng = std::sync::Mutex::new(rand::rngs::StdRng::seed_from_u64(seed));
+        StateGenerator::custom(move || {
+            let mut rng = rng.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            (0..length)
+                .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                .collect()
+        })
+    }
+
+    pub fn generate(&self) -> CsrfToken {
+        match &self.0 {
+            StateKind::Default => CsrfToken::new_random(),
+            StateKind::Random { length, alphabet } => {
+                CsrfToken::new(random_string(*length, alphabet))
+            }
+            StateKind::Custom(generator) => CsrfToken::new(generator()),
+        }
+    }
+}
+
+/// Characters used in the PKCE verifier, both are accepted by RFC 7636
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PkceCharset {
+    /// Letters, digits, "-", ".", "_" and "~"
+    #[default]
+    Unreserved,
+    /// Only letters and digits, for gateways that reject the symbols
+    Alphanumeric,
+}
+
+/// Generator of the PKCE verifier, the default is the same used by the oauth2 lib (43 characters in base64url)
+#[derive(Clone, Default)]
+pub struct PkceGenerator(PkceKind);
+
+/// Kind of the generator, private so the random one is only created with a length checked by `new`
+#[derive(Clone, Default)]
+enum PkceKind {
+    #[default]
+    Default,
+    Random {
+        length: usize,
+        charset: PkceCharset,
+    },
+    Custom(Arc<dyn Fn() -> String + Send + Sync>),
+}
+
+impl PkceGenerator {
+    /// Verifier with the length and the charset, RFC 7636 requires a length between 43 and 128
+    pub fn new(length: usize, charset: PkceCharset) -> Result<Self, OauthError> {
+        if !(43..=128).contains(&length) {
+            return Err(OauthError::InvalidPkceVerifierLength);
+        }
+        Ok(PkceGenerator(PkceKind::Random { length, charset }))
+    }
+
+    /// Use your own function to create the verifier, it needs 43 to 128 letters, digits, "-", ".", "_" or "~".
+    /// The verifier is validated in every `generate`, so a wrong one fails the URL generation
+    pub fn custom<F>(generator: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        PkceGenerator(PkceKind::Custom(Arc::new(generator)))
+    }
+
+    /// Always the same verifier, only for tests that need to exercise the callback
+    #[cfg(feature = "test-utils")]
+    pub fn fixed(verifier: &str) -> Result<Self, OauthError> {
+        validate_verifier(verifier)?;
+        let verifier = verifier.to_string();
+        Ok(PkceGenerator::custom(move || verifier.clone()))
+    }
+
+    /// Generate the challenge and the verifier
+    /// # Return
+    /// `InvalidPkceVerifierLength` or `InvalidPkceVerifier` when the custom function returns a verifier out of RFC 7636
+    pub fn generate(&self) -> Result<(PkceCodeChallenge, PkceCodeVerifier), OauthError> {
+        Ok(match &self.0 {
+            PkceKind::Default => PkceCodeChallenge::new_random_sha256(),
+            PkceKind::Random { length, charset } => {
+                let alphabet = match charset {
+                    PkceCharset::Unreserved => UNRESERVED,
+                    PkceCharset::Alphanumeric => ALPHANUMERIC,
+                };
+                let verifier = PkceCodeVerifier::new(random_string(
+                    *length,
+                    &alphabet.chars().collect::<Vec<char>>(),
+                ));
+                (
+                    PkceCodeChallenge::from_code_verifier_sha256(&verifier),
+                    verifier,
+                )
+            }
+            PkceKind::Custom(generator) => {
+                let verifier = generator();
+                validate_verifier(&verifier)?;
+                let verifier = PkceCodeVerifier::new(verifier);
+                (
+                    PkceCodeChallenge::from_code_verifier_sha256(&verifier),
+                    verifier,
+                )
+            }
+        })
+    }
+}
+
+/// Check that the verifier has 43 to 128 unreserved characters, as required by RFC 7636
+fn validate_verifier(verifier: &str) -> Result<(), OauthError> {
+    if !(43..=128).contains(&verifier.len()) {
+        return Err(OauthError::InvalidPkceVerifierLength);
+    }
+    if !verifier.chars().all(|c| UNRESERVED.contains(c)) {
+        return Err(OauthError::InvalidPkceVerifier);
+    }
+    Ok(())
+}
+
+pub(crate) fn random_string(length: usize, alphabet: &[char]) -> String {
+    if alphabet.is_empty() {
+        return String::new();
+    }
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+        .collect()
+}
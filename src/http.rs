@@ -0,0 +1,46 @@
+use std::sync::OnceLock;
+
+use reqwest::{Client, RequestBuilder, Response};
+
+use crate::error::OauthError;
+
+static CLIENT: OnceLock<Result<Client, OauthError>> = OnceLock::new();
+
+/// Shared HTTP client for the requests that are not done by the oauth2 lib.
+/// Redirects are disabled for the same reason as in the oauth2 lib, following them could leak the credentials
+pub(crate) fn client() -> Result<&'static Client, OauthError> {
+    CLIENT
+        .get_or_init(|| {
+            Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                // Some APIs, like GitHub, reject the requests without User-Agent
+                .user_agent(concat!("oauth-axum/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .map_err(|_| OauthError::HttpClientCreationFailed)
+        })
+        .as_ref()
+        .map_err(|e| e.clone())
+}
+
+/// Send the request, creating a span for it when the `otel` feature is enabled
+#[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+pub(crate) async fn execute(
+    span_name: &'static str,
+    request: RequestBuilder,
+) -> Result<Response, reqwest::Error> {
+    #[cfg(feature = "otel")]
+    {
+        use opentelemetry::trace::FutureExt;
+
+        let (client, request) = request.build_split();
+        let mut request = request?;
+        let method = request.method().clone();
+        let url = request.url().clone();
+        let cx = crate::otel::start_span(span_name, method.as_str(), &url, request.headers_mut());
+        let response = client.execute(request).with_context(cx.clone()).await;
+        crate::otel::end_span(&cx, response.as_ref().ok().map(|r| r.status().as_u16()));
+        response
+    }
+    #[cfg(not(feature = "otel"))]
+    request.send().await
+}
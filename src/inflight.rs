@@ -0,0 +1,217 @@
+//! Deduplication of the callbacks and code exchanges
+//!
+//! A callback can be delivered twice, by a double click or a retry of a proxy. The code can be used only once,
+//! so the second exchange waits for the first one and gets the same result. Each provider has its own `Dedup`,
+//! shared by its clones, that only keeps the exchanges in progress. A `Dedup` with a window, set with
+//! `CustomProvider::set_dedup`, also returns the result to the duplicates that arrive after the exchange finished,
+//! and can be shared by the providers that the app creates for every request.
+//!
+//! The tokens of the finished exchanges stay in memory until the window ends, so keep the window short.
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use tokio::sync::OnceCell;
+
+use crate::error::OauthError;
+use crate::token::TokenSet;
+
+/// Code exchanges and callbacks in progress or finished in the last window, the clones share the same items
+#[derive(Clone)]
+pub struct Dedup {
+    pub(crate) exchanges: InFlight<TokenSet>,
+    #[cfg(feature = "axum")]
+    pub(crate) callbacks: Claims,
+}
+
+impl Dedup {
+    /// Create an empty dedup, the results are kept for the window after the exchange finishes.
+    /// With `Duration::ZERO` only the exchanges in progress are deduplicated, and no token is kept
+    pub fn new(window: Duration) -> Self {
+        Dedup {
+            exchanges: InFlight::new(window),
+            #[cfg(feature = "axum")]
+            callbacks: Claims::new(window),
+        }
+    }
+}
+
+type Cell<T> = Arc<OnceCell<Result<T, OauthError>>>;
+
+/// Lock the items, a panic while the lock was held can't leave the map half updated
+fn lock<I>(items: &Mutex<I>) -> MutexGuard<'_, I> {
+    items.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The keys are hashed, so the codes are not kept in memory
+fn hash(key: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Items by key, with the time they finished
+struct Items<V> {
+    entries: HashMap<String, (V, Option<Instant>)>,
+    /// Keys in the order they finished, the oldest are in the front
+    finished: VecDeque<(String, Instant)>,
+}
+
+impl<V> Default for Items<V> {
+    fn default() -> Self {
+        Items {
+            entries: HashMap::new(),
+            finished: VecDeque::new(),
+        }
+    }
+}
+
+impl<V> Items<V> {
+    /// Remove the items that finished before the window
+    fn purge(&mut self, window: Duration) {
+        let now = Instant::now();
+        while let Some((key, finished_at)) = self.finished.front() {
+            if now.duration_since(*finished_at) < window {
+                break;
+            }
+            if self
+                .entries
+                .get(key)
+                .is_some_and(|(_, entry_finished_at)| *entry_finished_at == Some(*finished_at))
+            {
+                self.entries.remove(key);
+            }
+            self.finished.pop_front();
+        }
+    }
+
+    /// Keep the item for the window, or remove it right away
+    fn finish(&mut self, key: &str, keep: bool) {
+        if !keep {
+            self.entries.remove(key);
+            return;
+        }
+        let now = Instant::now();
+        if let Some((_, finished_at)) = self.entries.get_mut(key) {
+            *finished_at = Some(now);
+            self.finished.push_back((key.to_string(), now));
+        }
+    }
+}
+
+/// Requests in progress or finished in the window by key, the clones share the same requests
+pub(crate) struct InFlight<T> {
+    items: Arc<Mutex<Items<Cell<T>>>>,
+    window: Duration,
+}
+
+impl<T> Clone for InFlight<T> {
+    fn clone(&self) -> Self {
+        InFlight {
+            items: self.items.clone(),
+            window: self.window,
+        }
+    }
+}
+
+/// Finish the key when the first request finishes or is cancelled, only the successful results are kept
+struct Finish<'a, T> {
+    inflight: &'a InFlight<T>,
+    key: &'a str,
+    cell: &'a Cell<T>,
+}
+
+impl<T> Drop for Finish<'_, T> {
+    fn drop(&mut self) {
+        let keep = !self.inflight.window.is_zero() && matches!(self.cell.get(), Some(Ok(_)));
+        lock(&self.inflight.items).finish(self.key, keep);
+    }
+}
+
+impl<T: Clone> InFlight<T> {
+    fn new(window: Duration) -> Self {
+        InFlight {
+            items: Arc::new(Mutex::new(Items::default())),
+            window,
+        }
+    }
+
+    /// Run the request, or wait for the request with the same key that is in progress,
+    /// or get the result of the one that finished in the window
+    pub(crate) async fn run<F, Fut>(&self, key: &str, request: F) -> Result<T, OauthError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, OauthError>>,
+    {
+        let key = hash(key);
+        let (cell, first) = {
+            let mut items = lock(&self.items);
+            items.purge(self.window);
+            match items.entries.get(&key) {
+                Some((cell, _)) => (cell.clone(), false),
+                None => {
+                    let cell = Cell::default();
+                    items.entries.insert(key.clone(), (cell.clone(), None));
+                    (cell, true)
+                }
+            }
+        };
+        let _finish = first.then(|| Finish {
+            inflight: self,
+            key: &key,
+            cell: &cell,
+        });
+        cell.get_or_init(request).await.clone()
+    }
+}
+
+/// Keys in progress or finished in the window, the clones share the same keys
+#[cfg(feature = "axum")]
+#[derive(Clone)]
+pub(crate) struct Claims {
+    items: Arc<Mutex<Items<()>>>,
+    window: Duration,
+}
+
+/// Keep the key for the window when dropped
+#[cfg(feature = "axum")]
+pub(crate) struct Claim {
+    claims: Claims,
+    key: String,
+}
+
+#[cfg(feature = "axum")]
+impl Drop for Claim {
+    fn drop(&mut self) {
+        let keep = !self.claims.window.is_zero();
+        lock(&self.claims.items).finish(&self.key, keep);
+    }
+}
+
+#[cfg(feature = "axum")]
+impl Claims {
+    fn new(window: Duration) -> Self {
+        Claims {
+            items: Arc::new(Mutex::new(Items::default())),
+            window,
+        }
+    }
+
+    /// Claim the key, `None` when it is in progress or finished in the window
+    pub(crate) fn claim(&self, key: &str) -> Option<Claim> {
+        let key = hash(key);
+        let mut items = lock(&self.items);
+        items.purge(self.window);
+        if items.entries.contains_key(&key) {
+            return None;
+        }
+        items.entries.insert(key.clone(), ((), None));
+        Some(Claim {
+            claims: self.clone(),
+            key,
+        })
+    }
+}
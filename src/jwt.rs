@@ -0,0 +1,316 @@
+//! JWT signature verification
+//!
+//! Verify the signature of the JWTs issued by the provider (ID tokens, access tokens) with the keys of its JWKS.
+//! Only `RS256` and `ES256` are accepted, the tokens with `none` or HMAC algorithms are rejected.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::signature::{
+    RsaPublicKeyComponents, UnparsedPublicKey, ECDSA_P256_SHA256_FIXED, RSA_PKCS1_2048_8192_SHA256,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::OauthError;
+use crate::http;
+use crate::task::BackgroundTask;
+
+/// Public key of the JWKS
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    pub kid: Option<String>,
+    pub alg: Option<String>,
+    /// Modulus of the RSA key
+    pub n: Option<String>,
+    /// Exponent of the RSA key
+    pub e: Option<String>,
+    /// Curve of the EC key
+    pub crv: Option<String>,
+    pub x: Option<String>,
+    pub y: Option<String>,
+}
+
+/// Keys returned by the `jwks_uri`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    kid: Option<String>,
+    typ: Option<String>,
+}
+
+fn decode_part(part: &str) -> Result<Vec<u8>, OauthError> {
+    URL_SAFE_NO_PAD
+        .decode(part.trim_end_matches('='))
+        .map_err(|_| OauthError::InvalidIdToken)
+}
+
+impl Jwk {
+    /// Verify the signature of the message with the key
+    fn verify(&self, alg: &str, message: &[u8], signature: &[u8]) -> Result<(), OauthError> {
+        if self.alg.as_deref().is_some_and(|key_alg| key_alg != alg) {
+            return Err(OauthError::InvalidIdTokenSignature);
+        }
+        let key_part = |part: &Option<String>| {
+            part.as_deref()
+                .ok_or(OauthError::InvalidIdTokenSignature)
+                .and_then(decode_part)
+        };
+        match (alg, self.kty.as_str()) {
+            ("RS256", "RSA") => RsaPublicKeyComponents {
+                n: key_part(&self.n)?,
+                e: key_part(&self.e)?,
+            }
+            .verify(&RSA_PKCS1_2048_8192_SHA256, message, signature)
+            .map_err(|_| OauthError::InvalidIdTokenSignature),
+            ("ES256", "EC") if self.crv.as_deref() == Some("P-256") => {
+                // Uncompressed point: 0x04 || x || y
+                let mut point = vec![4u8];
+                point.extend(key_part(&self.x)?);
+                point.extend(key_part(&self.y)?);
+                UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, point)
+                    .verify(message, signature)
+                    .map_err(|_| OauthError::InvalidIdTokenSignature)
+            }
+            _ => Err(OauthError::InvalidIdTokenSignature),
+        }
+    }
+}
+
+struct CachedKeys {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+/// Cache of the JWKS of the provider, clones share the same cache.
+/// The keys are fetched again after the TTL or when the token has an unknown `kid` (key rotation).
+/// The unknown `kid`s fetch the keys at most once in the minimum refresh interval, so random `kid`s can't make
+/// the lib fetch the keys in every request. Only one fetch runs at a time, the requests that arrive during it
+/// use its keys, and the expired keys are used while the fetch fails
+#[derive(Clone)]
+pub struct JwksCache {
+    uri: Option<String>,
+    ttl: Duration,
+    min_refresh_interval: Duration,
+    keys: Arc<Mutex<Option<CachedKeys>>>,
+    last_refresh: Arc<Mutex<Option<Instant>>>,
+    /// Held by the fetch in progress
+    refreshing: Arc<tokio::sync::Mutex<()>>,
+    /// Count of the finished fetches, so the requests that waited for a fetch use its result
+    refreshes: Arc<AtomicU64>,
+}
+
+impl JwksCache {
+    /// Cache of the keys of the `jwks_uri`, the keys are kept for 1 hour
+    pub fn new(uri: &str) -> Self {
+        JwksCache {
+            uri: Some(uri.to_string()),
+            ttl: Duration::from_secs(3600),
+            min_refresh_interval: Duration::from_secs(30),
+            keys: Arc::new(Mutex::new(None)),
+            last_refresh: Arc::new(Mutex::new(None)),
+            refreshing: Arc::default(),
+            refreshes: Arc::default(),
+        }
+    }
+
+    /// Fixed keys that are never fetched, for providers that publish the keys out of band
+    pub fn from_keys(keys: JwkSet) -> Self {
+        JwksCache {
+            uri: None,
+            ttl: Duration::MAX,
+            min_refresh_interval: Duration::from_secs(30),
+            keys: Arc::new(Mutex::new(Some(CachedKeys {
+                keys,
+                fetched_at: Instant::now(),
+            }))),
+            last_refresh: Arc::new(Mutex::new(None)),
+            refreshing: Arc::default(),
+            refreshes: Arc::default(),
+        }
+    }
+
+    /// Set how long the keys are kept before being fetched again
+    pub fn set_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the minimum time between two fetches for an unknown `kid`, the default is 30 seconds.
+    /// The tokens with an unknown `kid` are rejected in this time
+    pub fn set_min_refresh_interval(mut self, min_refresh_interval: Duration) -> Self {
+        self.min_refresh_interval = min_refresh_interval;
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<CachedKeys>> {
+        self.keys
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Fetch the keys again ignoring the TTL, it waits for the fetch in progress
+    pub async fn force_refresh(&self) -> Result<JwkSet, OauthError> {
+        let Some(uri) = &self.uri else {
+            return self
+                .lock()
+                .as_ref()
+                .map(|cached| cached.keys.clone())
+                .ok_or(OauthError::JwksFetchFailed);
+        };
+        let _refreshing = self.refreshing.lock().await;
+        self.fetch(uri).await
+    }
+
+    /// Fetch the keys and save them, called with the `refreshing` lock
+    async fn fetch(&self, uri: &str) -> Result<JwkSet, OauthError> {
+        *self
+            .last_refresh
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Instant::now());
+        let result = async {
+            let response = http::execute("oauth.jwks", http::client()?.get(uri))
+                .await
+                .map_err(|_| OauthError::JwksFetchFailed)?;
+            if !response.status().is_success() {
+                return Err(OauthError::JwksFetchFailed);
+            }
+            response
+                .json::<JwkSet>()
+                .await
+                .map_err(|_| OauthError::JwksFetchFailed)
+        }
+        .await;
+        if let Ok(keys) = &result {
+            *self.lock() = Some(CachedKeys {
+                keys: keys.clone(),
+                fetched_at: Instant::now(),
+            });
+        }
+        self.refreshes.fetch_add(1, Ordering::SeqCst);
+        result
+    }
+
+    /// Spawn a task that fetches the keys in every interval, so the requests don't wait for the fetch
+    pub fn spawn_refresher(&self, interval: Duration) -> BackgroundTask {
+        let cache = self.clone();
+        BackgroundTask::spawn_interval_async(interval, move || {
+            let cache = cache.clone();
+            async move {
+                let _ = cache.force_refresh().await;
+            }
+        })
+    }
+
+    /// Find the key in the cache, the expired keys are only used when `expired` is true
+    fn find(&self, kid: Option<&str>, expired: bool) -> Option<Jwk> {
+        let cached = self.lock();
+        let cached = cached
+            .as_ref()
+            .filter(|cached| expired || cached.fetched_at.elapsed() < self.ttl)?;
+        find_key(&cached.keys, kid)
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.lock()
+            .as_ref()
+            .is_some_and(|cached| cached.fetched_at.elapsed() < self.ttl)
+    }
+
+    /// Key of the cache after a fetch, the expired keys are used when the fetch failed
+    fn find_after_fetch(&self, kid: Option<&str>) -> Result<Jwk, OauthError> {
+        self.find(kid, true).ok_or(match self.is_fresh() {
+            true => OauthError::InvalidIdTokenSignature,
+            false => OauthError::JwksFetchFailed,
+        })
+    }
+
+    fn refreshed_recently(&self) -> bool {
+        self.last_refresh
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .is_some_and(|last_refresh| last_refresh.elapsed() < self.min_refresh_interval)
+    }
+
+    /// Get the key of the `kid`, fetching the keys when it is not in the cache
+    pub async fn key(&self, kid: Option<&str>) -> Result<Jwk, OauthError> {
+        if let Some(key) = self.find(kid, false) {
+            return Ok(key);
+        }
+        let Some(uri) = &self.uri else {
+            return Err(OauthError::InvalidIdTokenSignature);
+        };
+        let refreshes = self.refreshes.load(Ordering::SeqCst);
+        let _refreshing = self.refreshing.lock().await;
+        // Other request fetched the keys while this one waited
+        if self.refreshes.load(Ordering::SeqCst) != refreshes {
+            return self.find_after_fetch(kid);
+        }
+        // Unknown `kid` in the fresh keys, the expired keys are always fetched again
+        if self.is_fresh() && self.refreshed_recently() {
+            return Err(OauthError::InvalidIdTokenSignature);
+        }
+        match self.fetch(uri).await {
+            Ok(keys) => find_key(&keys, kid).ok_or(OauthError::InvalidIdTokenSignature),
+            Err(_) => self.find_after_fetch(kid),
+        }
+    }
+
+    /// Verify the signature of the JWT and decode its claims, the claims are not validated
+    pub async fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T, OauthError> {
+        self.verify_typed(token, &[] as &[&str]).await
+    }
+
+    /// Same as `verify`, but the `typ` header needs to be one of the types (ignoring the case),
+    /// like `at+jwt` for the access tokens of RFC 9068. Any type is accepted when the list is empty
+    pub async fn verify_typed<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        types: &[impl AsRef<str>],
+    ) -> Result<T, OauthError> {
+        let mut parts = token.split('.');
+        let (header, payload, signature) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(header), Some(payload), Some(signature), None) => {
+                    (header, payload, signature)
+                }
+                _ => return Err(OauthError::InvalidIdToken),
+            };
+        let header: Header = serde_json::from_slice(&decode_part(header)?)
+            .map_err(|_| OauthError::InvalidIdToken)?;
+        if !types.is_empty()
+            && !header.typ.as_deref().is_some_and(|typ| {
+                types
+                    .iter()
+                    .any(|accepted| accepted.as_ref().eq_ignore_ascii_case(typ))
+            })
+        {
+            return Err(OauthError::InvalidIdToken);
+        }
+        let key = self.key(header.kid.as_deref()).await?;
+        let message = &token[..header_len(token)];
+        key.verify(&header.alg, message.as_bytes(), &decode_part(signature)?)?;
+        serde_json::from_slice(&decode_part(payload)?).map_err(|_| OauthError::InvalidIdToken)
+    }
+}
+
+/// Length of `header.payload`, the signed part of the JWT
+fn header_len(token: &str) -> usize {
+    token.rfind('.').unwrap_or(token.len())
+}
+
+fn find_key(keys: &JwkSet, kid: Option<&str>) -> Option<Jwk> {
+    keys.keys
+        .iter()
+        .find(|key| kid.is_none() || key.kid.as_deref() == kid)
+        .cloned()
+}
@@ -0,0 +1,248 @@
+//! Login kit
+//!
+//! `LoginKit` puts together the `ProviderRegistry`, a `StateStore` and an encrypted session cookie in a `Router`
+//! with the routes of the login of every provider:
+//!
+//! - `GET /login/:provider` - redirect the user to the provider, with an optional `return_to` query parameter
+//! - `GET|POST /callback/:provider` - finish the login, save the user in the session and redirect to the `return_to`
+//! - `POST /logout` - remove the session, only with POST so a link or an image of other site can't logout the user
+//!
+//! The handlers of the app read the user of the session with the `CurrentUser` extractor.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{FromRef, FromRequestParts, Query, State};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::Redirect;
+use axum::routing::{get, post};
+use axum::{async_trait, Router};
+use axum_extra::extract::cookie::{Cookie, Key, PrivateCookieJar, SameSite};
+use serde::{Deserialize, Serialize};
+
+use crate::callback::CallbackParams;
+use crate::error::OauthError;
+use crate::login::{begin_login, complete_login, LoginOptions, UserUpserter};
+use crate::registry::{ProviderRegistry, SelectedProvider};
+use crate::store::StateStore;
+use crate::validation::now;
+
+/// Name of the cookie that stores the user of the session
+pub const SESSION_COOKIE: &str = "oauth_axum_session";
+
+/// User of the session, saved in the encrypted cookie after the login
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CurrentUser {
+    /// Name of the provider in the registry
+    pub provider: String,
+    /// Id of the user in the provider
+    pub id: String,
+    /// Id of the user in the app, returned by the `UserUpserter`
+    #[serde(default)]
+    pub user_id: Option<String>,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub avatar_url: Option<String>,
+    /// Unix time when the session expires
+    pub expires_at: u64,
+}
+
+/// Routes of the login of every provider of the registry, with the user saved in an encrypted cookie
+///
+/// ```ignore
+/// let kit = LoginKit::new(registry, Arc::new(MemoryStateStore::default()), key.clone())
+///     .set_after_login("/dashboard");
+///
+/// // `Key: FromRef<AppState>` is needed to use `CurrentUser` in the handlers of the app
+/// async fn dashboard(user: CurrentUser) -> String { format!("Hello {}", user.id) }
+///
+/// let app = Router::new()
+///     .route("/dashboard", get(dashboard))
+///     .with_state(AppState { key })
+///     .nest("/auth", kit.router());
+/// ```
+#[derive(Clone)]
+pub struct LoginKit {
+    registry: Arc<ProviderRegistry>,
+    store: Arc<dyn StateStore>,
+    key: Key,
+    after_login: String,
+    after_logout: String,
+    session_ttl: Duration,
+    upserter: Option<Arc<dyn UserUpserter>>,
+}
+
+impl LoginKit {
+    /// Create the kit
+    /// # Arguments
+    /// * `registry` - ProviderRegistry - The providers, the name in the registry is the `:provider` of the routes
+    /// * `store` - Arc<dyn StateStore> - The store where the states are saved between the login and the callback
+    /// * `key` - Key - The key used to encrypt the session cookie, the same key of the app
+    pub fn new(registry: ProviderRegistry, store: Arc<dyn StateStore>, key: Key) -> Self {
+        LoginKit {
+            registry: Arc::new(registry),
+            store,
+            key,
+            after_login: String::from("/"),
+            after_logout: String::from("/"),
+            session_ttl: Duration::from_secs(7 * 24 * 3600),
+            upserter: None,
+        }
+    }
+
+    /// Set where the user goes after the login when there is no `return_to`, the default is `/`
+    pub fn set_after_login(mut self, after_login: &str) -> Self {
+        self.after_login = after_login.to_string();
+        self
+    }
+
+    /// Set where the user goes after the logout, the default is `/`
+    pub fn set_after_logout(mut self, after_logout: &str) -> Self {
+        self.after_logout = after_logout.to_string();
+        self
+    }
+
+    /// Set how long the session is valid, the default is 7 days
+    pub fn set_session_ttl(mut self, session_ttl: Duration) -> Self {
+        self.session_ttl = session_ttl;
+        self
+    }
+
+    /// Set the hook that saves the user in the app after the login, its id is in `CurrentUser::user_id`
+    pub fn set_upserter(mut self, upserter: impl UserUpserter + 'static) -> Self {
+        self.upserter = Some(Arc::new(upserter));
+        self
+    }
+
+    /// Create the router with the login, callback and logout routes, it can be nested or merged in the app
+    pub fn router<S>(self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        Router::new()
+            .route("/login/:provider", get(login))
+            .route("/callback/:provider", get(callback).post(callback))
+            .route("/logout", post(logout))
+            .with_state(self)
+    }
+}
+
+impl FromRef<LoginKit> for Arc<ProviderRegistry> {
+    fn from_ref(kit: &LoginKit) -> Self {
+        kit.registry.clone()
+    }
+}
+
+impl FromRef<LoginKit> for Key {
+    fn from_ref(kit: &LoginKit) -> Self {
+        kit.key.clone()
+    }
+}
+
+#[derive(Deserialize)]
+struct LoginQuery {
+    return_to: Option<String>,
+}
+
+fn status(error: OauthError) -> StatusCode {
+    match error {
+        OauthError::StateMismatch
+        | OauthError::FingerprintMismatch
+        | OauthError::UnsafeRedirectUrl
+        | OauthError::AuthorizationFailed => StatusCode::BAD_REQUEST,
+        OauthError::DuplicateCallback => StatusCode::CONFLICT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+async fn login(
+    selected: SelectedProvider,
+    State(kit): State<LoginKit>,
+    Query(query): Query<LoginQuery>,
+) -> Result<Redirect, StatusCode> {
+    let opts = LoginOptions {
+        scopes: selected.scopes,
+        return_to: query.return_to,
+        ..LoginOptions::default()
+    };
+    begin_login(&selected.name, selected.provider, kit.store.as_ref(), &opts)
+        .await
+        .map_err(status)
+}
+
+async fn callback(
+    selected: SelectedProvider,
+    State(kit): State<LoginKit>,
+    jar: PrivateCookieJar,
+    params: CallbackParams,
+) -> Result<(PrivateCookieJar, Redirect), StatusCode> {
+    let opts = LoginOptions {
+        fetch_user: true,
+        upserter: kit.upserter.clone(),
+        ..LoginOptions::default()
+    };
+    let outcome = complete_login(
+        &selected.name,
+        &selected.provider,
+        kit.store.as_ref(),
+        params,
+        &opts,
+    )
+    .await
+    .map_err(status)?;
+    let user = outcome.user.ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let session = CurrentUser {
+        provider: selected.name,
+        id: user.id,
+        user_id: outcome.user_id,
+        email: user.email,
+        name: user.name,
+        avatar_url: user.avatar_url,
+        expires_at: now() + kit.session_ttl.as_secs(),
+    };
+    let value = serde_json::to_string(&session).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let cookie = Cookie::build((SESSION_COOKIE, value))
+        .path("/")
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax)
+        .max_age(kit.session_ttl.try_into().unwrap_or_default())
+        .build();
+    let return_to = outcome.return_to.unwrap_or(kit.after_login);
+    Ok((jar.add(cookie), Redirect::to(&return_to)))
+}
+
+async fn logout(
+    State(kit): State<LoginKit>,
+    jar: PrivateCookieJar,
+) -> (PrivateCookieJar, Redirect) {
+    (
+        jar.remove(Cookie::build(SESSION_COOKIE).path("/")),
+        Redirect::to(&kit.after_logout),
+    )
+}
+
+/// Get the user of the session, rejects with `401` when there is no session or it is expired.
+/// Use `Option<CurrentUser>` in the pages that work without login
+#[async_trait]
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    S: Send + Sync,
+    Key: FromRef<S>,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let jar = PrivateCookieJar::<Key>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        let cookie = jar.get(SESSION_COOKIE).ok_or(StatusCode::UNAUTHORIZED)?;
+        let user: CurrentUser =
+            serde_json::from_str(cookie.value()).map_err(|_| StatusCode::UNAUTHORIZED)?;
+        match user.expires_at > now() {
+            true => Ok(user),
+            false => Err(StatusCode::UNAUTHORIZED),
+        }
+    }
+}
@@ -15,6 +15,55 @@
 //! - MicrosoftProvider
 //! - FacebookProvider
 //! - SpotifyProvider
+//! - PayPalProvider
+//! - SalesforceProvider
+//! - IntuitProvider
+//! - TikTokProvider
+//! - DocuSignProvider
+//! - AdobeProvider
+//! - AutodeskProvider
+//! - BasecampProvider
+//! - WebexProvider
+//! - DeezerProvider
+//! - DribbbleProvider
+//! - EtsyProvider
+//! - EventbriteProvider
+//! - ImgurProvider
+//! - MeetupProvider
+//! - PocketProvider
+//! - StackExchangeProvider
+//! - SurveyMonkeyProvider
+//! - TodoistProvider
+//! - TumblrProvider
+//! - TypeformProvider
+//! - UberProvider
+//! - UnsplashProvider
+//! - WithingsProvider
+//! - WordPressProvider
+//! - WrikeProvider
+//! - YelpProvider
+//! - ZohoProvider
+//! - CognitoProvider
+//! - AzureB2CProvider
+//! - GiteaProvider
+//! - MastodonProvider
+//! - NextcloudProvider
+//! - AuthentikProvider
+//! - ZitadelProvider
+//! - FusionAuthProvider
+//! - OryHydraProvider
+//! - CasdoorProvider
+//! - OneLoginProvider
+//! - PingOneProvider
+//! - JumpCloudProvider
+//! - LinearProvider
+//! - VercelProvider
+//! - MiroProvider
+//! - CanvaProvider
+//! - MondayProvider
+//! - ClickUpProvider
+//! - CalendlyProvider
+//! - OsuProvider
 //!
 //! in your project, pass to the ```new``` function:
 //!
@@ -27,6 +76,8 @@
 //!   - **auth_url:** URL from your provider that is used to get the permission of your app access user account
 //!   - **token_url:** URL that is used to generate the auth token
 //!
+//!   Providers that have a separated sandbox (PayPal, Salesforce, Intuit, DocuSign) receive an **environment** first: `Environment::Sandbox` or `Environment::Production`.
+//!
 //! The structure of this project is separated into two steps:
 //!
 //! ### 1. Generate the URL
@@ -47,18 +98,17 @@
 //!
 //! This method is for a small project that will run in one unique instance of Axum. It saves the state and verifier in memory, which can be accessible in the callback URL call.
 //!
-//! ```rust
-//! mod utils;
+//! ```rust,ignore
 //! use std::sync::Arc;
 //!
 //! use axum::extract::Query;
 //! use axum::Router;
 //! use axum::{routing::get, Extension};
+//! use oauth_axum::flow::PendingAuthorization;
 //! use oauth_axum::providers::twitter::TwitterProvider;
+//! use oauth_axum::store::{MemoryStateStore, StateStore};
 //! use oauth_axum::{CustomProvider, OAuthClient};
 //!
-//! use crate::utils::memory_db_util::AxumState;
-//!
 //! #[derive(Clone, serde::Deserialize)]
 //! pub struct QueryAxumCallback {
 //!     pub code: String,
@@ -70,7 +120,7 @@
 //!     dotenv::from_filename("examples/.env").ok();
 //!     println!("Starting server...");
 //!
-//!     let state = Arc::new(AxumState::new());
+//!     let state = Arc::new(MemoryStateStore::default());
 //!     let app = Router::new()
 //!         .route("/", get(create_url))
 //!         .route("/api/v1/twitter/callback", get(callback))
@@ -91,82 +141,461 @@
 //!     )
 //! }
 //!
-//! pub async fn create_url(Extension(state): Extension<Arc<AxumState>>) -> String {
-//!     let state_oauth = get_client()
-//!         .generate_url(
-//!             Vec::from(["users.read".to_string()]),
-//!             |state_e| async move {
-//!                 //SAVE THE DATA IN THE DB OR MEMORY
-//!                 //state should be your ID
-//!                 state.set(state_e.state, state_e.verifier);
-//!             },
-//!         )
+//! pub async fn create_url(Extension(state): Extension<Arc<MemoryStateStore>>) -> String {
+//!     let pending = get_client()
+//!         .authorize(Vec::from(["users.read".to_string()]))
 //!         .await
-//!         .ok()
-//!         .unwrap()
-//!         .state
 //!         .unwrap();
-//!
-//!     state_oauth.url_generated.unwrap()
+//!     let url = pending.url().to_string();
+//!     //SAVE THE DATA IN THE DB OR MEMORY
+//!     //state should be your ID
+//!     state.save("twitter", pending.into_state()).await.unwrap();
+//!     url
 //! }
 //!
 //! pub async fn callback(
-//!     Extension(state): Extension<Arc<AxumState>>,
+//!     Extension(state): Extension<Arc<MemoryStateStore>>,
 //!     Query(queries): Query<QueryAxumCallback>,
 //! ) -> String {
-//!     println!("{:?}", state.clone().get_all_items());
 //!     // GET DATA FROM DB OR MEMORY
 //!     // get data using state as ID
-//!     let item = state.get(queries.state.clone());
-//!     get_client()
-//!         .generate_token(queries.code, item.unwrap())
+//!     let item = state.take_for("twitter", &queries.state).await.unwrap();
+//!     PendingAuthorization::resume(get_client(), item.unwrap().state)
+//!         .unwrap()
+//!         .exchange(queries.code)
 //!         .await
 //!         .ok()
-//!        .unwrap()
+//!         .unwrap()
+//!         .access_token
+//! }
+//! ```
+//!
+//! ## Encrypted cookie flow
+//!
+//! If you don't want to save the state and verifier in a DB or memory, enable the `cookie` feature and use the helpers from the `cookie` module. They save the state and verifier in an encrypted `PrivateCookieJar` from axum-extra and validate the state in the callback. `generate_token_from_cookies` removes the cookies also when the exchange fails, so return its jar in the error response too. Check `examples/github_cookie.rs`.
+//!
+//! ## OpenTelemetry
+//!
+//! With the `otel` feature enabled, every request made to the provider creates a client span using the global tracer, and the current context is sent in the request headers using the global propagator.
+//!
+//! ## Refresh token
+//!
+//! `PendingAuthorization::exchange` returns the `TokenSet` with the access token, the refresh token, the expiration and the other fields returned by the provider. Use `refresh_token` with the refresh token to generate a new token when the access token expires, some providers also return a new refresh token.
+//!
+//! ## Deduplication
+//!
+//! The same code and verifier sent twice at the same time to `PendingAuthorization::exchange` are exchanged only once, and both calls get the same token. By default each provider (and its clones) has its own `inflight::Dedup`, and nothing is kept after the exchange finishes. To also return the token to the duplicates that arrive after the first exchange, like a proxy retry, or to share the dedup between the providers created for every request, pass a `Dedup::new(window)` held by the app to `set_dedup`: the token of each exchange stays in memory until the window ends, so keep it short (a few seconds). The failed exchanges are never kept.
+//!
+//! ## State stores
+//!
+//! The `store` module has the `StateStore` trait, used to save the state and verifier between the two steps, and the `MemoryStateStore` implementation. `stats()` and `list_pending()` show the pending authorization attempts (never the verifiers, and only a short hash of the states, so they can't be replayed), and with the `axum` feature `store::debug_router` exposes them in `/stats` and `/pending`. When many apps share the same DB, wrap the store in `Namespaced::new("app", store)` to prefix the keys (the namespace can't be empty or have a `:`), and use `take_for(provider, state)` to reject a state saved by other provider.
+//!
+//! ## Device flow
+//!
+//! Set the device authorization endpoint with `set_device_authorization_url` and call `DeviceSessions::start`, the lib polls the provider in background and `device::status_router` exposes the status (pending/complete/denied/expired/failed) by the user code, so the web UI can show the progress. The polling task belongs to the session: it stops when the session is removed, and the session is removed when the device code expires.
+//!
+//! ## Deterministic tests
+//!
+//! With the `test-utils` feature, `StateGenerator::fixed`, `StateGenerator::seeded` and `PkceGenerator::fixed` create the same state and verifier in every run, so the integration tests can assert on the generated URL and call the callback without parsing the redirect.
+//!
+//! ## Silent authentication
+//!
+//! `silent_auth_url` generates the URL with `prompt=none`, so the provider returns to the callback without showing the login page. In the callback, receive `Query<CallbackParams>` and use `silent_result()` to get the code or `LoginRequired`/`InteractionRequired`/`ConsentRequired`, and fall back to the normal login.
+//!
+//! ## OpenID Connect
+//!
+//! The `openid` module decodes the claims of the `id_token` returned with the token (`IdTokenClaims::from_token_set`). For step-up authentication, `set_max_age` sends the `max_age` in the URL and `check_max_age` returns `AuthenticationTooOld` when the `auth_time` claim is older than it. To require MFA, `set_acr_values` sends the `acr_values` and `check_acr` returns `AcrNotSatisfied` when the `acr` claim is not one of them. Keep the raw `TokenSet::id_token` to send it as `id_token_hint` in `openid::end_session_url` and in the silent re-authentication (`set_id_token_hint`). The endpoints can be loaded from the issuer with `discovery::DiscoveryCache`, that keeps the metadata for a TTL, revalidates it with the ETag and has `force_refresh` to fetch it again. `CustomOpenIdProvider` wraps a `CustomProvider` with the issuer, always requests the `openid` scope, and `fetch_userinfo` returns the claims of the userinfo endpoint after checking that the `sub` is the same of the ID token. `CustomOpenIdProvider` is created with the `jwks_uri` (from the discovery or the `into_openid` argument), and the code exchange verifies the signature (RS256/ES256) and the `iss`, `aud`, `exp` and `nbf` of the ID token. Every URL has a new `nonce`, saved in the `StateAuth`, and the ID token needs to have the same nonce, so the exchange needs the state: use `PendingAuthorization::exchange` or `generate_token_with_claims(code, &state)`, that returns the verified claims with the token. `generate_token_set` fails with `UnsupportedFlow`. The keys are fetched again for an unknown `kid` at most once every 30 seconds (`JwksCache::set_min_refresh_interval`), so random `kid`s can't make the lib fetch the keys in every request. Only one fetch of the keys runs at a time, the requests that arrive during it wait for its keys, and the expired keys are still used while the fetch fails. Any preset can be used as OpenID provider with `into_openid(issuer, jwks_uri)`, like `GoogleProvider::openid`.
+//!
+//! ## Provider registry
+//!
+//! `ProviderRegistry::from_config` creates all the providers from a config file, or from a JSON document when the argument starts with `{`, with the kind of preset, the credentials (`${VAR}` is read from the environment), the redirect and the scopes. The self-hosted presets, like `gitea` and `mastodon`, read the URL of the instance from `base_url`, the presets that need a domain or a region, like `cognito`, read them from the `domain` and `region` fields, and `azureb2c` reads the B2C tenant from `tenant` and the policy from `user_flow`. The `pocket` kind uses the `client_id` as the consumer key, Pocket doesn't follow OAuth2 so it is returned by `get_pocket` instead of `get` and `SelectedProvider`. The files ending in `.toml` are parsed as TOML with the `toml` feature, the files ending in `.yaml` or `.yml` as YAML with the `yaml` feature, and the others as JSON (`from_json`, `from_toml` and `from_yaml` parse a document). A file that can't be read fails with `ConfigReadFailed`, and an invalid document with `InvalidConfig`.
+//!
+//! ## Axum state
+//!
+//! `Arc<ProviderRegistry>`, `Arc<dyn StateStore>` and `DeviceSessions` can live in your `AppState` with `#[derive(FromRef)]` and be extracted with `State`, without `Extension` layers. `SelectedProvider` extracts the provider of the `:provider` path parameter from the registry, cloning only the `Arc` of the registry and the selected provider, and `store::debug_routes`/`device::status_routes` read the store and sessions from the state.
+//!
+//! ## Response type and mode
+//!
+//! `set_response_type` changes the `response_type` (like `code id_token` for the hybrid flow) and `set_response_mode` sends the `response_mode` (`Query`, `FormPost` or `Fragment`). With the `axum` feature, `CallbackParams` is an extractor that reads the query in GET and the form in POST, and the cookie helpers use `SameSite=None` in the `form_post` mode, because the callback is a cross site POST.
+//!
+//! ## User profile
+//!
+//! `get_user(access_token)` calls the user endpoint of the provider and returns an `OAuthUser` with the `id`, `email`, `name`, `avatar_url` and the `raw` response. The GitHub, Google, Discord, Microsoft, Facebook, Spotify and Twitter presets have the endpoint configured, for the other providers use `set_user_info`. To read the fields of the provider, use `get_user_typed` with `GithubUser`, `GoogleUserInfo`, `DiscordUser` or `TwitterUser`.
+//!
+//! ## Presets
+//!
+//! Every preset implements the `providers::ProviderPreset` trait, so the code can be generic over the providers. It has the `NAME` of the preset, the same of the `kind` in the registry, the `Config` with the values that change the endpoints and `create(&config, client_id, client_secret, redirect_url)`. The `Config` is `()` for the presets with a global endpoint, `Environment`, `Tenant` or `Datacenter` for the presets with environments, tenants or data centers (the default is the global or production one), `BaseUrl` or `Domain` for the self-hosted and per customer presets, and a struct for the presets with more values, like `CognitoConfig`. `endpoints`, `default_scopes` and `quirks` return the configuration of the preset for a config, like in the drift check.
+//!
+//! ## Login helpers
+//!
+//! With the `axum` feature, `login::begin_login` generates the URL, saves the state in a `StateStore` and returns the `Redirect`. `login::complete_login` consumes the state of the callback, checks that it is from the same provider (and the same client, when `client_info` is set), returns `AuthorizationFailed` when the provider sends an `error`, exchanges the code and, with `fetch_user`, gets the user profile. Both receive the same `LoginOptions`. With an `upserter` in the options, the `UserUpserter` is called with the user profile after the token exchange, and returns the id of the user in the app, saved in `LoginOutcome::user_id` (and in `CurrentUser::user_id` when it is set in the `LoginKit`). When the same callback arrives twice in the same process, at the same time or up to 60 seconds later, the second one fails with `DuplicateCallback` (409 in the `LoginKit`) without consuming the state or sending the code to the provider. In other instances, the state was already consumed by the first one and it fails with `StateMismatch`.
+//!
+//! ## URL templates
+//!
+//! For providers with the tenant, shop, region or realm in the endpoints, `template::ProviderTemplate` has the URLs with placeholders like `{tenant}`, filled with the typed setters (`tenant`, `shop`, `region`, `realm`) or `set(name, value)`, and `build` creates the provider. A missing value, or a value with characters other than letters, digits, `.`, `_` and `-`, fails with `InvalidConfig`, so the value can't change the host, port or path. `MicrosoftProvider::template()` is the template of the Microsoft identity platform, and the presets with the domain, tenant or environment in the URL (Microsoft, Cognito, OneLogin, Ory Network, Zitadel, PingOne and Azure AD B2C) fill it with a template, so their constructors return a `Result`.
+//!
+//! ## Google One Tap
+//!
+//! `providers::google::GoogleOneTap` verifies the credential posted by One Tap or Sign in with Google (signature with the Google keys, `iss`, `aud` and `exp`) and returns the same `OAuthUser` of `get_user`, so the app can have the redirect login and One Tap. `verify_callback` also compares the `g_csrf_token` of the form with the cookie.
+//!
+//! ## Login page options
+//!
+//! `set_prompt` sends the `prompt` with the typed `openid::Prompt`, like `Prompt::SelectAccount` in Google or `Prompt::None` in Discord. The options of a single provider are in traits of the preset module: `GithubOptions::set_allow_signup` in GitHub, `MicrosoftOptions::set_domain_hint` in Microsoft, `FusionAuthOptions::set_tenant_id` in FusionAuth and `LinearOptions::set_actor_application` in Linear. For the other parameters use `set_auth_param`.
+//!
+//! ## Login kit
+//!
+//! With the `cookie` feature, `kit::LoginKit` creates a `Router` with `/login/:provider`, `/callback/:provider` and `POST /logout` for every provider of the `ProviderRegistry`, using a `StateStore` for the states and an encrypted cookie for the session. After the login, the handlers of the app get the user with the `CurrentUser` extractor (`Option<CurrentUser>` in the pages that work without login), the app state only needs `Key: FromRef`.
+//!
+//! ## JWT sessions
+//!
+//! With the `session` feature, `session::SessionIssuer` issues a JWT of the app after `complete_login`, signed with `HS256` or `RS256`, with the `sub` of the user (the `user_id` of the upserter, or `{provider}:{id}`), the `provider`, `email` and `name` of the profile and the claims added with `set_claims`. `issue_json` returns it in the format of a token response, and `issue_cookie` (with the `cookie` feature) in a `HttpOnly` cookie, for the backends that don't keep the sessions in the server. `SessionIssuer::verify` validates the tokens with the same key. The `BearerValidator` only accepts the asymmetric keys of a JWKS, so the `HS256` tokens are validated only by the app that has the secret.
+//!
+//! ## Resource server
+//!
+//! `resource::BearerValidator` validates the JWT access tokens received by the API, with the JWKS of the issuer (`discover(issuer, audience)` reads it from the discovery document), the `at+jwt` type of RFC 9068 (change it with `set_token_types`) and the `iss`, `aud`, `exp` and `nbf` claims. For opaque tokens, `BearerValidator::introspection` checks them in the introspection endpoint (RFC 7662) and caches the results for 60 seconds (or until the token expires), so the endpoint is not called in every request. The cache keeps the last 10000 tokens (`set_introspection_cache_size`), so random tokens can't make it grow without limit. With the `axum` feature, `BearerClaims` is an extractor that validates the `Authorization: Bearer` header with the `BearerValidator` of the app state, and rejects the request with the `WWW-Authenticate` header, or with `503` when the keys or the introspection endpoint are not available. To require a scope, create its type with `scope!(ReadRepo, "repo:read")` and use the `RequireScope<ReadRepo>` extractor, or add `scope_guard` as a middleware of the router, the tokens without the scope are rejected with `403` and `error="insufficient_scope"`.
+//!
+//! ## Clock skew
+//!
+//! All the time validations accept a tolerance between the clocks, 60 seconds by default. `validation::ValidationOptions` has the `clock_skew`, set the same options with `set_validation_options` in the `MemoryStateStore` (state TTL), `CustomOpenIdProvider` (`exp`, `nbf` and `iat` of the ID token), `BearerValidator` and `GoogleOneTap`, and use `IdTokenClaims::check_max_age_with` for the `auth_time`.
+//!
+//! ## Token endpoint failover
+//!
+//! Providers with regional mirrors or a backup gateway can list other URLs of the token endpoint. When the connection to the token URL fails, the request is sent to the next URL. Only connection errors are retried, so a code is never sent twice.
+//!
+//! ```rust,ignore
+//! let provider = CustomProvider::new(auth_url, token_url, client_id, client_secret, redirect_url)
+//!     .set_token_url_fallbacks(&["https://token-eu.example.com/oauth/token"]);
+//! ```
+//!
+//! In the registry the URLs are in `token_url_fallbacks`.
+//!
+//! ## URL pool
+//!
+//! Landing pages that show the login buttons to every visitor can take the URLs from a `UrlPool`. The pool generates the URLs in background, and it saves their states in the store in one write with `StateStore::save_many`. Every URL is used only once. When the pool is empty, the URL is generated in the request.
+//!
+//! ```rust,ignore
+//! let pool = UrlPool::new("github", github_provider, store.clone(), vec!["read:user".to_string()])
+//!     .set_size(128);
+//! let refiller = pool.spawn_refiller(Duration::from_secs(10));
+//!
+//! let url = pool.next_url().await?;
+//! ```
+//!
+//! The URLs older than `set_max_age` are removed with their states. `invalidate` removes all of them, for example after a change in the scopes.
+//!
+//! ## Query encoding
+//!
+//! Some strict providers and WAFs reject `+` as the space of the scopes, or a parameter that is sent twice. The `Quirks` of the provider control how the authorization URL is built:
+//!
+//! ```rust,ignore
+//! let provider = CustomProvider::new(auth_url, token_url, client_id, client_secret, redirect_url)
+//!     .set_scope_delimiter(ScopeDelimiter::Custom(";"))
+//!     .set_quirks(
+//!         Quirks::new()
+//!             .query_encoding(QueryEncoding::Percent)
+//!             .dedupe_params()
+//!             .param_order(&["response_type", "client_id", "redirect_uri"]),
+//!     );
+//! ```
+//!
+//! ## Typed flow
+//!
+//! `OAuthClient::authorize` returns a `PendingAuthorization`, and it is the only type with `exchange`, so the code can't be exchanged without the verifier. Save it with `into_state`, and load it in the callback with `PendingAuthorization::resume`, which rejects a state without a verifier:
+//!
+//! ```rust,ignore
+//! let pending = provider.authorize(["read:user"]).await?;
+//! let url = pending.url().to_string();
+//! store.save("github", pending.into_state()).await?;
+//!
+//! // In the callback
+//! let stored = store.take_for("github", &state).await?.ok_or(OauthError::StateMismatch)?;
+//! let token = PendingAuthorization::resume(provider, stored.state)?
+//!     .exchange_callback(params)
+//!     .await?;
+//! ```
+//!
+//! The fields of `StateAuth` are read-only, read them with the getters of the same name. A state saved in your own DB is loaded with `StateAuth::restore(state, verifier)`, that rejects an empty state or verifier, and `with_return_to`, `with_fingerprint` and `with_nonce` for the other saved values.
+//!
+//! ## Endpoint drift
+//!
+//! The endpoints of the presets are hard-coded. The presets with a discovery document (`ProviderPreset::ISSUER`) can be compared with the endpoints published by the provider, at startup or in background. `compare_endpoints` checks a document that was already fetched. The templated issuer of the multi-tenant documents, like `https://login.microsoftonline.com/{tenantid}/v2.0` in the `common` one of Microsoft, is accepted by the drift check, the discovery of the OpenID providers and resource servers needs the exact issuer:
+//!
+//! ```rust,ignore
+//! let report = drift::check_builtin_presets().await;
+//! for drift in &report.drifts {
+//!     eprintln!("{} {} changed to {}", drift.preset, drift.endpoint, drift.published);
 //! }
+//!
+//! let checker = drift::spawn_drift_check(Duration::from_secs(24 * 3600), |drift| {
+//!     eprintln!("{} {} changed to {}", drift.preset, drift.endpoint, drift.published);
+//! });
 //! ```
 //!
+//! ## Upgrading to 0.2
+//!
+//! - `OAuthClient::get_client` returns `&ProviderClient`, so implementations of the trait need to keep the client. The oauth2 client of a `CustomProvider` is created in `new` and in the setters that change it, and `build` returns its error, like an invalid URL. The clones share the client, so keep one provider in the state of the app instead of creating one for every request.
+//! - `auth_url`, `token_url`, `client_id`, `client_secret` and `redirect_url` of `CustomProvider` are not public fields anymore, because a change after the creation was ignored by the client. Read them with the getters of the same name, and change the secret with `set_client_secret`.
+//! - The constructors of `MicrosoftProvider`, `CognitoProvider`, `OneLoginProvider`, `ZitadelProvider`, `PingOneProvider`, `AzureB2CProvider` and the Ory Network ones of `OryHydraProvider` return a `Result`, with `InvalidConfig` when the domain, tenant or environment can't be used in the URL.
+//! - `generate_token` and `generate_token_set` are deprecated, use `authorize` and `PendingAuthorization::exchange`. They return `VerifierNotFound` for an empty verifier instead of sending it to the provider.
+//!
 //! # Next Steps of Development
 //!
 //! - Add all tests
 //! - Add more Providers
 //!
 
+pub mod callback;
+#[cfg(feature = "cookie")]
+pub mod cookie;
+pub mod device;
+pub mod discovery;
+pub mod drift;
 pub mod error;
+pub mod fingerprint;
+pub mod flow;
+pub mod generators;
+mod http;
+pub mod inflight;
+pub mod jwt;
+#[cfg(feature = "cookie")]
+pub mod kit;
+#[cfg(feature = "axum")]
+pub mod login;
+pub mod openid;
+#[cfg(feature = "otel")]
+mod otel;
+pub mod pool;
 pub mod providers;
+pub mod quirks;
+mod redact;
+pub mod redirect;
+pub mod registration;
+pub mod registry;
+pub mod resource;
+pub mod scopes;
+#[cfg(feature = "session")]
+pub mod session;
+pub mod store;
+pub mod task;
+pub mod template;
+pub mod token;
+pub mod user;
+pub mod validation;
 
 use async_trait::async_trait;
+use callback::ResponseMode;
 use error::OauthError;
+use fingerprint::{ClientInfo, Fingerprinter};
+use flow::PendingAuthorization;
+use generators::{PkceGenerator, StateGenerator};
+use inflight::Dedup;
+use quirks::Quirks;
+use redact::{redacted, Redacted, RedactedParams, RedactedUrl};
+use redirect::RedirectPolicy;
+use scopes::{ScopeDelimiter, Scopes};
+use std::fmt;
 use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use token::{ProviderClient, ProviderTokenResponse, TokenSet};
+use user::UserInfo;
 
+use oauth2::basic::{BasicErrorResponse, BasicErrorResponseType};
 use oauth2::reqwest::async_http_client;
 use oauth2::{
-    basic::BasicClient, AuthUrl, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge, RedirectUrl,
-    Scope, TokenUrl,
+    AuthUrl, ClientId, ClientSecret, DeviceAuthorizationUrl, RedirectUrl, RefreshToken,
+    ResponseType, Scope, TokenUrl,
 };
-use oauth2::{AuthorizationCode, PkceCodeVerifier, TokenResponse};
+use oauth2::{AuthorizationCode, HttpRequest, HttpResponse, PkceCodeVerifier, RequestTokenError};
+
+pub use oauth2::AuthType;
+
+type HttpError = oauth2::reqwest::Error<reqwest::Error>;
+type TokenRequestError = RequestTokenError<HttpError, BasicErrorResponse>;
 
 #[derive(Clone)]
 pub struct CustomProvider {
-    pub auth_url: String,
-    pub token_url: String,
-    pub client_id: String,
-    pub client_secret: String,
-    pub redirect_url: String,
+    // The oauth2 client is created from these fields in `new`, so they are only changed by the setters that create it again
+    pub(crate) auth_url: String,
+    pub(crate) token_url: String,
+    pub(crate) client_id: String,
+    pub(crate) client_secret: String,
+    pub(crate) redirect_url: String,
     pub state: Option<StateAuth>,
+    /// Shared by the clones, until a setter that changes the client creates a new one
+    client: Arc<Result<ProviderClient, OauthError>>,
+    secondary_client_secret: Option<String>,
+    secondary_client: Option<Arc<Result<ProviderClient, OauthError>>>,
+    on_secret_used: Option<Arc<dyn Fn(SecretUsed) + Send + Sync>>,
+    scope_delimiter: ScopeDelimiter,
+    auth_type: AuthType,
+    quirks: Quirks,
+    redirect_policy: Option<RedirectPolicy>,
+    return_to: Option<String>,
+    state_generator: StateGenerator,
+    pkce_generator: PkceGenerator,
+    fingerprinter: Fingerprinter,
+    client_info: Option<ClientInfo>,
+    device_authorization_url: Option<String>,
+    auth_params: Vec<(String, String)>,
+    public_client: bool,
+    response_type: Option<String>,
+    response_mode: Option<ResponseMode>,
+    default_scopes: Scopes,
+    user_info: Option<UserInfo>,
+    token_url_fallbacks: Vec<String>,
+    dedup: Dedup,
+}
+
+/// Client secret that generated the token, used to follow a secret rotation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecretUsed {
+    Primary,
+    Secondary,
 }
 
+/// Not used by the lib, the state is saved by the `save` function of `generate_url` or by a `StateStore`
+#[deprecated(note = "use the `store::StateStore` trait to save the state")]
 #[derive(Clone)]
 pub enum MethodExecute {
     DB,
     MEMORY,
 }
 
-#[derive(Clone, Debug)]
+/// State and verifier generated with the URL. The fields are read-only, so a state loaded in the callback
+/// can't be changed before `PendingAuthorization::resume`
+#[derive(Clone)]
 pub struct StateAuth {
-    pub url_generated: Option<String>,
-    pub state: String,
-    pub verifier: String,
+    url_generated: Option<String>,
+    state: String,
+    verifier: String,
+    return_to: Option<String>,
+    fingerprint: Option<String>,
+    nonce: Option<String>,
+}
+
+impl StateAuth {
+    /// Create the state loaded from a DB or a `StateStore`, to resume the authorization in the callback
+    /// # Arguments
+    /// * `state` - String - The state saved with the URL
+    /// * `verifier` - String - The verifier saved with the state
+    /// # Return
+    /// `VerifierNotFound` when the state or the verifier is empty
+    pub fn restore(state: String, verifier: String) -> Result<Self, OauthError> {
+        if state.is_empty() || verifier.is_empty() {
+            return Err(OauthError::VerifierNotFound);
+        }
+        Ok(StateAuth {
+            url_generated: None,
+            state,
+            verifier,
+            return_to: None,
+            fingerprint: None,
+            nonce: None,
+        })
+    }
+
+    /// Set the URL saved with the state
+    pub fn with_url_generated(mut self, url_generated: Option<String>) -> Self {
+        self.url_generated = url_generated;
+        self
+    }
+
+    /// Set the `return_to` saved with the state
+    pub fn with_return_to(mut self, return_to: Option<String>) -> Self {
+        self.return_to = return_to;
+        self
+    }
+
+    /// Set the fingerprint saved with the state
+    pub fn with_fingerprint(mut self, fingerprint: Option<String>) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    /// Set the nonce saved with the state
+    pub fn with_nonce(mut self, nonce: Option<String>) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// URL to redirect the user to the provider, None when the state was loaded without it
+    pub fn url_generated(&self) -> Option<&str> {
+        self.url_generated.as_deref()
+    }
+
+    /// The state sent in the URL, the key used to save the authorization
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    /// The PKCE verifier, sent in the code exchange
+    pub fn verifier(&self) -> &str {
+        &self.verifier
+    }
+
+    /// URL to redirect the user after the login, already validated with the redirect policy
+    pub fn return_to(&self) -> Option<&str> {
+        self.return_to.as_deref()
+    }
+
+    /// Fingerprint of the client that started the login, compared in the callback with `verify_fingerprint`
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.fingerprint.as_deref()
+    }
+
+    /// `nonce` sent in the authorization URL, the ID token of the OpenID providers needs to have the same nonce
+    pub fn nonce(&self) -> Option<&str> {
+        self.nonce.as_deref()
+    }
+}
+
+/// The state and the verifier are masked, and the URL is shown without the query
+impl fmt::Debug for StateAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateAuth")
+            .field(
+                "url_generated",
+                &self.url_generated.as_deref().map(RedactedUrl),
+            )
+            .field("state", &Redacted(&self.state))
+            .field("verifier", &Redacted(&self.verifier))
+            .field("return_to", &self.return_to)
+            .field("fingerprint", &self.fingerprint)
+            .field("nonce", &self.nonce)
+            .finish()
+    }
+}
+
+/// The client secrets and the values of the auth params are masked, only the last 4 characters are shown
+impl fmt::Debug for CustomProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomProvider")
+            .field("auth_url", &self.auth_url)
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &Redacted(&self.client_secret))
+            .field(
+                "secondary_client_secret",
+                &redacted(&self.secondary_client_secret),
+            )
+            .field("redirect_url", &self.redirect_url)
+            .field("state", &self.state)
+            .field("scope_delimiter", &self.scope_delimiter)
+            .field("quirks", &self.quirks)
+            .field("redirect_policy", &self.redirect_policy)
+            .field("return_to", &self.return_to)
+            .field("client_info", &self.client_info)
+            .field("device_authorization_url", &self.device_authorization_url)
+            .field("auth_params", &RedactedParams(&self.auth_params))
+            .field("public_client", &self.public_client)
+            .field("response_type", &self.response_type)
+            .field("response_mode", &self.response_mode)
+            .field("default_scopes", &self.default_scopes)
+            .field("user_info", &self.user_info)
+            .field("token_url_fallbacks", &self.token_url_fallbacks)
+            .finish_non_exhaustive()
+    }
 }
 
 impl CustomProvider {
@@ -177,13 +606,377 @@ impl CustomProvider {
         client_secret: String,
         redirect_url: String,
     ) -> Self {
-        CustomProvider {
+        let mut provider = CustomProvider {
             auth_url,
             token_url,
             client_id,
             client_secret,
             redirect_url,
             state: None,
+            client: Arc::new(Err(OauthError::AuthUrlCreationFailed)),
+            secondary_client_secret: None,
+            secondary_client: None,
+            on_secret_used: None,
+            scope_delimiter: ScopeDelimiter::Space,
+            auth_type: AuthType::BasicAuth,
+            quirks: Quirks::new(),
+            redirect_policy: None,
+            return_to: None,
+            state_generator: StateGenerator::default(),
+            pkce_generator: PkceGenerator::default(),
+            fingerprinter: Fingerprinter::Default,
+            client_info: None,
+            device_authorization_url: None,
+            auth_params: Vec::new(),
+            public_client: false,
+            response_type: None,
+            response_mode: None,
+            default_scopes: Scopes::new(),
+            user_info: None,
+            token_url_fallbacks: Vec::new(),
+            dedup: Dedup::new(Duration::ZERO),
+        };
+        provider.build_clients();
+        provider
+    }
+
+    /// Authorization URL of the provider, where the user is redirected to login
+    pub fn auth_url(&self) -> &str {
+        &self.auth_url
+    }
+
+    /// Token URL of the provider, where the code is exchanged for the token
+    pub fn token_url(&self) -> &str {
+        &self.token_url
+    }
+
+    /// Client id of the application in the provider
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    /// URL of the callback, sent in the authorization URL and in the code exchange
+    pub fn redirect_url(&self) -> &str {
+        &self.redirect_url
+    }
+
+    /// Replace the client secret, the oauth2 client is created again with it
+    pub fn set_client_secret(mut self, client_secret: String) -> Self {
+        self.client_secret = client_secret;
+        self.build_clients();
+        self
+    }
+
+    /// Set the character used to join the scopes in the URL, the default is space
+    pub fn set_scope_delimiter(mut self, scope_delimiter: ScopeDelimiter) -> Self {
+        self.scope_delimiter = scope_delimiter;
+        self
+    }
+
+    /// Set how the client_id and client_secret are sent to the token endpoint, the default is BasicAuth
+    pub fn set_auth_type(mut self, auth_type: AuthType) -> Self {
+        self.auth_type = auth_type;
+        self.build_clients();
+        self
+    }
+
+    /// Don't send the client secret to the token endpoint, for public clients that only use PKCE.
+    /// The client_id is sent in the body
+    pub fn set_public_client(mut self) -> Self {
+        self.public_client = true;
+        self.build_clients();
+        self
+    }
+
+    /// Set the `response_type` of the authorization URL, the default is `code`.
+    /// For the hybrid flow (`code id_token`) the provider requires a `nonce`, add it with `set_auth_param`
+    pub fn set_response_type(mut self, response_type: &str) -> Self {
+        self.response_type = Some(response_type.to_string());
+        self
+    }
+
+    /// Set how the provider returns the parameters to the callback, the default is in the query
+    pub fn set_response_mode(mut self, response_mode: ResponseMode) -> Self {
+        self.response_mode = Some(response_mode);
+        self
+    }
+
+    /// Set the scopes used when the `generate_url` receives no scopes
+    pub fn set_default_scopes(mut self, scopes: impl Into<Scopes>) -> Self {
+        self.default_scopes = scopes.into();
+        self
+    }
+
+    /// Set the user endpoint used by `get_user`
+    pub fn set_user_info(mut self, user_info: UserInfo) -> Self {
+        self.user_info = Some(user_info);
+        self
+    }
+
+    /// Set the differences from the spec that the provider has
+    pub fn set_quirks(mut self, quirks: Quirks) -> Self {
+        self.quirks = quirks;
+        self
+    }
+
+    /// Set the policy used to validate the redirect URL and the `return_to` values.
+    /// When it is set, an unsafe redirect URL fails in the client creation
+    pub fn set_redirect_policy(mut self, redirect_policy: RedirectPolicy) -> Self {
+        self.redirect_policy = Some(redirect_policy);
+        self.build_clients();
+        self
+    }
+
+    /// Set the URL that the user wants to go after the login, it is saved with the state in the `generate_url`.
+    /// Without a redirect policy only relative paths are accepted
+    pub fn set_return_to(mut self, return_to: &str) -> Result<Self, OauthError> {
+        let return_to = self
+            .redirect_policy
+            .clone()
+            .unwrap_or_default()
+            .validate_return_to(return_to)?;
+        self.return_to = Some(return_to);
+        Ok(self)
+    }
+
+    /// Add a parameter to the authorization URL of this login, like `prompt` or `login_hint`.
+    /// A parameter with the same name is replaced
+    pub fn set_auth_param(mut self, name: &str, value: &str) -> Self {
+        self.auth_params.retain(|(param, _)| param != name);
+        self.auth_params.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Generate the URL with `prompt=none`, the provider returns to the callback without showing any page.
+    /// Use `CallbackParams::silent_result` in the callback to know if the user needs to login again
+    pub async fn silent_auth_url<S, F, Fut>(
+        self,
+        scopes: S,
+        save: F,
+    ) -> Result<Box<Self>, OauthError>
+    where
+        S: Into<Scopes> + Send,
+        F: FnOnce(StateAuth) -> Fut + Send,
+        Fut: Future<Output = ()> + Send,
+    {
+        self.set_prompt(openid::Prompt::None)
+            .generate_url(scopes, save)
+            .await
+    }
+
+    /// Set the function used to create the `state` value
+    pub fn set_state_generator(mut self, state_generator: StateGenerator) -> Self {
+        self.state_generator = state_generator;
+        self
+    }
+
+    /// Set where the code exchanges and callbacks are deduplicated, the default is only for this provider and its
+    /// clones, without keeping the finished exchanges. Share the same `Dedup` between the providers that can
+    /// receive the same callback, the tokens are kept in memory for the window of the `Dedup`
+    pub fn set_dedup(mut self, dedup: Dedup) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Set the length and charset of the PKCE verifier
+    pub fn set_pkce_generator(mut self, pkce_generator: PkceGenerator) -> Self {
+        self.pkce_generator = pkce_generator;
+        self
+    }
+
+    /// Set the function used to create the fingerprint of the client
+    pub fn set_fingerprinter(mut self, fingerprinter: Fingerprinter) -> Self {
+        self.fingerprinter = fingerprinter;
+        self
+    }
+
+    /// Bind the state to the client that is starting the login, the fingerprint is saved with the state in the `generate_url`
+    pub fn set_client_info(mut self, client_info: ClientInfo) -> Self {
+        self.client_info = Some(client_info);
+        self
+    }
+
+    /// Compare the fingerprint saved with the state with the client of the callback.
+    /// When the state was saved without a fingerprint nothing is checked
+    pub fn verify_fingerprint(
+        &self,
+        fingerprint: Option<&str>,
+        client_info: &ClientInfo,
+    ) -> Result<(), OauthError> {
+        match fingerprint {
+            Some(fingerprint) => self.fingerprinter.verify(fingerprint, client_info),
+            None => Ok(()),
+        }
+    }
+
+    /// Set a second client secret, used when the provider rejects the client_secret in the token exchange.
+    /// This allows rotating the secret without downtime: add the new secret here, update it in the provider and after that move it to client_secret
+    pub fn set_secondary_client_secret(mut self, client_secret: String) -> Self {
+        self.secondary_client_secret = Some(client_secret);
+        self.build_clients();
+        self
+    }
+
+    /// Set a function that is called with the secret that generated the token, so you can know when the old secret is not used anymore
+    pub fn set_on_secret_used<F>(mut self, on_secret_used: F) -> Self
+    where
+        F: Fn(SecretUsed) + Send + Sync + 'static,
+    {
+        self.on_secret_used = Some(Arc::new(on_secret_used));
+        self
+    }
+
+    /// Set the device authorization endpoint, used by the device flow
+    pub fn set_device_authorization_url(mut self, device_authorization_url: &str) -> Self {
+        self.device_authorization_url = Some(device_authorization_url.to_string());
+        self.build_clients();
+        self
+    }
+
+    /// Set other URLs of the token endpoint, like regional mirrors or a backup gateway.
+    /// When the connection to the token URL fails, the request is sent to these URLs in order.
+    /// Only connection errors are retried, so a code is never sent twice to the provider
+    pub fn set_token_url_fallbacks(mut self, token_urls: &[&str]) -> Self {
+        self.token_url_fallbacks = token_urls.iter().map(|url| url.to_string()).collect();
+        self
+    }
+
+    /// Return the error of the oauth2 client created with the provider, so an invalid URL fails at startup
+    /// instead of in the first request
+    pub fn build(self) -> Result<Self, OauthError> {
+        self.get_client()?;
+        if let Some(Err(error)) = self.get_secondary_client() {
+            return Err(error);
+        }
+        Ok(self)
+    }
+
+    /// Create the oauth2 clients, in `new` and in the setters that change them
+    fn build_clients(&mut self) {
+        self.client = Arc::new(self.create_client(&self.client_secret));
+        self.secondary_client = self
+            .secondary_client_secret
+            .as_ref()
+            .map(|client_secret| Arc::new(self.create_client(client_secret)));
+    }
+
+    fn get_secondary_client(&self) -> Option<Result<&ProviderClient, OauthError>> {
+        self.secondary_client
+            .as_ref()
+            .map(|client| client.as_ref().as_ref().map_err(|e| e.clone()))
+    }
+
+    fn report_secret_used(&self, secret_used: SecretUsed) {
+        if let Some(on_secret_used) = &self.on_secret_used {
+            on_secret_used(secret_used);
+        }
+    }
+
+    fn create_client(&self, client_secret: &str) -> Result<ProviderClient, OauthError> {
+        if let Some(redirect_policy) = &self.redirect_policy {
+            redirect_policy.validate_redirect_url(&self.redirect_url)?;
+        }
+        let client = ProviderClient::new(
+            ClientId::new(self.client_id.clone()),
+            Some(ClientSecret::new(client_secret.to_string())).filter(|_| !self.public_client),
+            AuthUrl::new(self.auth_url.clone()).map_err(|_| OauthError::AuthUrlCreationFailed)?,
+            Some(
+                TokenUrl::new(self.token_url.clone())
+                    .map_err(|_| OauthError::TokenUrlCreationFailed)?,
+            ),
+        )
+        .set_auth_type(self.auth_type.clone())
+        .set_redirect_uri(
+            RedirectUrl::new(self.redirect_url.clone())
+                .map_err(|_| OauthError::RedirectUrlCreationFailed)?,
+        );
+        match &self.device_authorization_url {
+            Some(device_authorization_url) => Ok(client.set_device_authorization_url(
+                DeviceAuthorizationUrl::new(device_authorization_url.clone())
+                    .map_err(|_| OauthError::DeviceAuthorizationUrlCreationFailed)?,
+            )),
+            None => Ok(client),
+        }
+    }
+
+    pub(crate) async fn send(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        let fallbacks = match oauth2::url::Url::parse(&self.token_url) {
+            Ok(token_url) if token_url == request.url => self.token_url_fallbacks.as_slice(),
+            _ => &[],
+        };
+        let mut response = self.send_once(request.clone()).await;
+        for fallback in fallbacks {
+            match &response {
+                Err(oauth2::reqwest::Error::Reqwest(error)) if error.is_connect() => {}
+                _ => break,
+            }
+            let Ok(url) = oauth2::url::Url::parse(fallback) else {
+                continue;
+            };
+            response = self
+                .send_once(HttpRequest {
+                    url,
+                    ..request.clone()
+                })
+                .await;
+        }
+        response
+    }
+
+    async fn send_once(&self, mut request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        request.body = self.quirks.apply_to_body(request.body);
+        #[cfg(feature = "otel")]
+        {
+            use opentelemetry::trace::FutureExt;
+
+            let cx = otel::start_span(
+                "oauth.token",
+                request.method.as_str(),
+                &request.url,
+                &mut request.headers,
+            );
+            let response = async_http_client(request).with_context(cx.clone()).await;
+            otel::end_span(&cx, response.as_ref().ok().map(|r| r.status_code.as_u16()));
+            response.map(|response| self.quirks.apply_to_response(response))
+        }
+        #[cfg(not(feature = "otel"))]
+        async_http_client(request)
+            .await
+            .map(|response| self.quirks.apply_to_response(response))
+    }
+
+    /// Execute the token request, retrying with the secondary client secret when the provider rejects the client
+    async fn request_token<'a, F, Fut>(
+        &'a self,
+        request: F,
+    ) -> Result<ProviderTokenResponse, OauthError>
+    where
+        F: Fn(&'a ProviderClient) -> Fut,
+        Fut: Future<Output = Result<ProviderTokenResponse, TokenRequestError>>,
+    {
+        match request(self.get_client()?).await {
+            Ok(token) => {
+                self.report_secret_used(SecretUsed::Primary);
+                Ok(token)
+            }
+            // Only a rejected client can be retried, in other errors the code could already be used
+            Err(RequestTokenError::ServerResponse(response))
+                if matches!(
+                    response.error(),
+                    BasicErrorResponseType::InvalidClient
+                        | BasicErrorResponseType::UnauthorizedClient
+                ) =>
+            {
+                let client = self
+                    .get_secondary_client()
+                    .ok_or(OauthError::TokenRequestFailed)??;
+                let token = request(client)
+                    .await
+                    .map_err(|_| OauthError::TokenRequestFailed)?;
+                self.report_secret_used(SecretUsed::Secondary);
+                Ok(token)
+            }
+            Err(_) => Err(OauthError::TokenRequestFailed),
         }
     }
 }
@@ -191,7 +984,8 @@ impl CustomProvider {
 /// OAuthClient is the main struct of the lib, it will handle all the connection with the provider
 #[async_trait]
 pub trait OAuthClient {
-    fn get_client(&self) -> Result<BasicClient, OauthError>;
+    /// Get the oauth2 client, it is created with the provider and reused in every request
+    fn get_client(&self) -> Result<&ProviderClient, OauthError>;
 
     /// Get fields data from generated URL
     /// # Return
@@ -200,64 +994,141 @@ pub trait OAuthClient {
 
     /// Generate the URL to redirect the user to the provider
     /// # Arguments
-    /// * `scopes` - Vec<String> - The scopes that you want to access in the provider
+    /// * `scopes` - S - The scopes that you want to access in the provider, a Vec<String> or `Scopes`
     /// * `save` - F - The function that will use to save your state in the db/memory
-    async fn generate_url<F, Fut>(
-        mut self,
-        scopes: Vec<String>,
-        save: F,
-    ) -> Result<Box<Self>, OauthError>
+    async fn generate_url<S, F, Fut>(mut self, scopes: S, save: F) -> Result<Box<Self>, OauthError>
     where
+        S: Into<Scopes> + Send,
         F: FnOnce(StateAuth) -> Fut + Send,
         Fut: Future<Output = ()> + Send;
 
+    /// Start the authorization, generating the state, the verifier and the URL
+    /// # Arguments
+    /// * `scopes` - S - The scopes that you want to access in the provider
+    /// # Return
+    /// PendingAuthorization - The only way to exchange the code of the callback
+    async fn authorize<S>(&self, scopes: S) -> Result<PendingAuthorization<Self>, OauthError>
+    where
+        Self: Clone + Sized + Send + Sync,
+        S: Into<Scopes> + Send,
+    {
+        let provider = self.clone().generate_url(scopes, |_| async {}).await?;
+        let state = provider
+            .get_state()
+            .ok_or(OauthError::AuthUrlCreationFailed)?;
+        if state.url_generated.is_none() {
+            return Err(OauthError::AuthUrlCreationFailed);
+        }
+        PendingAuthorization::resume(*provider, state)
+    }
+
     /// Generate the token from the code and verifier
     /// # Arguments
     /// * `code` - String - The code that the provider will return after the user accept the auth
     /// * `verifier` - String - The verifier that was generated in the first step
     /// # Return
     /// The token generated
+    #[deprecated(
+        since = "0.2.0",
+        note = "use `authorize` and `PendingAuthorization::exchange`, that can't be called without the verifier"
+    )]
     async fn generate_token(&self, code: String, verifier: String) -> Result<String, OauthError>;
+
+    /// Same as `generate_token`, but returning all the fields of the token response, like the refresh token
+    /// # Arguments
+    /// * `code` - String - The code that the provider will return after the user accept the auth
+    /// * `verifier` - String - The verifier that was generated in the first step
+    /// # Return
+    /// TokenSet - The access token, refresh token, expiration and the other fields returned
+    #[deprecated(
+        since = "0.2.0",
+        note = "use `authorize` and `PendingAuthorization::exchange`, that can't be called without the verifier"
+    )]
+    async fn generate_token_set(
+        &self,
+        code: String,
+        verifier: String,
+    ) -> Result<TokenSet, OauthError>;
+
+    /// Exchange the code with the state saved in the first step, it is used by `PendingAuthorization::exchange`.
+    /// The OpenID providers also compare the `nonce` of the state with the nonce of the ID token
+    /// # Arguments
+    /// * `code` - String - The code that the provider will return after the user accept the auth
+    /// * `state` - &StateAuth - The state generated in the first step
+    /// # Return
+    /// TokenSet - The access token, refresh token, expiration and the other fields returned
+    async fn exchange_code(&self, code: String, state: &StateAuth) -> Result<TokenSet, OauthError>
+    where
+        Self: Sync,
+    {
+        #[allow(deprecated)]
+        self.generate_token_set(code, state.verifier.clone()).await
+    }
+
+    /// Generate a new token from the refresh token
+    /// # Arguments
+    /// * `refresh_token` - String - The refresh token returned with the previous token
+    /// # Return
+    /// TokenSet - The new token, some providers also return a new refresh token
+    async fn refresh_token(&self, refresh_token: String) -> Result<TokenSet, OauthError>;
 }
 
 #[async_trait]
 impl OAuthClient for CustomProvider {
-    fn get_client(&self) -> Result<BasicClient, OauthError> {
-        Ok(BasicClient::new(
-            ClientId::new(self.client_id.clone()),
-            Some(ClientSecret::new(self.client_secret.clone())),
-            AuthUrl::new(self.auth_url.clone()).map_err(|_| OauthError::AuthUrlCreationFailed)?,
-            Some(TokenUrl::new(self.token_url.clone()).unwrap()),
-        )
-        .set_redirect_uri(RedirectUrl::new(self.redirect_url.clone()).unwrap()))
+    fn get_client(&self) -> Result<&ProviderClient, OauthError> {
+        self.client.as_ref().as_ref().map_err(|e| e.clone())
     }
 
     fn get_state(&self) -> Option<StateAuth> {
         self.state.clone()
     }
 
-    async fn generate_url<F, Fut>(
-        mut self,
-        scopes: Vec<String>,
-        save: F,
-    ) -> Result<Box<Self>, OauthError>
+    async fn generate_url<S, F, Fut>(mut self, scopes: S, save: F) -> Result<Box<Self>, OauthError>
     where
+        S: Into<Scopes> + Send,
         F: FnOnce(StateAuth) -> Fut + Send,
         Fut: Future<Output = ()> + Send,
     {
-        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let mut scopes: Scopes = scopes.into();
+        if scopes.is_empty() {
+            scopes = self.default_scopes.clone();
+        }
+        scopes.validate(self.scope_delimiter)?;
+        let (pkce_challenge, pkce_verifier) = self.pkce_generator.generate()?;
 
-        let binding = self.get_client();
-        let (auth_url, csrf_token) = binding?
-            .authorize_url(CsrfToken::new_random)
-            .add_scopes(scopes.into_iter().map(Scope::new).collect::<Vec<Scope>>())
-            .set_pkce_challenge(pkce_challenge)
-            .url();
+        let mut request = self
+            .get_client()?
+            .authorize_url(|| self.state_generator.generate())
+            .set_pkce_challenge(pkce_challenge);
+        if !scopes.is_empty() {
+            request = request.add_scope(Scope::new(scopes.join(self.scope_delimiter)));
+        }
+        if let Some(response_type) = &self.response_type {
+            request = request.set_response_type(&ResponseType::new(response_type.clone()));
+        }
+        if let Some(response_mode) = self.response_mode {
+            request = request.add_extra_param("response_mode", response_mode.as_str());
+        }
+        for (name, value) in &self.auth_params {
+            request = request.add_extra_param(name, value);
+        }
+        let (auth_url, csrf_token) = request.url();
+        let auth_url = self.quirks.apply_to_url(auth_url);
 
         let state = StateAuth {
             url_generated: Some(auth_url.to_string()),
             state: csrf_token.secret().to_string(),
             verifier: pkce_verifier.secret().to_string(),
+            return_to: self.return_to.clone(),
+            fingerprint: self
+                .client_info
+                .as_ref()
+                .map(|client_info| self.fingerprinter.fingerprint(client_info)),
+            nonce: self
+                .auth_params
+                .iter()
+                .find(|(name, _)| name == "nonce")
+                .map(|(_, nonce)| nonce.clone()),
         };
 
         self.state = Some(state.clone());
@@ -267,13 +1138,49 @@ impl OAuthClient for CustomProvider {
     }
 
     async fn generate_token(&self, code: String, verifier: String) -> Result<String, OauthError> {
-        let token = self
-            .get_client()?
-            .exchange_code(AuthorizationCode::new(code.clone()))
-            .set_pkce_verifier(PkceCodeVerifier::new(verifier.clone()))
-            .request_async(async_http_client)
+        #[allow(deprecated)]
+        Ok(self.generate_token_set(code, verifier).await?.access_token)
+    }
+
+    /// Fails with `VerifierNotFound` when the verifier is empty, the provider would reject the code
+    async fn generate_token_set(
+        &self,
+        code: String,
+        verifier: String,
+    ) -> Result<TokenSet, OauthError> {
+        if verifier.is_empty() {
+            return Err(OauthError::VerifierNotFound);
+        }
+        // The same code received twice is exchanged only once, the code can't be used again.
+        // The verifier is part of the key, so only the callers with the verifier get the token
+        self.dedup
+            .exchanges
+            .run(
+                &format!("{}:{}:{code}:{verifier}", self.token_url, self.client_id),
+                || async {
+                    let token = self
+                        .request_token(|client| {
+                            client
+                                .exchange_code(AuthorizationCode::new(code.clone()))
+                                .set_pkce_verifier(PkceCodeVerifier::new(verifier.clone()))
+                                .request_async(|request| self.send(request))
+                        })
+                        .await?;
+                    Ok(token.into())
+                },
+            )
             .await
-            .map_err(|_| OauthError::TokenRequestFailed)?;
-        Ok(token.access_token().secret().to_string())
+    }
+
+    async fn refresh_token(&self, refresh_token: String) -> Result<TokenSet, OauthError> {
+        let refresh_token = RefreshToken::new(refresh_token);
+        let token = self
+            .request_token(|client| {
+                client
+                    .exchange_refresh_token(&refresh_token)
+                    .request_async(|request| self.send(request))
+            })
+            .await?;
+        Ok(token.into())
     }
 }
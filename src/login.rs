@@ -0,0 +1,166 @@
+//! High-level login flow
+//!
+//! `begin_login` and `complete_login` do all the security steps of the two-step flow: the state is saved in the
+//! store and consumed only once, it needs to be from the same provider (and client, with the fingerprint),
+//! the errors returned by the provider are handled, and the token is exchanged with the saved verifier.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::response::Redirect;
+
+use crate::callback::CallbackParams;
+use crate::error::OauthError;
+use crate::fingerprint::ClientInfo;
+use crate::flow::PendingAuthorization;
+use crate::scopes::Scopes;
+use crate::store::StateStore;
+use crate::token::TokenSet;
+use crate::user::OAuthUser;
+use crate::{CustomProvider, OAuthClient};
+
+/// Options of the login
+#[derive(Clone, Debug, Default)]
+pub struct LoginOptions {
+    /// Scopes requested, the default scopes of the provider are used when it is empty
+    pub scopes: Scopes,
+    /// URL that the user wants to go after the login, validated by the redirect policy of the provider
+    pub return_to: Option<String>,
+    /// Client that is starting and finishing the login, the state is bound to it when it is set
+    pub client_info: Option<ClientInfo>,
+    /// Get the user profile with `get_user` after the token exchange
+    pub fetch_user: bool,
+    /// Save the user in the app after the login, the user profile is fetched when it is set
+    pub upserter: Option<Arc<dyn UserUpserter>>,
+}
+
+/// Hook called after the login with the user profile, so the crate does the OAuth part and the app keeps its users
+#[async_trait]
+pub trait UserUpserter: Send + Sync {
+    /// Create or update the user of the app
+    /// # Arguments
+    /// * `provider` - &str - The name of the provider of the login
+    /// * `user` - &OAuthUser - The user profile returned by the provider
+    /// * `token` - &TokenSet - The token of the login, to save it with the user
+    /// # Return
+    /// The id of the user in the app, saved in the `LoginOutcome` (and in the session of the `LoginKit`)
+    /// or `UserUpsertFailed` when the user can't be saved, the login fails with it
+    async fn upsert(
+        &self,
+        provider: &str,
+        user: &OAuthUser,
+        token: &TokenSet,
+    ) -> Result<String, OauthError>;
+}
+
+impl std::fmt::Debug for dyn UserUpserter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UserUpserter")
+    }
+}
+
+/// Result of the login
+#[derive(Clone, Debug)]
+pub struct LoginOutcome {
+    pub token: TokenSet,
+    /// Only when `fetch_user` is enabled or there is an `upserter`
+    pub user: Option<OAuthUser>,
+    /// Id returned by the `upserter`
+    pub user_id: Option<String>,
+    /// The `return_to` saved in the `begin_login`
+    pub return_to: Option<String>,
+}
+
+/// Generate the URL, save the state in the store and redirect the user to the provider
+/// # Arguments
+/// * `provider_name` - &str - The name of the provider, saved with the state
+/// * `provider` - CustomProvider - The provider used to generate the URL
+/// * `store` - &dyn StateStore - The store where the state is saved
+/// * `opts` - &LoginOptions - The scopes, return_to and client of the login
+/// # Return
+/// The redirect to the provider
+pub async fn begin_login(
+    provider_name: &str,
+    mut provider: CustomProvider,
+    store: &dyn StateStore,
+    opts: &LoginOptions,
+) -> Result<Redirect, OauthError> {
+    if let Some(return_to) = &opts.return_to {
+        provider = provider.set_return_to(return_to)?;
+    }
+    if let Some(client_info) = &opts.client_info {
+        provider = provider.set_client_info(client_info.clone());
+    }
+    let state = provider
+        .generate_url(opts.scopes.clone(), |_| async {})
+        .await?
+        .get_state()
+        .ok_or(OauthError::AuthUrlCreationFailed)?;
+    let url = state
+        .url_generated
+        .clone()
+        .ok_or(OauthError::AuthUrlCreationFailed)?;
+    store.save(provider_name, state).await?;
+    Ok(Redirect::to(&url))
+}
+
+/// Validate the callback and exchange the code for the token
+/// # Arguments
+/// * `provider_name` - &str - The name of the provider used in the `begin_login`
+/// * `provider` - &CustomProvider - The provider used to generate the URL
+/// * `store` - &dyn StateStore - The store where the state was saved
+/// * `callback` - CallbackParams - The parameters received in the callback
+/// * `opts` - &LoginOptions - The client of the callback and if the user profile is fetched
+/// # Return
+/// LoginOutcome - The token, the user profile and the return_to
+pub async fn complete_login(
+    provider_name: &str,
+    provider: &CustomProvider,
+    store: &dyn StateStore,
+    callback: CallbackParams,
+    opts: &LoginOptions,
+) -> Result<LoginOutcome, OauthError> {
+    let state = callback.state.ok_or(OauthError::StateMismatch)?;
+    // The same callback received twice is handled only once
+    let _claim = provider
+        .dedup
+        .callbacks
+        .claim(&format!("{provider_name}:{state}"))
+        .ok_or(OauthError::DuplicateCallback)?;
+    // The state is removed even when the login fails, so it can't be used again
+    let stored = store
+        .take_for(provider_name, &state)
+        .await?
+        .ok_or(OauthError::StateMismatch)?;
+    if stored.state.state != state {
+        return Err(OauthError::StateMismatch);
+    }
+    if callback.error.is_some() {
+        return Err(OauthError::AuthorizationFailed);
+    }
+    if let Some(fingerprint) = stored.state.fingerprint.as_deref() {
+        let client_info = opts
+            .client_info
+            .as_ref()
+            .ok_or(OauthError::FingerprintMismatch)?;
+        provider.verify_fingerprint(Some(fingerprint), client_info)?;
+    }
+    let code = callback.code.ok_or(OauthError::AuthorizationFailed)?;
+    let token = PendingAuthorization::resume(provider.clone(), stored.state.clone())?
+        .exchange(code)
+        .await?;
+    let user = match opts.fetch_user || opts.upserter.is_some() {
+        true => Some(provider.get_user(&token.access_token).await?),
+        false => None,
+    };
+    let user_id = match (&opts.upserter, &user) {
+        (Some(upserter), Some(user)) => Some(upserter.upsert(provider_name, user, &token).await?),
+        _ => None,
+    };
+    Ok(LoginOutcome {
+        token,
+        user,
+        user_id,
+        return_to: stored.state.return_to,
+    })
+}
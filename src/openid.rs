@@ -0,0 +1,426 @@
+//! OpenID Connect helpers
+//!
+//! `IdTokenClaims::decode` reads the claims without verifying the signature. The [`CustomOpenIdProvider`]
+//! verifies the signature and the claims of the ID token with the [`IdTokenVerifier`] in the code exchange.
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use async_trait::async_trait;
+use oauth2::CsrfToken;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::discovery::ProviderMetadata;
+use crate::error::OauthError;
+use crate::jwt::JwksCache;
+use crate::scopes::Scopes;
+use crate::token::{ProviderClient, TokenSet};
+use crate::user::UserInfo;
+use crate::validation::ValidationOptions;
+use crate::{CustomProvider, OAuthClient, StateAuth};
+
+/// `aud` claim, it can be a string or a list
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl Audience {
+    pub fn contains(&self, client_id: &str) -> bool {
+        match self {
+            Audience::Single(aud) => aud == client_id,
+            Audience::Multiple(auds) => auds.iter().any(|aud| aud == client_id),
+        }
+    }
+}
+
+/// Claims of the ID token
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: Audience,
+    pub exp: u64,
+    pub iat: u64,
+    pub nbf: Option<u64>,
+    pub auth_time: Option<u64>,
+    pub nonce: Option<String>,
+    /// Authentication context class that the authentication satisfied
+    pub acr: Option<String>,
+    /// Claims that are not validated by the lib, like `email` or `name`
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl IdTokenClaims {
+    /// Decode the payload of the ID token, the signature is not verified
+    pub fn decode(id_token: &str) -> Result<Self, OauthError> {
+        let payload = id_token
+            .split('.')
+            .nth(1)
+            .ok_or(OauthError::InvalidIdToken)?;
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload.trim_end_matches('='))
+            .map_err(|_| OauthError::InvalidIdToken)?;
+        serde_json::from_slice(&payload).map_err(|_| OauthError::InvalidIdToken)
+    }
+
+    /// Decode the `id_token` returned with the token
+    pub fn from_token_set(token: &TokenSet) -> Result<Self, OauthError> {
+        IdTokenClaims::decode(token.id_token().ok_or(OauthError::InvalidIdToken)?)
+    }
+
+    /// Check that the user authenticated in the last `max_age` seconds, used with `set_max_age`.
+    /// The `auth_time` claim is required when the `max_age` is sent
+    pub fn check_max_age(&self, max_age: u64) -> Result<(), OauthError> {
+        self.check_max_age_with(max_age, &ValidationOptions::default())
+    }
+
+    /// Same of `check_max_age`, with the clock skew of the options
+    pub fn check_max_age_with(
+        &self,
+        max_age: u64,
+        validation: &ValidationOptions,
+    ) -> Result<(), OauthError> {
+        let auth_time = self.auth_time.ok_or(OauthError::InvalidIdToken)?;
+        if validation.is_older_than(auth_time, max_age) {
+            return Err(OauthError::AuthenticationTooOld);
+        }
+        Ok(())
+    }
+
+    /// Check that the `acr` claim is one of the values requested with `set_acr_values`
+    pub fn check_acr(&self, acr_values: &[&str]) -> Result<(), OauthError> {
+        match &self.acr {
+            Some(acr) if acr_values.contains(&acr.as_str()) => Ok(()),
+            _ => Err(OauthError::AcrNotSatisfied),
+        }
+    }
+}
+
+/// Value of the `prompt` parameter, Google and Discord use it too without the OpenID flow
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Prompt {
+    /// No page is shown, the login fails when the user needs to login or consent
+    None,
+    /// Ask the user to login again
+    Login,
+    /// Ask the consent again, even when the user already authorized the app
+    Consent,
+    /// Show the account chooser (Google and Microsoft)
+    SelectAccount,
+}
+
+impl Prompt {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Prompt::None => "none",
+            Prompt::Login => "login",
+            Prompt::Consent => "consent",
+            Prompt::SelectAccount => "select_account",
+        }
+    }
+}
+
+impl CustomProvider {
+    /// Send the `prompt` in the authorization URL, like `Prompt::SelectAccount` in Google or `Prompt::None` in Discord
+    pub fn set_prompt(self, prompt: Prompt) -> Self {
+        self.set_auth_param("prompt", prompt.as_str())
+    }
+
+    /// Send the `max_age` in the authorization URL, the provider asks the user to login again
+    /// when the last authentication is older than it. Check the `auth_time` with `IdTokenClaims::check_max_age`
+    pub fn set_max_age(self, max_age: u64) -> Self {
+        self.set_auth_param("max_age", &max_age.to_string())
+    }
+
+    /// Request the authentication context classes, in order of preference, like `mfa`.
+    /// Check the `acr` returned with `IdTokenClaims::check_acr`
+    pub fn set_acr_values(self, acr_values: &[&str]) -> Self {
+        self.set_auth_param("acr_values", &acr_values.join(" "))
+    }
+
+    /// Wrap the provider with the OpenID behavior, so any preset can verify the ID token and use the userinfo:
+    /// ```ignore
+    /// let provider = GoogleProvider::new(client_id, client_secret, redirect_url)
+    ///     .into_openid("https://accounts.google.com", "https://www.googleapis.com/oauth2/v3/certs");
+    /// ```
+    /// # Arguments
+    /// * `issuer` - &str - The issuer of the ID tokens
+    /// * `jwks_uri` - &str - The keys used to verify the ID tokens
+    pub fn into_openid(self, issuer: &str, jwks_uri: &str) -> CustomOpenIdProvider {
+        CustomOpenIdProvider::new(self, issuer.to_string(), JwksCache::new(jwks_uri))
+    }
+
+    /// Send the ID token of the current session as `id_token_hint`, used in the silent re-authentication
+    /// to tell the provider which user is expected
+    pub fn set_id_token_hint(self, id_token: &str) -> Self {
+        self.set_auth_param("id_token_hint", id_token)
+    }
+}
+
+/// Generate the URL of the provider logout (RP-Initiated Logout)
+/// # Arguments
+/// * `end_session_endpoint` - &str - The `end_session_endpoint` of the provider
+/// * `id_token_hint` - Option<&str> - The raw ID token of the session, many providers skip the confirmation page with it
+/// * `post_logout_redirect_uri` - Option<&str> - Where the provider sends the user after the logout, it needs to be registered
+/// # Return
+/// The URL to redirect the user
+pub fn end_session_url(
+    end_session_endpoint: &str,
+    id_token_hint: Option<&str>,
+    post_logout_redirect_uri: Option<&str>,
+) -> Result<String, OauthError> {
+    let mut url = oauth2::url::Url::parse(end_session_endpoint)
+        .map_err(|_| OauthError::EndSessionUrlCreationFailed)?;
+    if let Some(id_token_hint) = id_token_hint {
+        url.query_pairs_mut()
+            .append_pair("id_token_hint", id_token_hint);
+    }
+    if let Some(post_logout_redirect_uri) = post_logout_redirect_uri {
+        url.query_pairs_mut()
+            .append_pair("post_logout_redirect_uri", post_logout_redirect_uri);
+    }
+    Ok(url.to_string())
+}
+
+/// Verify the signature and the claims of the ID token: `iss`, `aud`, `exp`, `nbf`, `iat` and `nonce`
+#[derive(Clone)]
+pub struct IdTokenVerifier {
+    pub jwks: JwksCache,
+    pub issuer: String,
+    pub client_id: String,
+    /// Tolerance in the time claims
+    pub validation: ValidationOptions,
+}
+
+impl IdTokenVerifier {
+    pub fn new(jwks: JwksCache, issuer: String, client_id: String) -> Self {
+        IdTokenVerifier {
+            jwks,
+            issuer,
+            client_id,
+            validation: ValidationOptions::default(),
+        }
+    }
+
+    /// Verify the ID token and return its claims
+    /// # Arguments
+    /// * `id_token` - &str - The raw ID token returned with the token
+    /// * `nonce` - &str - The nonce saved with the state, a token without it or with other nonce is rejected
+    pub async fn verify(&self, id_token: &str, nonce: &str) -> Result<IdTokenClaims, OauthError> {
+        let claims: IdTokenClaims = self.jwks.verify(id_token).await?;
+        if claims.iss != self.issuer
+            || claims.nonce.as_deref() != Some(nonce)
+            || !claims.aud.contains(&self.client_id)
+            || self.validation.is_expired(claims.exp)
+            || self.validation.is_in_future(claims.iat)
+            || claims
+                .nbf
+                .is_some_and(|nbf| self.validation.is_in_future(nbf))
+        {
+            return Err(OauthError::InvalidIdToken);
+        }
+        Ok(claims)
+    }
+}
+
+/// Claims returned by the userinfo endpoint, the fields depend on the scopes
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserInfoClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub picture: Option<String>,
+    pub locale: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Provider of OpenID Connect, it is a `CustomProvider` with the issuer and the userinfo endpoint
+#[derive(Clone)]
+pub struct CustomOpenIdProvider {
+    pub provider: CustomProvider,
+    pub issuer: String,
+    pub user_url: Option<String>,
+    verifier: IdTokenVerifier,
+}
+
+impl CustomOpenIdProvider {
+    /// Create the provider, the ID token of every code exchange is verified with the keys of the JWKS
+    /// # Arguments
+    /// * `provider` - CustomProvider - The provider with the endpoints and credentials
+    /// * `issuer` - String - The issuer of the ID tokens
+    /// * `jwks` - JwksCache - The keys of the provider
+    pub fn new(provider: CustomProvider, issuer: String, jwks: JwksCache) -> Self {
+        let verifier = IdTokenVerifier::new(jwks, issuer.clone(), provider.client_id.clone());
+        CustomOpenIdProvider {
+            provider,
+            issuer,
+            user_url: None,
+            verifier,
+        }
+    }
+
+    /// Create the provider with the endpoints of the discovery document, it fails when there is no `jwks_uri`
+    /// # Arguments
+    /// * `metadata` - &ProviderMetadata - The discovery document of the issuer
+    /// * `client_id` - String - The client id of the application
+    /// * `client_secret` - String - The client secret of the application
+    /// * `redirect_url` - String - The redirect url of the application
+    pub fn from_metadata(
+        metadata: &ProviderMetadata,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<Self, OauthError> {
+        let provider = metadata.to_provider(client_id, client_secret, redirect_url)?;
+        let provider = match &metadata.userinfo_endpoint {
+            Some(userinfo_endpoint) => provider.set_user_info(UserInfo::new(userinfo_endpoint)),
+            None => provider,
+        };
+        let jwks_uri = metadata
+            .jwks_uri
+            .as_deref()
+            .ok_or(OauthError::DiscoveryFailed)?;
+        let mut openid =
+            CustomOpenIdProvider::new(provider, metadata.issuer.clone(), JwksCache::new(jwks_uri));
+        openid.user_url = metadata.userinfo_endpoint.clone();
+        Ok(openid)
+    }
+
+    /// Replace the `jwks_uri` of the provider, used to verify the ID token
+    pub fn set_jwks_uri(self, jwks_uri: &str) -> Self {
+        self.set_jwks(JwksCache::new(jwks_uri))
+    }
+
+    /// Set the cache of the keys, to share the same cache between providers or to refresh it in background
+    pub fn set_jwks(mut self, jwks: JwksCache) -> Self {
+        self.verifier.jwks = jwks;
+        self
+    }
+
+    /// Set the clock skew accepted in the `exp`, `nbf` and `iat` of the ID token
+    pub fn set_validation_options(mut self, validation: ValidationOptions) -> Self {
+        self.verifier.validation = validation;
+        self
+    }
+
+    /// Generate the token and verify the ID token returned with it
+    /// # Arguments
+    /// * `code` - String - The code that the provider will return after the user accept the auth
+    /// * `state` - &StateAuth - The state generated in the first step, with the verifier and the nonce
+    /// # Return
+    /// The token and the verified claims of the ID token, `InvalidIdToken` when the state has no nonce
+    pub async fn generate_token_with_claims(
+        &self,
+        code: String,
+        state: &StateAuth,
+    ) -> Result<(TokenSet, IdTokenClaims), OauthError> {
+        let nonce = state.nonce.as_deref().ok_or(OauthError::InvalidIdToken)?;
+        let token = self.provider.exchange_code(code, state).await?;
+        let claims = self
+            .verifier
+            .verify(token.id_token().ok_or(OauthError::InvalidIdToken)?, nonce)
+            .await?;
+        Ok((token, claims))
+    }
+
+    /// Set the userinfo endpoint, it is also used by `get_user`
+    pub fn set_user_url(mut self, user_url: &str) -> Self {
+        self.user_url = Some(user_url.to_string());
+        self.provider = self.provider.set_user_info(UserInfo::new(user_url));
+        self
+    }
+
+    /// Get the claims of the userinfo endpoint
+    /// # Arguments
+    /// * `access_token` - &str - The access token generated in the callback
+    /// * `id_token_claims` - &IdTokenClaims - The claims of the ID token, the `sub` needs to be the same in both
+    /// # Return
+    /// UserInfoClaims - The claims of the user
+    pub async fn fetch_userinfo(
+        &self,
+        access_token: &str,
+        id_token_claims: &IdTokenClaims,
+    ) -> Result<UserInfoClaims, OauthError> {
+        let user_url = self
+            .user_url
+            .as_deref()
+            .ok_or(OauthError::UserInfoNotSupported)?;
+        let raw = UserInfo::new(user_url).fetch(access_token).await?;
+        let claims: UserInfoClaims =
+            serde_json::from_value(raw).map_err(|_| OauthError::UserInfoFailed)?;
+        // The response can be from another user when the token was substituted
+        if claims.sub != id_token_claims.sub {
+            return Err(OauthError::UserInfoSubjectMismatch);
+        }
+        Ok(claims)
+    }
+}
+
+#[async_trait]
+impl OAuthClient for CustomOpenIdProvider {
+    fn get_client(&self) -> Result<&ProviderClient, OauthError> {
+        self.provider.get_client()
+    }
+
+    fn get_state(&self) -> Option<StateAuth> {
+        self.provider.get_state()
+    }
+
+    async fn generate_url<S, F, Fut>(mut self, scopes: S, save: F) -> Result<Box<Self>, OauthError>
+    where
+        S: Into<Scopes> + Send,
+        F: FnOnce(StateAuth) -> Fut + Send,
+        Fut: Future<Output = ()> + Send,
+    {
+        let mut scopes: Scopes = scopes.into();
+        // Without scopes the provider would only receive `openid`, not the default scopes
+        if scopes.is_empty() {
+            scopes = self.provider.default_scopes.clone();
+        }
+        // A new nonce for every URL, it is saved with the state and compared with the ID token
+        let nonce = CsrfToken::new_random();
+        self.provider = *self
+            .provider
+            .set_auth_param("nonce", nonce.secret())
+            .generate_url(scopes.with("openid"), save)
+            .await?;
+        Ok(Box::new(self))
+    }
+
+    /// Fails with `UnsupportedFlow`, the ID token can't be verified without the nonce of the state
+    async fn generate_token(&self, code: String, verifier: String) -> Result<String, OauthError> {
+        #[allow(deprecated)]
+        Ok(self.generate_token_set(code, verifier).await?.access_token)
+    }
+
+    /// Fails with `UnsupportedFlow`, the ID token can't be verified without the nonce of the state.
+    /// Use `authorize` and `PendingAuthorization::exchange`
+    async fn generate_token_set(
+        &self,
+        _code: String,
+        _verifier: String,
+    ) -> Result<TokenSet, OauthError> {
+        Err(OauthError::UnsupportedFlow)
+    }
+
+    /// The ID token is verified with the nonce of the state, the token is not returned when it is invalid
+    async fn exchange_code(&self, code: String, state: &StateAuth) -> Result<TokenSet, OauthError> {
+        Ok(self.generate_token_with_claims(code, state).await?.0)
+    }
+
+    async fn refresh_token(&self, refresh_token: String) -> Result<TokenSet, OauthError> {
+        self.provider.refresh_token(refresh_token).await
+    }
+}
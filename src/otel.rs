@@ -0,0 +1,72 @@
+//! OpenTelemetry spans for the requests made by the lib
+//!
+//! Every outbound request creates a client span using the global tracer, and the current context
+//! is injected in the request headers with the global propagator, so the provider call shows up in the trace.
+
+use oauth2::http::header::{HeaderMap, HeaderName, HeaderValue};
+use oauth2::url::Url;
+use opentelemetry::global;
+use opentelemetry::propagation::Injector;
+use opentelemetry::trace::{SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+/// Start the span of the request and inject the context in the headers
+pub(crate) fn start_span(
+    name: &'static str,
+    method: &str,
+    url: &Url,
+    headers: &mut HeaderMap,
+) -> Context {
+    let tracer = global::tracer("oauth-axum");
+    // The query is removed because it can have credentials
+    let mut full_url = url.clone();
+    full_url.set_query(None);
+    let mut attributes = vec![
+        KeyValue::new("http.request.method", method.to_string()),
+        KeyValue::new("url.full", full_url.to_string()),
+    ];
+    if let Some(host) = url.host_str() {
+        attributes.push(KeyValue::new("server.address", host.to_string()));
+    }
+    let span = tracer
+        .span_builder(name)
+        .with_kind(SpanKind::Client)
+        .with_attributes(attributes)
+        .start_with_context(&tracer, &Context::current());
+    let cx = Context::current_with_span(span);
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&cx, &mut HeaderInjector(headers))
+    });
+    cx
+}
+
+/// End the span with the status code of the response, or as an error when there is no response
+pub(crate) fn end_span(cx: &Context, status_code: Option<u16>) {
+    let span = cx.span();
+    match status_code {
+        Some(status_code) => {
+            span.set_attribute(KeyValue::new(
+                "http.response.status_code",
+                i64::from(status_code),
+            ));
+            if status_code >= 400 {
+                span.set_status(Status::error(format!("HTTP {}", status_code)));
+            }
+        }
+        None => span.set_status(Status::error("request failed")),
+    }
+    span.end();
+}
@@ -0,0 +1,191 @@
+//! Pool of authorization URLs
+//!
+//! Landing pages that render the login buttons for every visitor can take the URLs from a `UrlPool`,
+//! generated in background with the states saved in one write to the store, instead of generating
+//! the state and verifier and writing to the store in every request.
+//!
+//! Every URL is used only once. The URLs of the pool are generated without `return_to` and client fingerprint,
+//! use `generate_url` for the logins that need them.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::OauthError;
+use crate::scopes::Scopes;
+use crate::store::StateStore;
+use crate::task::BackgroundTask;
+use crate::{CustomProvider, OAuthClient, StateAuth};
+
+struct PooledUrl {
+    state: String,
+    url: String,
+    created_at: Instant,
+}
+
+/// URLs generated ahead of time for a provider, the clones share the same URLs
+#[derive(Clone)]
+pub struct UrlPool {
+    provider_name: String,
+    provider: CustomProvider,
+    store: Arc<dyn StateStore>,
+    scopes: Scopes,
+    size: usize,
+    max_age: Duration,
+    items: Arc<Mutex<VecDeque<PooledUrl>>>,
+    /// Held by the `refill` in progress, so two refills don't generate the same missing URLs
+    refilling: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl UrlPool {
+    /// Create the pool, empty until the first `refill`. It keeps 64 URLs for 5 minutes
+    /// # Arguments
+    /// * `provider_name` - &str - The name of the provider, saved with the states
+    /// * `provider` - CustomProvider - The provider used to generate the URLs
+    /// * `store` - Arc<dyn StateStore> - The store where the states are saved
+    /// * `scopes` - impl Into<Scopes> - The scopes of the URLs, empty to use the default scopes of the provider
+    pub fn new(
+        provider_name: &str,
+        provider: CustomProvider,
+        store: Arc<dyn StateStore>,
+        scopes: impl Into<Scopes>,
+    ) -> Self {
+        UrlPool {
+            provider_name: provider_name.to_string(),
+            provider,
+            store,
+            scopes: scopes.into(),
+            size: 64,
+            max_age: Duration::from_secs(300),
+            items: Arc::new(Mutex::new(VecDeque::new())),
+            refilling: Arc::default(),
+        }
+    }
+
+    /// Set how many URLs are kept in the pool
+    pub fn set_size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set how long an URL stays in the pool, it needs to be lower than the ttl of the store,
+    /// so the state is still in the store when the user comes back
+    pub fn set_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, VecDeque<PooledUrl>>, OauthError> {
+        self.items.lock().map_err(|_| OauthError::StateStoreFailed)
+    }
+
+    async fn generate(&self) -> Result<StateAuth, OauthError> {
+        self.provider
+            .clone()
+            .generate_url(self.scopes.clone(), |_| async {})
+            .await?
+            .get_state()
+            .ok_or(OauthError::AuthUrlCreationFailed)
+    }
+
+    /// Remove the old URLs and generate the missing ones, saving the new states with `StateStore::save_many`.
+    /// A refill called while other is in progress waits for it
+    /// # Return
+    /// The count of URLs generated
+    pub async fn refill(&self) -> Result<usize, OauthError> {
+        let _refilling = self.refilling.lock().await;
+        self.remove_expired().await?;
+        let missing = self.size.saturating_sub(self.len()?);
+        let mut states = Vec::with_capacity(missing);
+        for _ in 0..missing {
+            states.push(self.generate().await?);
+        }
+        let urls = states
+            .iter()
+            .map(|state| {
+                Ok(PooledUrl {
+                    state: state.state.clone(),
+                    url: state
+                        .url_generated
+                        .clone()
+                        .ok_or(OauthError::AuthUrlCreationFailed)?,
+                    created_at: Instant::now(),
+                })
+            })
+            .collect::<Result<Vec<_>, OauthError>>()?;
+        self.store.save_many(&self.provider_name, states).await?;
+        self.lock()?.extend(urls);
+        Ok(missing)
+    }
+
+    /// Take an URL from the pool, when the pool is empty the URL is generated and saved right away
+    pub async fn next_url(&self) -> Result<String, OauthError> {
+        let max_age = self.max_age;
+        let pooled = {
+            let mut items = self.lock()?;
+            let mut pooled = None;
+            while let Some(item) = items.pop_front() {
+                if item.created_at.elapsed() < max_age {
+                    pooled = Some(item.url);
+                    break;
+                }
+            }
+            pooled
+        };
+        if let Some(url) = pooled {
+            return Ok(url);
+        }
+        let state = self.generate().await?;
+        let url = state
+            .url_generated
+            .clone()
+            .ok_or(OauthError::AuthUrlCreationFailed)?;
+        self.store.save(&self.provider_name, state).await?;
+        Ok(url)
+    }
+
+    /// Count of URLs in the pool
+    pub fn len(&self) -> Result<usize, OauthError> {
+        Ok(self.lock()?.len())
+    }
+
+    /// Return true when the pool has no URLs
+    pub fn is_empty(&self) -> Result<bool, OauthError> {
+        Ok(self.lock()?.is_empty())
+    }
+
+    /// Remove all the URLs of the pool and their states in the store, like after changing the scopes
+    /// or rotating the credentials of the provider. It waits for the `refill` in progress, so its URLs are removed too
+    pub async fn invalidate(&self) -> Result<(), OauthError> {
+        let _refilling = self.refilling.lock().await;
+        let states: Vec<String> = self.lock()?.drain(..).map(|item| item.state).collect();
+        self.store.remove_many(&states).await
+    }
+
+    async fn remove_expired(&self) -> Result<(), OauthError> {
+        let max_age = self.max_age;
+        let states: Vec<String> = {
+            let mut items = self.lock()?;
+            let (expired, fresh): (Vec<_>, Vec<_>) = items
+                .drain(..)
+                .partition(|item| item.created_at.elapsed() >= max_age);
+            items.extend(fresh);
+            expired.into_iter().map(|item| item.state).collect()
+        };
+        match states.is_empty() {
+            true => Ok(()),
+            false => self.store.remove_many(&states).await,
+        }
+    }
+
+    /// Spawn a task that refills the pool in every interval, it stops when the handle is dropped or shutdown
+    pub fn spawn_refiller(&self, interval: Duration) -> BackgroundTask {
+        let pool = self.clone();
+        BackgroundTask::spawn_interval_async(interval, move || {
+            let pool = pool.clone();
+            async move {
+                let _ = pool.refill().await;
+            }
+        })
+    }
+}
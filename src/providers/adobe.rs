@@ -0,0 +1,36 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct AdobeProvider {}
+
+impl AdobeProvider {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://ims-na1.adobelogin.com/ims/authorize/v2"),
+            String::from("https://ims-na1.adobelogin.com/ims/token/v3"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for AdobeProvider {
+    const NAME: &'static str = "adobe";
+    const ISSUER: Option<&'static str> = Some("https://ims-na1.adobelogin.com");
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(AdobeProvider::new(client_id, client_secret, redirect_url))
+    }
+}
@@ -0,0 +1,91 @@
+use crate::error::OauthError;
+use crate::openid::CustomOpenIdProvider;
+use crate::providers::{BaseUrl, ProviderPreset};
+use crate::user::UserInfo;
+use crate::CustomProvider;
+
+pub struct AuthentikProvider {}
+
+impl AuthentikProvider {
+    /// Create a new AuthentikProvider
+    ///
+    /// # Arguments
+    /// * `base_url` - The URL of the authentik server, like `https://auth.example.com`
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        base_url: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let base_url = base_url.trim_end_matches('/');
+        CustomProvider::new(
+            format!("{base_url}/application/o/authorize/"),
+            format!("{base_url}/application/o/token/"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_user_info(UserInfo::new(&format!(
+            "{base_url}/application/o/userinfo/"
+        )))
+    }
+
+    /// Create the provider with the OpenID behavior, the issuer and keys are per application
+    ///
+    /// # Arguments
+    /// * `base_url` - The URL of the authentik server
+    /// * `slug` - The slug of the application in authentik
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    pub fn openid(
+        base_url: &str,
+        slug: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomOpenIdProvider {
+        let base_url = base_url.trim_end_matches('/');
+        AuthentikProvider::new(base_url, client_id, client_secret, redirect_url)
+            .into_openid(
+                &format!("{base_url}/application/o/{slug}/"),
+                &format!("{base_url}/application/o/{slug}/jwks/"),
+            )
+            .set_user_url(&format!("{base_url}/application/o/userinfo/"))
+    }
+
+    /// Logout endpoint of the application, use it with `openid::end_session_url`
+    pub fn end_session_endpoint(base_url: &str, slug: &str) -> String {
+        format!(
+            "{}/application/o/{slug}/end-session/",
+            base_url.trim_end_matches('/')
+        )
+    }
+}
+
+impl ProviderPreset for AuthentikProvider {
+    const NAME: &'static str = "authentik";
+
+    type Config = BaseUrl;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &BaseUrl,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(AuthentikProvider::new(
+            &config.0,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
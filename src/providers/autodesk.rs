@@ -0,0 +1,39 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::CustomProvider;
+
+pub struct AutodeskProvider {}
+
+impl AutodeskProvider {
+    /// Create a new AutodeskProvider, the token endpoint of APS requires the credentials in Basic auth
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://developer.api.autodesk.com/authentication/v2/authorize"),
+            String::from("https://developer.api.autodesk.com/authentication/v2/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+    }
+}
+
+impl ProviderPreset for AutodeskProvider {
+    const NAME: &'static str = "autodesk";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(AutodeskProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -0,0 +1,84 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::template::ProviderTemplate;
+use crate::{AuthType, CustomProvider};
+
+pub struct AzureB2CProvider {}
+
+impl AzureB2CProvider {
+    /// Create a new AzureB2CProvider
+    ///
+    /// # Arguments
+    /// * `tenant_name` - The name of the B2C tenant, without `.onmicrosoft.com`
+    /// * `user_flow` - The user flow or custom policy, like `B2C_1_signupsignin`
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        tenant_name: &str,
+        user_flow: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(template(tenant_name, user_flow)
+            .build(client_id, client_secret, redirect_url)?
+            .set_auth_type(AuthType::RequestBody))
+    }
+
+    /// Logout endpoint of the user flow, use it with `openid::end_session_url`
+    pub fn end_session_endpoint(tenant_name: &str, user_flow: &str) -> Result<String, OauthError> {
+        template(tenant_name, user_flow).fill(&format!("{BASE_URL}/logout"))
+    }
+
+    /// Issuer of the ID tokens of the user flow, the keys are in `{issuer}/discovery/v2.0/keys`
+    pub fn issuer(tenant_name: &str, user_flow: &str) -> Result<String, OauthError> {
+        template(tenant_name, user_flow)
+            .fill("https://{tenant}.b2clogin.com/{tenant}.onmicrosoft.com/{user_flow}/v2.0/")
+    }
+}
+
+const BASE_URL: &str =
+    "https://{tenant}.b2clogin.com/{tenant}.onmicrosoft.com/{user_flow}/oauth2/v2.0";
+
+fn template(tenant_name: &str, user_flow: &str) -> ProviderTemplate {
+    ProviderTemplate::new(
+        &format!("{BASE_URL}/authorize"),
+        &format!("{BASE_URL}/token"),
+    )
+    .tenant(tenant_name)
+    .set("user_flow", user_flow)
+}
+
+/// Config of the `azureb2c` preset
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AzureB2CConfig {
+    /// Name of the B2C tenant, without `.onmicrosoft.com`
+    pub tenant_name: String,
+    /// User flow or custom policy, like `B2C_1_signupsignin`
+    pub user_flow: String,
+}
+
+impl ProviderPreset for AzureB2CProvider {
+    const NAME: &'static str = "azureb2c";
+
+    type Config = AzureB2CConfig;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &AzureB2CConfig,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        AzureB2CProvider::new(
+            &config.tenant_name,
+            &config.user_flow,
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+    }
+}
@@ -0,0 +1,48 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::quirks::Quirks;
+use crate::{AuthType, CustomProvider};
+
+pub struct BasecampProvider {}
+
+impl BasecampProvider {
+    /// Create a new BasecampProvider, the 37signals Launchpad requires `type=web_server` in the authorization and token requests,
+    /// and `type=refresh` when refreshing the token
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://launchpad.37signals.com/authorization/new"),
+            String::from("https://launchpad.37signals.com/authorization/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_quirks(
+            Quirks::new()
+                .auth_param("type", "web_server")
+                .token_param("type", "web_server")
+                .refresh_param("type", "refresh"),
+        )
+    }
+}
+
+impl ProviderPreset for BasecampProvider {
+    const NAME: &'static str = "basecamp";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(BasecampProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
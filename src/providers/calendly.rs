@@ -0,0 +1,61 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::token::TokenSet;
+use crate::user::UserInfo;
+use crate::CustomProvider;
+
+pub struct CalendlyProvider {}
+
+impl CalendlyProvider {
+    /// Create a new CalendlyProvider
+    ///
+    /// The access token expires in 2 hours and is renewed with `refresh_token`. Calendly returns a new refresh token
+    /// in every refresh and the old one stops working, so save the `refresh_token` of the new `TokenSet`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://auth.calendly.com/oauth/authorize"),
+            String::from("https://auth.calendly.com/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_user_info(UserInfo::with_pointers(
+            "https://api.calendly.com/users/me",
+            "/resource/uri",
+            Some("/resource/email"),
+            Some("/resource/name"),
+            Some("/resource/avatar_url"),
+        ))
+    }
+
+    /// Get the URI of the user returned with the token, used in the `user` parameter of the API
+    pub fn owner(token: &TokenSet) -> Option<&str> {
+        token.extra_str("owner")
+    }
+
+    /// Get the URI of the organization of the user returned with the token
+    pub fn organization(token: &TokenSet) -> Option<&str> {
+        token.extra_str("organization")
+    }
+}
+
+impl ProviderPreset for CalendlyProvider {
+    const NAME: &'static str = "calendly";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(CalendlyProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
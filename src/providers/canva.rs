@@ -0,0 +1,36 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::CustomProvider;
+
+pub struct CanvaProvider {}
+
+impl CanvaProvider {
+    /// Create a new CanvaProvider, Canva requires PKCE, always sent in the URL, and the client id and secret
+    /// with Basic authentication
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://www.canva.com/api/oauth/authorize"),
+            String::from("https://api.canva.com/rest/v1/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+    }
+}
+
+impl ProviderPreset for CanvaProvider {
+    const NAME: &'static str = "canva";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(CanvaProvider::new(client_id, client_secret, redirect_url))
+    }
+}
@@ -0,0 +1,56 @@
+use crate::error::OauthError;
+use crate::providers::{BaseUrl, ProviderPreset};
+use crate::user::UserInfo;
+use crate::{AuthType, CustomProvider};
+
+pub struct CasdoorProvider {}
+
+impl CasdoorProvider {
+    /// Create a new CasdoorProvider
+    ///
+    /// # Arguments
+    /// * `endpoint` - The URL of the Casdoor server, like `https://door.example.com`
+    /// * `client_id` - The client id of the application
+    /// * `client_secret` - The client secret of the application
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        endpoint: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let endpoint = endpoint.trim_end_matches('/');
+        CustomProvider::new(
+            format!("{endpoint}/login/oauth/authorize"),
+            format!("{endpoint}/api/login/oauth/access_token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_user_info(UserInfo::new(&format!("{endpoint}/api/userinfo")))
+    }
+}
+
+impl ProviderPreset for CasdoorProvider {
+    const NAME: &'static str = "casdoor";
+
+    type Config = BaseUrl;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &BaseUrl,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(CasdoorProvider::new(
+            &config.0,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -0,0 +1,55 @@
+use crate::error::OauthError;
+use serde_json::Value;
+
+use crate::providers::ProviderPreset;
+use crate::quirks::Quirks;
+use crate::user::UserInfo;
+use crate::{AuthType, CustomProvider};
+
+pub struct ClickUpProvider {}
+
+impl ClickUpProvider {
+    /// Create a new ClickUpProvider
+    ///
+    /// The authorization page is `/api` of the app, without scopes, and the token endpoint returns only the
+    /// `access_token`, without `token_type` and expiration, so the `token_type` is added by the quirks
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://app.clickup.com/api"),
+            String::from("https://api.clickup.com/api/v2/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_quirks(Quirks::new().map_response(|fields| {
+            fields
+                .entry("token_type")
+                .or_insert_with(|| Value::String("bearer".to_string()));
+        }))
+        .set_user_info(UserInfo::with_pointers(
+            "https://api.clickup.com/api/v2/user",
+            "/user/id",
+            Some("/user/email"),
+            Some("/user/username"),
+            Some("/user/profilePicture"),
+        ))
+    }
+}
+
+impl ProviderPreset for ClickUpProvider {
+    const NAME: &'static str = "clickup";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(ClickUpProvider::new(client_id, client_secret, redirect_url))
+    }
+}
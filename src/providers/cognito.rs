@@ -0,0 +1,109 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::template::ProviderTemplate;
+use crate::user::UserInfo;
+use crate::CustomProvider;
+
+pub struct CognitoProvider {}
+
+impl CognitoProvider {
+    /// Create a new CognitoProvider
+    ///
+    /// # Arguments
+    /// * `domain` - The domain prefix of the user pool, like `my-app`, or a custom domain, like `auth.example.com`
+    /// * `region` - The AWS region of the user pool, like `us-east-1`, not used with a custom domain
+    /// * `client_id` - The client id of the app client
+    /// * `client_secret` - The client secret of the app client
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        domain: &str,
+        region: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        let template = template(domain, region);
+        let user_url = template.fill(&format!("{}/oauth2/userInfo", base_url(domain)))?;
+        Ok(template
+            .build(client_id, client_secret, redirect_url)?
+            .set_user_info(UserInfo::new(&user_url)))
+    }
+
+    /// Generate the URL of the Cognito logout, that ends the session of the hosted UI
+    ///
+    /// # Arguments
+    /// * `domain` - The same domain of the `new`
+    /// * `region` - The same region of the `new`
+    /// * `client_id` - The client id of the app client
+    /// * `logout_uri` - Where Cognito sends the user after the logout, it needs to be in the sign out URLs of the app client
+    ///
+    pub fn logout_url(
+        domain: &str,
+        region: &str,
+        client_id: &str,
+        logout_uri: &str,
+    ) -> Result<String, OauthError> {
+        let logout_url = template(domain, region).fill(&format!("{}/logout", base_url(domain)))?;
+        let mut url = oauth2::url::Url::parse(&logout_url)
+            .map_err(|_| OauthError::EndSessionUrlCreationFailed)?;
+        url.query_pairs_mut()
+            .append_pair("client_id", client_id)
+            .append_pair("logout_uri", logout_uri);
+        Ok(url.to_string())
+    }
+}
+
+/// URL of the user pool with the `{domain}` and `{region}` placeholders
+fn base_url(domain: &str) -> &'static str {
+    match domain.contains('.') {
+        true => "https://{domain}",
+        false => "https://{domain}.auth.{region}.amazoncognito.com",
+    }
+}
+
+/// The custom domains have no region, so the region is only checked in the domains of Cognito
+fn template(domain: &str, region: &str) -> ProviderTemplate {
+    let base_url = base_url(domain);
+    let template = ProviderTemplate::new(
+        &format!("{base_url}/oauth2/authorize"),
+        &format!("{base_url}/oauth2/token"),
+    )
+    .set("domain", domain);
+    match domain.contains('.') {
+        true => template,
+        false => template.region(region),
+    }
+}
+
+/// Config of the `cognito` preset
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CognitoConfig {
+    /// Domain prefix of the user pool, like `my-app`, or a custom domain, like `auth.example.com`
+    pub domain: String,
+    /// AWS region of the user pool, like `us-east-1`, None with a custom domain
+    pub region: Option<String>,
+}
+
+impl ProviderPreset for CognitoProvider {
+    const NAME: &'static str = "cognito";
+
+    type Config = CognitoConfig;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &CognitoConfig,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        CognitoProvider::new(
+            &config.domain,
+            config.region.as_deref().unwrap_or_default(),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+    }
+}
@@ -0,0 +1,56 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::quirks::{Quirks, TokenResponseFormat};
+use crate::scopes::ScopeDelimiter;
+use crate::{AuthType, CustomProvider};
+
+pub struct DeezerProvider {}
+
+impl DeezerProvider {
+    /// Create a new DeezerProvider
+    ///
+    /// Deezer uses `app_id`, `secret` and `perms` instead of the standard names, and returns the token as form,
+    /// the expiration in the `expires` field is read as `expires_in`
+    ///
+    /// # Arguments
+    /// * `app_id` - The application id
+    /// * `secret` - The secret key
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(app_id: String, secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://connect.deezer.com/oauth/auth.php"),
+            String::from("https://connect.deezer.com/oauth/access_token.php"),
+            app_id,
+            secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_scope_delimiter(ScopeDelimiter::Comma)
+        .set_quirks(
+            Quirks::new()
+                .rename_param("client_id", "app_id")
+                .rename_param("client_secret", "secret")
+                .rename_param("scope", "perms")
+                .rename_field("expires", "expires_in")
+                .token_response_format(TokenResponseFormat::FormUrlEncoded),
+        )
+    }
+}
+
+impl ProviderPreset for DeezerProvider {
+    const NAME: &'static str = "deezer";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(DeezerProvider::new(client_id, client_secret, redirect_url))
+    }
+}
@@ -1,8 +1,31 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::user::{OAuthUser, UserInfo, UserModel};
 use crate::CustomProvider;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Response of the Discord `/users/@me` endpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DiscordUser {
+    pub id: String,
+    pub username: String,
+    /// `0` for the users that migrated to the new usernames
+    pub discriminator: String,
+    pub global_name: Option<String>,
+    pub avatar: Option<String>,
+    /// Only with the `email` scope
+    pub email: Option<String>,
+    pub verified: Option<bool>,
+    pub locale: Option<String>,
+}
+
+impl UserModel for DiscordUser {}
 
 pub struct DiscordProvider {}
 
 impl DiscordProvider {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
         CustomProvider::new(
             String::from("https://discord.com/oauth2/authorize"),
@@ -11,5 +34,40 @@ impl DiscordProvider {
             client_secret,
             redirect_url,
         )
+        .set_user_info(UserInfo::custom(
+            "https://discord.com/api/users/@me",
+            |raw| {
+                let id = raw.get("id")?.as_str()?.to_string();
+                Some(OAuthUser {
+                    email: raw.get("email").and_then(Value::as_str).map(String::from),
+                    name: raw
+                        .get("global_name")
+                        .and_then(Value::as_str)
+                        .or_else(|| raw.get("username").and_then(Value::as_str))
+                        .map(String::from),
+                    avatar_url: raw.get("avatar").and_then(Value::as_str).map(|avatar| {
+                        format!("https://cdn.discordapp.com/avatars/{id}/{avatar}.png")
+                    }),
+                    id,
+                    raw: raw.clone(),
+                })
+            },
+        ))
+    }
+}
+
+impl ProviderPreset for DiscordProvider {
+    const NAME: &'static str = "discord";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(DiscordProvider::new(client_id, client_secret, redirect_url))
     }
 }
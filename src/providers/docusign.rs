@@ -0,0 +1,57 @@
+use crate::error::OauthError;
+use crate::CustomProvider;
+
+use super::{Environment, ProviderPreset};
+
+pub struct DocuSignProvider {}
+
+impl DocuSignProvider {
+    /// Create a new DocuSignProvider
+    ///
+    /// # Arguments
+    /// * `environment` - Sandbox uses the developer account server (account-d.docusign.com)
+    /// * `client_id` - The integration key
+    /// * `client_secret` - The secret key
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        environment: Environment,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let base_url = match environment {
+            Environment::Sandbox => "https://account-d.docusign.com/oauth",
+            Environment::Production => "https://account.docusign.com/oauth",
+        };
+        CustomProvider::new(
+            base_url.to_string() + "/auth",
+            base_url.to_string() + "/token",
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+    }
+}
+
+impl ProviderPreset for DocuSignProvider {
+    const NAME: &'static str = "docusign";
+
+    type Config = Environment;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &Environment,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(DocuSignProvider::new(
+            *config,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -0,0 +1,39 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct DribbbleProvider {}
+
+impl DribbbleProvider {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://dribbble.com/oauth/authorize"),
+            String::from("https://dribbble.com/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for DribbbleProvider {
+    const NAME: &'static str = "dribbble";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(DribbbleProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
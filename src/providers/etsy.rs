@@ -0,0 +1,50 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct EtsyProvider {}
+
+impl EtsyProvider {
+    /// Create a new EtsyProvider, Etsy v3 requires PKCE and the client_id in the body of the token request
+    ///
+    /// # Arguments
+    /// * `client_id` - The keystring of the app
+    /// * `client_secret` - The shared secret of the app
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://www.etsy.com/oauth/connect"),
+            String::from("https://api.etsy.com/v3/public/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+
+    /// Get the user id from the access token, Etsy returns it as prefix of the token: `{user_id}.{token}`
+    pub fn user_id(access_token: &str) -> Option<&str> {
+        access_token
+            .split_once('.')
+            .map(|(user_id, _)| user_id)
+            .filter(|user_id| !user_id.is_empty() && user_id.chars().all(|c| c.is_ascii_digit()))
+    }
+}
+
+impl ProviderPreset for EtsyProvider {
+    const NAME: &'static str = "etsy";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(EtsyProvider::new(client_id, client_secret, redirect_url))
+    }
+}
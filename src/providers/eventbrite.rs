@@ -0,0 +1,39 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct EventbriteProvider {}
+
+impl EventbriteProvider {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://www.eventbrite.com/oauth/authorize"),
+            String::from("https://www.eventbrite.com/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for EventbriteProvider {
+    const NAME: &'static str = "eventbrite";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(EventbriteProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -1,8 +1,14 @@
-use crate::CustomProvider;
+use crate::error::OauthError;
+use crate::http;
+use crate::providers::ProviderPreset;
+use crate::token::{ProviderTokenResponse, TokenSet};
+use crate::user::UserInfo;
+use crate::{AuthType, CustomProvider};
 
 pub struct FacebookProvider {}
 
 impl FacebookProvider {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
         CustomProvider::new(
             String::from("https://www.facebook.com/v19.0/dialog/oauth"),
@@ -11,5 +17,61 @@ impl FacebookProvider {
             client_secret,
             redirect_url,
         )
+        .set_auth_type(AuthType::RequestBody)
+        .set_user_info(UserInfo::with_pointers(
+            "https://graph.facebook.com/v19.0/me?fields=id,name,email,picture",
+            "/id",
+            Some("/email"),
+            Some("/name"),
+            Some("/picture/data/url"),
+        ))
+    }
+
+    /// Exchange the short-lived token (about 1 hour) for a long-lived token (about 60 days).
+    /// The `expires_in` of the returned token has the new expiration
+    /// # Arguments
+    /// * `provider` - &CustomProvider - The provider created with `FacebookProvider::new`
+    /// * `short_token` - &str - The access token returned in the login
+    pub async fn exchange_long_lived_token(
+        provider: &CustomProvider,
+        short_token: &str,
+    ) -> Result<TokenSet, OauthError> {
+        let request = http::client()?.get(&provider.token_url).query(&[
+            ("grant_type", "fb_exchange_token"),
+            ("client_id", &provider.client_id),
+            ("client_secret", &provider.client_secret),
+            ("fb_exchange_token", short_token),
+        ]);
+        let response = http::execute("oauth.token", request)
+            .await
+            .map_err(|_| OauthError::TokenRequestFailed)?;
+        if !response.status().is_success() {
+            return Err(OauthError::TokenRequestFailed);
+        }
+        response
+            .json::<ProviderTokenResponse>()
+            .await
+            .map(TokenSet::from)
+            .map_err(|_| OauthError::TokenRequestFailed)
+    }
+}
+
+impl ProviderPreset for FacebookProvider {
+    const NAME: &'static str = "facebook";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(FacebookProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
     }
 }
@@ -0,0 +1,76 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::user::UserInfo;
+use crate::CustomProvider;
+
+pub struct FusionAuthProvider {}
+
+impl FusionAuthProvider {
+    /// Create a new FusionAuthProvider
+    ///
+    /// # Arguments
+    /// * `base_url` - The URL of the FusionAuth server, like `https://auth.example.com`
+    /// * `client_id` - The client id of the application
+    /// * `client_secret` - The client secret of the application
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        base_url: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let base_url = base_url.trim_end_matches('/');
+        CustomProvider::new(
+            format!("{base_url}/oauth2/authorize"),
+            format!("{base_url}/oauth2/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_user_info(UserInfo::new(&format!("{base_url}/oauth2/userinfo")))
+    }
+}
+
+/// Options of the FusionAuth login page
+pub trait FusionAuthOptions {
+    /// Send the `tenantId`, required when the server has more than one tenant and the
+    /// application id is not enough to find it
+    fn set_tenant_id(self, tenant_id: &str) -> Self;
+}
+
+impl FusionAuthOptions for CustomProvider {
+    fn set_tenant_id(self, tenant_id: &str) -> Self {
+        self.set_auth_param("tenantId", tenant_id)
+    }
+}
+
+/// Config of the `fusionauth` preset
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FusionAuthConfig {
+    pub base_url: String,
+    /// Sent as `tenantId`, see `FusionAuthOptions::set_tenant_id`
+    pub tenant_id: Option<String>,
+}
+
+impl ProviderPreset for FusionAuthProvider {
+    const NAME: &'static str = "fusionauth";
+
+    type Config = FusionAuthConfig;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &FusionAuthConfig,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        let provider =
+            FusionAuthProvider::new(&config.base_url, client_id, client_secret, redirect_url);
+        Ok(match &config.tenant_id {
+            Some(tenant_id) => provider.set_tenant_id(tenant_id),
+            None => provider,
+        })
+    }
+}
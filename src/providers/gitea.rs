@@ -0,0 +1,61 @@
+use crate::error::OauthError;
+use crate::providers::{BaseUrl, ProviderPreset};
+use crate::user::UserInfo;
+use crate::CustomProvider;
+
+pub struct GiteaProvider {}
+
+impl GiteaProvider {
+    /// Create a new GiteaProvider, also used for Forgejo. The PKCE challenge is sent in every login
+    ///
+    /// # Arguments
+    /// * `base_url` - The URL of the instance, like `https://gitea.example.com`
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        base_url: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let base_url = base_url.trim_end_matches('/');
+        CustomProvider::new(
+            format!("{base_url}/login/oauth/authorize"),
+            format!("{base_url}/login/oauth/access_token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_user_info(UserInfo::new(&format!("{base_url}/login/oauth/userinfo")))
+    }
+
+    /// Create the provider for an application registered without the confidential client option,
+    /// only the client id and the PKCE verifier are sent
+    pub fn new_public(base_url: &str, client_id: String, redirect_url: String) -> CustomProvider {
+        GiteaProvider::new(base_url, client_id, String::new(), redirect_url).set_public_client()
+    }
+}
+
+impl ProviderPreset for GiteaProvider {
+    const NAME: &'static str = "gitea";
+
+    type Config = BaseUrl;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &BaseUrl,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(GiteaProvider::new(
+            &config.0,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -1,8 +1,30 @@
-use crate::CustomProvider;
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::user::{UserInfo, UserModel};
+use crate::{AuthType, CustomProvider};
+use serde::{Deserialize, Serialize};
+
+/// Response of the GitHub `/user` endpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GithubUser {
+    pub id: u64,
+    pub login: String,
+    pub name: Option<String>,
+    /// Only the public email, it is None when the user keeps it private
+    pub email: Option<String>,
+    pub avatar_url: String,
+    pub html_url: String,
+    pub company: Option<String>,
+    pub location: Option<String>,
+    pub bio: Option<String>,
+}
+
+impl UserModel for GithubUser {}
 
 pub struct GithubProvider {}
 
 impl GithubProvider {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
         CustomProvider::new(
             String::from("https://github.com/login/oauth/authorize"),
@@ -11,5 +33,41 @@ impl GithubProvider {
             client_secret,
             redirect_url,
         )
+        .set_auth_type(AuthType::RequestBody)
+        .set_user_info(UserInfo::with_pointers(
+            "https://api.github.com/user",
+            "/id",
+            Some("/email"),
+            Some("/name"),
+            Some("/avatar_url"),
+        ))
+    }
+}
+
+/// Options of the GitHub authorization page
+pub trait GithubOptions {
+    /// Show the option to create an account in the login page, the default of GitHub is `true`
+    fn set_allow_signup(self, allow_signup: bool) -> Self;
+}
+
+impl GithubOptions for CustomProvider {
+    fn set_allow_signup(self, allow_signup: bool) -> Self {
+        self.set_auth_param("allow_signup", &allow_signup.to_string())
+    }
+}
+
+impl ProviderPreset for GithubProvider {
+    const NAME: &'static str = "github";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(GithubProvider::new(client_id, client_secret, redirect_url))
     }
 }
@@ -1,8 +1,34 @@
+use crate::error::OauthError;
+use crate::jwt::JwksCache;
+use crate::openid::{CustomOpenIdProvider, IdTokenClaims};
+use crate::providers::ProviderPreset;
+use crate::user::{OAuthUser, UserInfo, UserModel};
+use crate::validation::ValidationOptions;
 use crate::CustomProvider;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Response of the Google userinfo endpoint, the fields depend on the scopes
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GoogleUserInfo {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub picture: Option<String>,
+    pub locale: Option<String>,
+    /// Domain of the Google Workspace account
+    pub hd: Option<String>,
+}
+
+impl UserModel for GoogleUserInfo {}
 
 pub struct GoogleProvider {}
 
 impl GoogleProvider {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
         CustomProvider::new(
             String::from("https://accounts.google.com/o/oauth2/v2/auth"),
@@ -11,5 +37,106 @@ impl GoogleProvider {
             client_secret,
             redirect_url,
         )
+        .set_user_info(UserInfo::new(
+            "https://openidconnect.googleapis.com/v1/userinfo",
+        ))
+    }
+
+    /// Create the provider with the OpenID behavior, the ID token is verified with the Google keys
+    pub fn openid(
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomOpenIdProvider {
+        GoogleProvider::new(client_id, client_secret, redirect_url)
+            .into_openid(
+                "https://accounts.google.com",
+                "https://www.googleapis.com/oauth2/v3/certs",
+            )
+            .set_user_url("https://openidconnect.googleapis.com/v1/userinfo")
+    }
+}
+
+/// Issuers of the Google ID tokens, the One Tap tokens can have the issuer without the scheme
+const ISSUERS: [&str; 2] = ["https://accounts.google.com", "accounts.google.com"];
+
+/// Form posted by Google to the `login_uri` of One Tap and Sign in with Google
+#[derive(Clone, Debug, Deserialize)]
+pub struct OneTapCallback {
+    /// ID token of the user
+    pub credential: String,
+    /// Same value of the `g_csrf_token` cookie
+    pub g_csrf_token: Option<String>,
+}
+
+/// Verify the ID tokens of Google One Tap, with the signature checked with the Google keys
+#[derive(Clone)]
+pub struct GoogleOneTap {
+    jwks: JwksCache,
+    client_id: String,
+    validation: ValidationOptions,
+}
+
+impl GoogleOneTap {
+    /// Create the verifier, the `client_id` is the same used in the One Tap button
+    pub fn new(client_id: String) -> Self {
+        GoogleOneTap {
+            jwks: JwksCache::new("https://www.googleapis.com/oauth2/v3/certs"),
+            client_id,
+            validation: ValidationOptions::default(),
+        }
+    }
+
+    /// Set the clock skew accepted in the `exp` and `iat` of the credential
+    pub fn set_validation_options(mut self, validation: ValidationOptions) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Verify the signature, `iss`, `aud` and `exp` of the credential and return the user, with the claims in `raw`
+    pub async fn verify(&self, credential: &str) -> Result<OAuthUser, OauthError> {
+        let raw: Value = self.jwks.verify(credential).await?;
+        let claims: IdTokenClaims =
+            serde_json::from_value(raw.clone()).map_err(|_| OauthError::InvalidIdToken)?;
+        if !ISSUERS.contains(&claims.iss.as_str())
+            || !claims.aud.contains(&self.client_id)
+            || self.validation.is_expired(claims.exp)
+            || self.validation.is_in_future(claims.iat)
+        {
+            return Err(OauthError::InvalidIdToken);
+        }
+        UserInfo::new("https://openidconnect.googleapis.com/v1/userinfo").map(raw)
+    }
+
+    /// Check the CSRF token of the form with the `g_csrf_token` cookie and verify the credential
+    /// # Arguments
+    /// * `callback` - OneTapCallback - The form posted by Google
+    /// * `csrf_cookie` - Option<&str> - The value of the `g_csrf_token` cookie
+    pub async fn verify_callback(
+        &self,
+        callback: &OneTapCallback,
+        csrf_cookie: Option<&str>,
+    ) -> Result<OAuthUser, OauthError> {
+        match (callback.g_csrf_token.as_deref(), csrf_cookie) {
+            (Some(form), Some(cookie)) if form == cookie => self.verify(&callback.credential).await,
+            _ => Err(OauthError::StateMismatch),
+        }
+    }
+}
+
+impl ProviderPreset for GoogleProvider {
+    const NAME: &'static str = "google";
+    const ISSUER: Option<&'static str> = Some("https://accounts.google.com");
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(GoogleProvider::new(client_id, client_secret, redirect_url))
     }
 }
@@ -0,0 +1,35 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct ImgurProvider {}
+
+impl ImgurProvider {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://api.imgur.com/oauth2/authorize"),
+            String::from("https://api.imgur.com/oauth2/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for ImgurProvider {
+    const NAME: &'static str = "imgur";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(ImgurProvider::new(client_id, client_secret, redirect_url))
+    }
+}
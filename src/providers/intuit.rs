@@ -0,0 +1,61 @@
+use crate::error::OauthError;
+use crate::CustomProvider;
+
+use super::{Environment, ProviderPreset};
+
+pub struct IntuitProvider {}
+
+impl IntuitProvider {
+    /// Create a new IntuitProvider
+    ///
+    /// # Arguments
+    /// * `environment` - Sandbox or Production, Intuit uses the same OAuth endpoints in both, but the keys are different
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        _environment: Environment,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://appcenter.intuit.com/connect/oauth2"),
+            String::from("https://oauth.platform.intuit.com/oauth2/v1/tokens/bearer"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+    }
+
+    /// Base URL of the QuickBooks API for the environment, the token generated only works in the same environment
+    pub fn api_base_url(environment: Environment) -> &'static str {
+        match environment {
+            Environment::Sandbox => "https://sandbox-quickbooks.api.intuit.com",
+            Environment::Production => "https://quickbooks.api.intuit.com",
+        }
+    }
+}
+
+impl ProviderPreset for IntuitProvider {
+    const NAME: &'static str = "intuit";
+
+    type Config = Environment;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &Environment,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(IntuitProvider::new(
+            *config,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -0,0 +1,58 @@
+use crate::error::OauthError;
+use crate::openid::CustomOpenIdProvider;
+use crate::providers::ProviderPreset;
+use crate::user::UserInfo;
+use crate::CustomProvider;
+
+pub struct JumpCloudProvider {}
+
+impl JumpCloudProvider {
+    /// Create a new JumpCloudProvider, without scopes it requests `openid profile email`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://oauth.id.jumpcloud.com/oauth2/auth"),
+            String::from("https://oauth.id.jumpcloud.com/oauth2/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_default_scopes(["openid", "profile", "email"])
+        .set_user_info(UserInfo::new("https://oauth.id.jumpcloud.com/userinfo"))
+    }
+
+    /// Create the provider with the OpenID behavior, the ID token is verified with the JumpCloud keys
+    pub fn openid(
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomOpenIdProvider {
+        JumpCloudProvider::new(client_id, client_secret, redirect_url)
+            .into_openid(
+                "https://oauth.id.jumpcloud.com/",
+                "https://oauth.id.jumpcloud.com/.well-known/jwks.json",
+            )
+            .set_user_url("https://oauth.id.jumpcloud.com/userinfo")
+    }
+}
+
+impl ProviderPreset for JumpCloudProvider {
+    const NAME: &'static str = "jumpcloud";
+    const ISSUER: Option<&'static str> = Some("https://oauth.id.jumpcloud.com");
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(JumpCloudProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -0,0 +1,51 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::scopes::ScopeDelimiter;
+use crate::{AuthType, CustomProvider};
+
+pub struct LinearProvider {}
+
+impl LinearProvider {
+    /// Create a new LinearProvider, Linear separates the scopes with comma
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://linear.app/oauth/authorize"),
+            String::from("https://api.linear.app/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_scope_delimiter(ScopeDelimiter::Comma)
+        .set_default_scopes(["read"])
+    }
+}
+
+/// Options of the Linear authorization
+pub trait LinearOptions {
+    /// Send `actor=application`, the actions are done as the app instead of the user that authorized it
+    fn set_actor_application(self) -> Self;
+}
+
+impl LinearOptions for CustomProvider {
+    fn set_actor_application(self) -> Self {
+        self.set_auth_param("actor", "application")
+    }
+}
+
+impl ProviderPreset for LinearProvider {
+    const NAME: &'static str = "linear";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(LinearProvider::new(client_id, client_secret, redirect_url))
+    }
+}
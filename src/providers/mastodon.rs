@@ -0,0 +1,139 @@
+use crate::providers::{BaseUrl, ProviderPreset};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::OauthError;
+use crate::redact::Redacted;
+use crate::scopes::{ScopeDelimiter, Scopes};
+use crate::user::UserInfo;
+use crate::{http, CustomProvider};
+
+/// Application registered in the instance with `MastodonProvider::register_app`, save it to use in the next logins
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MastodonApp {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl fmt::Debug for MastodonApp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MastodonApp")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &Redacted(&self.client_secret))
+            .finish()
+    }
+}
+
+pub struct MastodonProvider {}
+
+impl MastodonProvider {
+    /// Create a new MastodonProvider
+    ///
+    /// # Arguments
+    /// * `instance_url` - The URL of the instance, like `https://mastodon.social`
+    /// * `client_id` - The client id of the app registered in the instance
+    /// * `client_secret` - The client secret of the app registered in the instance
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        instance_url: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let instance_url = instance_url.trim_end_matches('/');
+        CustomProvider::new(
+            format!("{instance_url}/oauth/authorize"),
+            format!("{instance_url}/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_default_scopes(["read:accounts"])
+        .set_user_info(UserInfo::with_pointers(
+            &format!("{instance_url}/api/v1/accounts/verify_credentials"),
+            "/id",
+            None,
+            Some("/display_name"),
+            Some("/avatar"),
+        ))
+    }
+
+    /// Register the app in the instance with `/api/v1/apps`. Every instance has its own apps,
+    /// so register it in the first login of the instance and save the `MastodonApp`
+    ///
+    /// # Arguments
+    /// * `instance_url` - The URL of the instance
+    /// * `client_name` - The name of the app shown to the users
+    /// * `redirect_url` - The redirect url
+    /// * `scopes` - The scopes that the app can request
+    /// * `website` - The URL of the app
+    ///
+    pub async fn register_app(
+        instance_url: &str,
+        client_name: &str,
+        redirect_url: &str,
+        scopes: impl Into<Scopes>,
+        website: Option<&str>,
+    ) -> Result<MastodonApp, OauthError> {
+        let mut form = vec![
+            ("client_name", client_name.to_string()),
+            ("redirect_uris", redirect_url.to_string()),
+            ("scopes", scopes.into().join(ScopeDelimiter::Space)),
+        ];
+        if let Some(website) = website {
+            form.push(("website", website.to_string()));
+        }
+        let request = http::client()?
+            .post(format!(
+                "{}/api/v1/apps",
+                instance_url.trim_end_matches('/')
+            ))
+            .form(&form);
+        let response = http::execute("oauth.registration", request)
+            .await
+            .map_err(|_| OauthError::RegistrationFailed)?;
+        if !response.status().is_success() {
+            return Err(OauthError::RegistrationFailed);
+        }
+        response
+            .json::<MastodonApp>()
+            .await
+            .map_err(|_| OauthError::RegistrationFailed)
+    }
+}
+
+impl MastodonApp {
+    /// Create the provider of the instance with the credentials of this app
+    pub fn to_provider(&self, instance_url: &str, redirect_url: String) -> CustomProvider {
+        MastodonProvider::new(
+            instance_url,
+            self.client_id.clone(),
+            self.client_secret.clone(),
+            redirect_url,
+        )
+    }
+}
+
+impl ProviderPreset for MastodonProvider {
+    const NAME: &'static str = "mastodon";
+
+    type Config = BaseUrl;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &BaseUrl,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(MastodonProvider::new(
+            &config.0,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
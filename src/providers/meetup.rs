@@ -0,0 +1,35 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct MeetupProvider {}
+
+impl MeetupProvider {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://secure.meetup.com/oauth2/authorize"),
+            String::from("https://secure.meetup.com/oauth2/access"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for MeetupProvider {
+    const NAME: &'static str = "meetup";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(MeetupProvider::new(client_id, client_secret, redirect_url))
+    }
+}
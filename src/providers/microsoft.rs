@@ -1,4 +1,45 @@
-use crate::CustomProvider;
+use crate::error::OauthError;
+use crate::providers::azureb2c::AzureB2CProvider;
+use crate::providers::ProviderPreset;
+use crate::template::ProviderTemplate;
+use crate::user::UserInfo;
+use crate::{AuthType, CustomProvider};
+
+/// Users that can login in the application
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Tenant {
+    /// Work, school and personal accounts
+    #[default]
+    Common,
+    /// Only work and school accounts
+    Organizations,
+    /// Only personal accounts
+    Consumers,
+    /// Only the accounts of the tenant, by id or domain
+    Id(String),
+}
+
+impl Tenant {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Tenant::Common => "common",
+            Tenant::Organizations => "organizations",
+            Tenant::Consumers => "consumers",
+            Tenant::Id(id) => id,
+        }
+    }
+}
+
+impl From<String> for Tenant {
+    fn from(tenant: String) -> Self {
+        match tenant.as_str() {
+            "common" => Tenant::Common,
+            "organizations" => Tenant::Organizations,
+            "consumers" => Tenant::Consumers,
+            _ => Tenant::Id(tenant),
+        }
+    }
+}
 
 pub struct MicrosoftProvider {}
 
@@ -6,26 +47,94 @@ impl MicrosoftProvider {
     /// Create a new MicrosoftProvider
     ///
     /// # Arguments
-    /// * `tenant_id` - The tenant id - Check Microsfot docmentation for more information: https://learn.microsoft.com/en-us/entra/identity-platform/v2-oauth2-auth-code-flow#request-an-authorization-code
+    /// * `tenant` - The tenant - Check Microsfot docmentation for more information: https://learn.microsoft.com/en-us/entra/identity-platform/v2-oauth2-auth-code-flow#request-an-authorization-code
     /// * `client_id` - The client id
     /// * `client_secret` - The client secret
     /// * `redirect_url` - The redirect url
     ///
+    /// # Return
+    /// `InvalidConfig` when the tenant would change the URL, like a value with `/` or `..`
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(
-        tenant_id: String,
+        tenant: Tenant,
         client_id: String,
         client_secret: String,
         redirect_url: String,
-    ) -> CustomProvider {
-        let base_url = String::from(
-            "https://login.microsoftonline.com/".to_string() + tenant_id.as_str() + "/oauth2/v2.0",
-        );
-        CustomProvider::new(
-            String::from(base_url.clone() + "/authorize"),
-            String::from(base_url + "/token"),
+    ) -> Result<CustomProvider, OauthError> {
+        // The template rejects the tenants that would change the URL
+        MicrosoftProvider::template().tenant(tenant.as_str()).build(
             client_id,
             client_secret,
             redirect_url,
         )
     }
+
+    /// Template with the `{tenant}` placeholder, for apps that choose the tenant in runtime
+    ///
+    /// ```ignore
+    /// let provider = MicrosoftProvider::template()
+    ///     .tenant(&tenant_id)
+    ///     .build(client_id, client_secret, redirect_url)?;
+    /// ```
+    pub fn template() -> ProviderTemplate {
+        ProviderTemplate::new(
+            "https://login.microsoftonline.com/{tenant}/oauth2/v2.0/authorize",
+            "https://login.microsoftonline.com/{tenant}/oauth2/v2.0/token",
+        )
+        .set_configure(configure)
+    }
+
+    /// Create a new MicrosoftProvider for Azure AD B2C
+    #[deprecated(note = "use `AzureB2CProvider::new`")]
+    pub fn new_b2c(
+        tenant_name: &str,
+        policy: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        AzureB2CProvider::new(tenant_name, policy, client_id, client_secret, redirect_url)
+    }
+}
+
+/// Options of the Microsoft login page
+pub trait MicrosoftOptions {
+    /// Skip the home realm discovery, sending the user directly to the login page of the domain,
+    /// like `contoso.com` or `consumers`
+    fn set_domain_hint(self, domain_hint: &str) -> Self;
+}
+
+impl MicrosoftOptions for CustomProvider {
+    fn set_domain_hint(self, domain_hint: &str) -> Self {
+        self.set_auth_param("domain_hint", domain_hint)
+    }
+}
+
+fn configure(provider: CustomProvider) -> CustomProvider {
+    provider
+        .set_auth_type(AuthType::RequestBody)
+        .set_user_info(UserInfo::with_pointers(
+            "https://graph.microsoft.com/v1.0/me",
+            "/id",
+            Some("/mail"),
+            Some("/displayName"),
+            None,
+        ))
+}
+
+impl ProviderPreset for MicrosoftProvider {
+    const NAME: &'static str = "microsoft";
+    const ISSUER: Option<&'static str> = Some("https://login.microsoftonline.com/common/v2.0");
+
+    type Config = Tenant;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &Tenant,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        MicrosoftProvider::new(config.clone(), client_id, client_secret, redirect_url)
+    }
 }
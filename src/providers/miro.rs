@@ -0,0 +1,42 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::token::TokenSet;
+use crate::{AuthType, CustomProvider};
+
+pub struct MiroProvider {}
+
+impl MiroProvider {
+    /// Create a new MiroProvider, the team where the app was installed is returned with the token, check `team_id`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://miro.com/oauth/authorize"),
+            String::from("https://api.miro.com/v1/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+
+    /// Get the `team_id` returned with the token, the token only has access to the boards of this team
+    pub fn team_id(token: &TokenSet) -> Option<&str> {
+        token.extra_str("team_id")
+    }
+}
+
+impl ProviderPreset for MiroProvider {
+    const NAME: &'static str = "miro";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(MiroProvider::new(client_id, client_secret, redirect_url))
+    }
+}
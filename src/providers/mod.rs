@@ -1,7 +1,150 @@
+pub mod adobe;
+pub mod authentik;
+pub mod autodesk;
+pub mod azureb2c;
+pub mod basecamp;
+pub mod calendly;
+pub mod canva;
+pub mod casdoor;
+pub mod clickup;
+pub mod cognito;
+pub mod deezer;
 pub mod discord;
+pub mod docusign;
+pub mod dribbble;
+pub mod etsy;
+pub mod eventbrite;
 pub mod facebook;
+pub mod fusionauth;
+pub mod gitea;
 pub mod github;
 pub mod google;
+pub mod imgur;
+pub mod intuit;
+pub mod jumpcloud;
+pub mod linear;
+pub mod mastodon;
+pub mod meetup;
 pub mod microsoft;
+pub mod miro;
+pub mod monday;
+pub mod nextcloud;
+pub mod onelogin;
+pub mod oryhydra;
+pub mod osu;
+pub mod paypal;
+pub mod pingone;
+pub mod pocket;
+pub mod salesforce;
 pub mod spotify;
+pub mod stackexchange;
+pub mod surveymonkey;
+pub mod tiktok;
+pub mod todoist;
+pub mod tumblr;
 pub mod twitter;
+pub mod typeform;
+pub mod uber;
+pub mod unsplash;
+pub mod vercel;
+pub mod webex;
+pub mod withings;
+pub mod wordpress;
+pub mod wrike;
+pub mod yelp;
+pub mod zitadel;
+pub mod zoho;
+
+use crate::error::OauthError;
+use crate::quirks::Quirks;
+use crate::scopes::Scopes;
+use crate::CustomProvider;
+
+/// Common interface of the presets, so the code can be generic over the providers
+pub trait ProviderPreset {
+    /// Name of the preset, the same of the module and of the `kind` in the registry config
+    const NAME: &'static str;
+
+    /// Where the provider publishes its discovery document, `{ISSUER}/.well-known/openid-configuration`.
+    /// Used by the `drift` module to check the endpoints of the preset
+    const ISSUER: Option<&'static str> = None;
+
+    /// Values that change the endpoints of the preset, like the tenant, the environment or the URL of the
+    /// self-hosted server. `()` for the presets with a single global endpoint
+    type Config;
+
+    /// Provider created by the preset, `CustomProvider` except for the providers that don't follow OAuth2
+    type Provider: PresetProvider;
+
+    /// Create the provider with the configuration of the preset: endpoints, auth style, quirks and scopes
+    ///
+    /// # Return
+    /// `InvalidConfig` when the config can't be used in the endpoints
+    fn create(
+        config: &Self::Config,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<Self::Provider, OauthError>;
+
+    /// Authorization and token endpoints of the preset
+    fn endpoints(config: &Self::Config) -> Result<(String, String), OauthError> {
+        let provider = Self::create(config, String::new(), String::new(), String::new())?;
+        Ok((
+            provider.auth_url().to_string(),
+            provider.token_url().to_string(),
+        ))
+    }
+
+    /// Scopes requested when the login doesn't pass any
+    fn default_scopes(config: &Self::Config) -> Result<Scopes, OauthError> {
+        Ok(Self::create(config, String::new(), String::new(), String::new())?.default_scopes())
+    }
+
+    /// Differences of the provider from the spec, like renamed parameters or extra token params
+    fn quirks(config: &Self::Config) -> Result<Quirks, OauthError> {
+        Ok(Self::create(config, String::new(), String::new(), String::new())?.quirks())
+    }
+}
+
+/// Provider created by a `ProviderPreset`
+pub trait PresetProvider {
+    fn auth_url(&self) -> &str;
+    fn token_url(&self) -> &str;
+    fn default_scopes(&self) -> Scopes;
+    fn quirks(&self) -> Quirks;
+}
+
+impl PresetProvider for CustomProvider {
+    fn auth_url(&self) -> &str {
+        &self.auth_url
+    }
+
+    fn token_url(&self) -> &str {
+        &self.token_url
+    }
+
+    fn default_scopes(&self) -> Scopes {
+        self.default_scopes.clone()
+    }
+
+    fn quirks(&self) -> Quirks {
+        self.quirks.clone()
+    }
+}
+
+/// URL of the self-hosted presets, like `https://gitea.example.com`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BaseUrl(pub String);
+
+/// Domain of the presets that are per customer, like the instance domain of Zitadel or the subdomain of OneLogin
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Domain(pub String);
+
+/// Environment used by the providers that have separated sandbox endpoints
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Environment {
+    Sandbox,
+    #[default]
+    Production,
+}
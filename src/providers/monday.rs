@@ -0,0 +1,36 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct MondayProvider {}
+
+impl MondayProvider {
+    /// Create a new MondayProvider, the tokens don't expire and there is no refresh token
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://auth.monday.com/oauth2/authorize"),
+            String::from("https://auth.monday.com/oauth2/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for MondayProvider {
+    const NAME: &'static str = "monday";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(MondayProvider::new(client_id, client_secret, redirect_url))
+    }
+}
@@ -0,0 +1,54 @@
+use crate::error::OauthError;
+use crate::providers::{BaseUrl, ProviderPreset};
+use crate::{AuthType, CustomProvider};
+
+pub struct NextcloudProvider {}
+
+impl NextcloudProvider {
+    /// Create a new NextcloudProvider, the token has the `user_id` of the user, read it with `TokenSet::extra_str`
+    ///
+    /// # Arguments
+    /// * `base_url` - The URL of the Nextcloud server, like `https://cloud.example.com`
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        base_url: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let base_url = base_url.trim_end_matches('/');
+        CustomProvider::new(
+            format!("{base_url}/apps/oauth2/authorize"),
+            format!("{base_url}/apps/oauth2/api/v1/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for NextcloudProvider {
+    const NAME: &'static str = "nextcloud";
+
+    type Config = BaseUrl;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &BaseUrl,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(NextcloudProvider::new(
+            &config.0,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -0,0 +1,71 @@
+use crate::error::OauthError;
+use crate::openid::CustomOpenIdProvider;
+use crate::providers::{Domain, ProviderPreset};
+use crate::template::ProviderTemplate;
+use crate::user::UserInfo;
+use crate::CustomProvider;
+
+pub struct OneLoginProvider {}
+
+impl OneLoginProvider {
+    /// Create a new OneLoginProvider
+    ///
+    /// # Arguments
+    /// * `subdomain` - The subdomain of the account, like `my-company` for `my-company.onelogin.com`
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        subdomain: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        let template = template(subdomain);
+        let base_url = template.fill(BASE_URL)?;
+        Ok(template
+            .build(client_id, client_secret, redirect_url)?
+            .set_default_scopes(["openid", "profile", "email"])
+            .set_user_info(UserInfo::new(&format!("{base_url}/me"))))
+    }
+
+    /// Create the provider with the OpenID behavior, the ID token is verified with the keys of the account
+    pub fn openid(
+        subdomain: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomOpenIdProvider, OauthError> {
+        let base_url = template(subdomain).fill(BASE_URL)?;
+        Ok(
+            OneLoginProvider::new(subdomain, client_id, client_secret, redirect_url)?
+                .into_openid(&base_url, &format!("{base_url}/certs"))
+                .set_user_url(&format!("{base_url}/me")),
+        )
+    }
+}
+
+const BASE_URL: &str = "https://{subdomain}.onelogin.com/oidc/2";
+
+fn template(subdomain: &str) -> ProviderTemplate {
+    ProviderTemplate::new(&format!("{BASE_URL}/auth"), &format!("{BASE_URL}/token"))
+        .set("subdomain", subdomain)
+}
+
+impl ProviderPreset for OneLoginProvider {
+    const NAME: &'static str = "onelogin";
+
+    type Config = Domain;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &Domain,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        OneLoginProvider::new(&config.0, client_id, client_secret, redirect_url)
+    }
+}
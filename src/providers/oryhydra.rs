@@ -0,0 +1,108 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::template::ProviderTemplate;
+use crate::user::UserInfo;
+use crate::CustomProvider;
+
+pub struct OryHydraProvider {}
+
+impl OryHydraProvider {
+    /// Create a new OryHydraProvider
+    ///
+    /// # Arguments
+    /// * `public_url` - The URL of the Hydra public API, like `https://auth.example.com`
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        public_url: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let public_url = public_url.trim_end_matches('/');
+        CustomProvider::new(
+            format!("{public_url}/oauth2/auth"),
+            format!("{public_url}/oauth2/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_user_info(UserInfo::new(&format!("{public_url}/userinfo")))
+    }
+
+    /// Create the provider for a project of the Ory Network
+    ///
+    /// # Arguments
+    /// * `project_slug` - The slug of the project, like `affectionate-goldberg-abc123`
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    pub fn for_project(
+        project_slug: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(OryHydraProvider::new(
+            &OryHydraProvider::project_url(project_slug)?,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+
+    /// URL of a project of the Ory Network, it is the issuer of the ID tokens
+    /// # Return
+    /// `InvalidConfig` when the slug has characters that could change the host
+    pub fn project_url(project_slug: &str) -> Result<String, OauthError> {
+        ProviderTemplate::fill_value(PROJECT_URL, "project_slug", project_slug)
+    }
+
+    /// Logout endpoint, use it with `openid::end_session_url`
+    pub fn end_session_endpoint(public_url: &str) -> String {
+        format!(
+            "{}/oauth2/sessions/logout",
+            public_url.trim_end_matches('/')
+        )
+    }
+}
+
+const PROJECT_URL: &str = "https://{project_slug}.projects.oryapis.com";
+
+/// Config of the `oryhydra` preset
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OryHydraConfig {
+    /// URL of the Hydra public API of a self-hosted server
+    PublicUrl(String),
+    /// Slug of the Ory Network project
+    Project(String),
+}
+
+impl ProviderPreset for OryHydraProvider {
+    const NAME: &'static str = "oryhydra";
+
+    type Config = OryHydraConfig;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &OryHydraConfig,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        let public_url = match config {
+            OryHydraConfig::PublicUrl(public_url) => public_url.clone(),
+            OryHydraConfig::Project(project_slug) => OryHydraProvider::project_url(project_slug)?,
+        };
+        Ok(OryHydraProvider::new(
+            &public_url,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -0,0 +1,45 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::user::UserInfo;
+use crate::{AuthType, CustomProvider};
+
+pub struct OsuProvider {}
+
+impl OsuProvider {
+    /// Create a new OsuProvider, without scopes the `identify` scope is requested to read the user
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://osu.ppy.sh/oauth/authorize"),
+            String::from("https://osu.ppy.sh/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_default_scopes(["identify"])
+        .set_user_info(UserInfo::with_pointers(
+            "https://osu.ppy.sh/api/v2/me",
+            "/id",
+            None,
+            Some("/username"),
+            Some("/avatar_url"),
+        ))
+    }
+}
+
+impl ProviderPreset for OsuProvider {
+    const NAME: &'static str = "osu";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(OsuProvider::new(client_id, client_secret, redirect_url))
+    }
+}
@@ -0,0 +1,63 @@
+use crate::error::OauthError;
+use crate::CustomProvider;
+
+use super::{Environment, ProviderPreset};
+
+pub struct PayPalProvider {}
+
+impl PayPalProvider {
+    /// Create a new PayPalProvider
+    ///
+    /// # Arguments
+    /// * `environment` - Sandbox or Production, the credentials are different in each environment
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        environment: Environment,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let (auth_url, token_url) = match environment {
+            Environment::Sandbox => (
+                "https://www.sandbox.paypal.com/signin/authorize",
+                "https://api-m.sandbox.paypal.com/v1/oauth2/token",
+            ),
+            Environment::Production => (
+                "https://www.paypal.com/signin/authorize",
+                "https://api-m.paypal.com/v1/oauth2/token",
+            ),
+        };
+        CustomProvider::new(
+            String::from(auth_url),
+            String::from(token_url),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+    }
+}
+
+impl ProviderPreset for PayPalProvider {
+    const NAME: &'static str = "paypal";
+
+    type Config = Environment;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &Environment,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(PayPalProvider::new(
+            *config,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -0,0 +1,91 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::template::ProviderTemplate;
+use crate::user::UserInfo;
+use crate::CustomProvider;
+
+/// Region of the PingOne environment
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Region {
+    #[default]
+    NorthAmerica,
+    Europe,
+    AsiaPacific,
+    Canada,
+}
+
+impl Region {
+    /// Top-level domain of the auth server
+    pub fn domain(&self) -> &'static str {
+        match self {
+            Region::NorthAmerica => "com",
+            Region::Europe => "eu",
+            Region::AsiaPacific => "asia",
+            Region::Canada => "ca",
+        }
+    }
+}
+
+pub struct PingOneProvider {}
+
+impl PingOneProvider {
+    /// Create a new PingOneProvider
+    ///
+    /// # Arguments
+    /// * `environment_id` - The id of the PingOne environment
+    /// * `region` - The region of the environment
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        environment_id: &str,
+        region: Region,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        let template = ProviderTemplate::new(
+            &format!("{BASE_URL}/authorize"),
+            &format!("{BASE_URL}/token"),
+        )
+        .region(region.domain())
+        .set("environment_id", environment_id);
+        let base_url = template.fill(BASE_URL)?;
+        Ok(template
+            .build(client_id, client_secret, redirect_url)?
+            .set_user_info(UserInfo::new(&format!("{base_url}/userinfo"))))
+    }
+}
+
+const BASE_URL: &str = "https://auth.pingone.{region}/{environment_id}/as";
+
+/// Config of the `pingone` preset
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PingOneConfig {
+    pub environment_id: String,
+    pub region: Region,
+}
+
+impl ProviderPreset for PingOneProvider {
+    const NAME: &'static str = "pingone";
+
+    type Config = PingOneConfig;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &PingOneConfig,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        PingOneProvider::new(
+            &config.environment_id,
+            config.region,
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::future::Future;
+
+use async_trait::async_trait;
+use oauth2::url::Url;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::OauthError;
+use crate::generators::StateGenerator;
+use crate::providers::{PresetProvider, ProviderPreset};
+use crate::quirks::Quirks;
+use crate::scopes::Scopes;
+use crate::token::{ProviderClient, TokenSet};
+use crate::{http, OAuthClient, StateAuth};
+
+/// Pocket doesn't follow OAuth2: the app gets a request token, the user authorizes it and the app
+/// exchanges it for the access token, all with JSON bodies. The provider has the same two steps of the other
+/// providers, the request token is saved as the verifier, and the callback has only the `state`,
+/// so use an empty code in the `PendingAuthorization::exchange`
+#[derive(Clone)]
+pub struct PocketProvider {
+    pub(crate) consumer_key: String,
+    pub(crate) redirect_url: String,
+    pub(crate) state: Option<StateAuth>,
+    state_generator: StateGenerator,
+}
+
+const REQUEST_URL: &str = "https://getpocket.com/v3/oauth/request";
+const AUTH_URL: &str = "https://getpocket.com/auth/authorize";
+const TOKEN_URL: &str = "https://getpocket.com/v3/oauth/authorize";
+
+#[derive(Deserialize)]
+struct RequestTokenResponse {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    access_token: String,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+impl PocketProvider {
+    /// Create a new PocketProvider
+    ///
+    /// # Arguments
+    /// * `consumer_key` - The consumer key of the app
+    /// * `redirect_url` - The redirect url, the `state` is added to it in the query
+    ///
+    pub fn new(consumer_key: String, redirect_url: String) -> Self {
+        PocketProvider {
+            consumer_key,
+            redirect_url,
+            state: None,
+            state_generator: StateGenerator::default(),
+        }
+    }
+
+    /// Consumer key of the app in Pocket
+    pub fn consumer_key(&self) -> &str {
+        &self.consumer_key
+    }
+
+    /// URL of the callback, the `state` is added to it in the query
+    pub fn redirect_url(&self) -> &str {
+        &self.redirect_url
+    }
+
+    /// Set the function used to create the `state` value
+    pub fn set_state_generator(mut self, state_generator: StateGenerator) -> Self {
+        self.state_generator = state_generator;
+        self
+    }
+
+    async fn post<T: for<'de> Deserialize<'de>>(
+        &self,
+        url: &str,
+        body: Value,
+    ) -> Result<T, OauthError> {
+        let response = http::execute(
+            "oauth.token",
+            http::client()?
+                .post(url)
+                .header("X-Accept", "application/json")
+                .json(&body),
+        )
+        .await
+        .map_err(|_| OauthError::TokenRequestFailed)?;
+        if !response.status().is_success() {
+            return Err(OauthError::TokenRequestFailed);
+        }
+        response
+            .json::<T>()
+            .await
+            .map_err(|_| OauthError::TokenRequestFailed)
+    }
+}
+
+#[async_trait]
+impl OAuthClient for PocketProvider {
+    /// Pocket is not compatible with the oauth2 client
+    fn get_client(&self) -> Result<&ProviderClient, OauthError> {
+        Err(OauthError::UnsupportedFlow)
+    }
+
+    fn get_state(&self) -> Option<StateAuth> {
+        self.state.clone()
+    }
+
+    /// Pocket has no scopes, the scopes are ignored
+    async fn generate_url<S, F, Fut>(mut self, _scopes: S, save: F) -> Result<Box<Self>, OauthError>
+    where
+        S: Into<Scopes> + Send,
+        F: FnOnce(StateAuth) -> Fut + Send,
+        Fut: Future<Output = ()> + Send,
+    {
+        let state = self.state_generator.generate().secret().to_string();
+        let mut redirect_url =
+            Url::parse(&self.redirect_url).map_err(|_| OauthError::RedirectUrlCreationFailed)?;
+        redirect_url.query_pairs_mut().append_pair("state", &state);
+
+        let request_token: RequestTokenResponse = self
+            .post(
+                REQUEST_URL,
+                json!({ "consumer_key": self.consumer_key, "redirect_uri": redirect_url.as_str() }),
+            )
+            .await?;
+
+        let mut auth_url = Url::parse(AUTH_URL).map_err(|_| OauthError::AuthUrlCreationFailed)?;
+        auth_url
+            .query_pairs_mut()
+            .append_pair("request_token", &request_token.code)
+            .append_pair("redirect_uri", redirect_url.as_str());
+
+        let state = StateAuth {
+            url_generated: Some(auth_url.to_string()),
+            state,
+            verifier: request_token.code,
+            return_to: None,
+            fingerprint: None,
+            nonce: None,
+        };
+        self.state = Some(state.clone());
+        save(state).await;
+
+        Ok(Box::new(self.clone()))
+    }
+
+    async fn generate_token(&self, code: String, verifier: String) -> Result<String, OauthError> {
+        #[allow(deprecated)]
+        Ok(self.generate_token_set(code, verifier).await?.access_token)
+    }
+
+    /// The `verifier` is the request token saved in the first step, the `code` is ignored
+    async fn generate_token_set(
+        &self,
+        _code: String,
+        verifier: String,
+    ) -> Result<TokenSet, OauthError> {
+        let token: AccessTokenResponse = self
+            .post(
+                TOKEN_URL,
+                json!({ "consumer_key": self.consumer_key, "code": verifier }),
+            )
+            .await?;
+        Ok(TokenSet {
+            access_token: token.access_token,
+            token_type: String::from("bearer"),
+            refresh_token: None,
+            expires_in: None,
+            scopes: None,
+            extra: token.extra,
+        })
+    }
+
+    /// Pocket tokens don't expire and there is no refresh token
+    async fn refresh_token(&self, _refresh_token: String) -> Result<TokenSet, OauthError> {
+        Err(OauthError::UnsupportedFlow)
+    }
+}
+
+impl ProviderPreset for PocketProvider {
+    const NAME: &'static str = "pocket";
+
+    type Config = ();
+    type Provider = PocketProvider;
+
+    /// The `client_id` is the consumer key, Pocket has no client secret
+    fn create(
+        _config: &(),
+        client_id: String,
+        _client_secret: String,
+        redirect_url: String,
+    ) -> Result<PocketProvider, OauthError> {
+        Ok(PocketProvider::new(client_id, redirect_url))
+    }
+}
+
+impl PresetProvider for PocketProvider {
+    fn auth_url(&self) -> &str {
+        AUTH_URL
+    }
+
+    fn token_url(&self) -> &str {
+        TOKEN_URL
+    }
+
+    /// Pocket has no scopes
+    fn default_scopes(&self) -> Scopes {
+        Scopes::new()
+    }
+
+    fn quirks(&self) -> Quirks {
+        Quirks::new()
+    }
+}
@@ -0,0 +1,59 @@
+use crate::error::OauthError;
+use crate::{AuthType, CustomProvider};
+
+use super::{Environment, ProviderPreset};
+
+pub struct SalesforceProvider {}
+
+impl SalesforceProvider {
+    /// Create a new SalesforceProvider
+    ///
+    /// # Arguments
+    /// * `environment` - Sandbox uses test.salesforce.com and Production uses login.salesforce.com
+    /// * `client_id` - The consumer key of the connected app
+    /// * `client_secret` - The consumer secret of the connected app
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        environment: Environment,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let base_url = match environment {
+            Environment::Sandbox => "https://test.salesforce.com/services/oauth2",
+            Environment::Production => "https://login.salesforce.com/services/oauth2",
+        };
+        CustomProvider::new(
+            base_url.to_string() + "/authorize",
+            base_url.to_string() + "/token",
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for SalesforceProvider {
+    const NAME: &'static str = "salesforce";
+    const ISSUER: Option<&'static str> = Some("https://login.salesforce.com");
+
+    type Config = Environment;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &Environment,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(SalesforceProvider::new(
+            *config,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -1,8 +1,12 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::user::UserInfo;
 use crate::CustomProvider;
 
 pub struct SpotifyProvider {}
 
 impl SpotifyProvider {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
         CustomProvider::new(
             String::from("https://accounts.spotify.com/authorize"),
@@ -11,5 +15,28 @@ impl SpotifyProvider {
             client_secret,
             redirect_url,
         )
+        .set_user_info(UserInfo::with_pointers(
+            "https://api.spotify.com/v1/me",
+            "/id",
+            Some("/email"),
+            Some("/display_name"),
+            Some("/images/0/url"),
+        ))
+    }
+}
+
+impl ProviderPreset for SpotifyProvider {
+    const NAME: &'static str = "spotify";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(SpotifyProvider::new(client_id, client_secret, redirect_url))
     }
 }
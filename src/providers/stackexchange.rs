@@ -0,0 +1,74 @@
+use crate::error::OauthError;
+use serde_json::Value;
+
+use crate::providers::ProviderPreset;
+use crate::quirks::Quirks;
+use crate::{AuthType, CustomProvider};
+
+pub struct StackExchangeProvider {}
+
+impl StackExchangeProvider {
+    /// Create a new StackExchangeProvider
+    ///
+    /// The `/json` token endpoint returns the expiration in `expires` and no `token_type`, both are fixed by the quirks
+    ///
+    /// # Arguments
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://stackoverflow.com/oauth"),
+            String::from("https://stackoverflow.com/oauth/access_token/json"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_quirks(
+            Quirks::new()
+                .rename_field("expires", "expires_in")
+                .map_response(|fields| {
+                    fields
+                        .entry("token_type")
+                        .or_insert_with(|| Value::String("bearer".to_string()));
+                }),
+        )
+    }
+
+    /// Query parameters of the requests to `https://api.stackexchange.com/2.3`,
+    /// the API gives higher quotas to the requests with the key of the app
+    /// # Arguments
+    /// * `site` - The site of the network, like `stackoverflow`
+    /// * `key` - The key of the app in Stack Apps
+    /// * `access_token` - The token generated
+    pub fn api_params(site: &str, key: &str, access_token: &str) -> [(&'static str, String); 3] {
+        [
+            ("site", site.to_string()),
+            ("key", key.to_string()),
+            ("access_token", access_token.to_string()),
+        ]
+    }
+}
+
+impl ProviderPreset for StackExchangeProvider {
+    const NAME: &'static str = "stackexchange";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(StackExchangeProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -0,0 +1,40 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct SurveyMonkeyProvider {}
+
+impl SurveyMonkeyProvider {
+    /// Create a new SurveyMonkeyProvider, the token doesn't expire and there is no refresh token
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://api.surveymonkey.com/oauth/authorize"),
+            String::from("https://api.surveymonkey.com/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for SurveyMonkeyProvider {
+    const NAME: &'static str = "surveymonkey";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(SurveyMonkeyProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
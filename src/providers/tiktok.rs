@@ -0,0 +1,48 @@
+use crate::error::OauthError;
+use oauth2::AuthType;
+
+use crate::providers::ProviderPreset;
+use crate::quirks::Quirks;
+use crate::scopes::ScopeDelimiter;
+use crate::CustomProvider;
+
+pub struct TikTokProvider {}
+
+impl TikTokProvider {
+    /// Create a new TikTokProvider
+    ///
+    /// # Arguments
+    /// * `client_key` - The client key, TikTok uses this name instead of client_id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_key: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://www.tiktok.com/v2/auth/authorize/"),
+            String::from("https://open.tiktokapis.com/v2/oauth/token/"),
+            client_key,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_scope_delimiter(ScopeDelimiter::Comma)
+        .set_quirks(Quirks::new().rename_param("client_id", "client_key"))
+    }
+}
+
+impl ProviderPreset for TikTokProvider {
+    const NAME: &'static str = "tiktok";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(TikTokProvider::new(client_id, client_secret, redirect_url))
+    }
+}
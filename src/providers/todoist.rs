@@ -0,0 +1,38 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::scopes::ScopeDelimiter;
+use crate::{AuthType, CustomProvider};
+
+pub struct TodoistProvider {}
+
+impl TodoistProvider {
+    /// Create a new TodoistProvider, Todoist separates the scopes with comma
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://todoist.com/oauth/authorize"),
+            String::from("https://todoist.com/oauth/access_token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_scope_delimiter(ScopeDelimiter::Comma)
+    }
+}
+
+impl ProviderPreset for TodoistProvider {
+    const NAME: &'static str = "todoist";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(TodoistProvider::new(client_id, client_secret, redirect_url))
+    }
+}
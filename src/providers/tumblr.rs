@@ -0,0 +1,36 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct TumblrProvider {}
+
+impl TumblrProvider {
+    /// Create a new TumblrProvider, request the `offline_access` scope to receive the refresh token and use `refresh_token` to renew the access token
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://www.tumblr.com/oauth2/authorize"),
+            String::from("https://api.tumblr.com/v2/oauth2/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for TumblrProvider {
+    const NAME: &'static str = "tumblr";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(TumblrProvider::new(client_id, client_secret, redirect_url))
+    }
+}
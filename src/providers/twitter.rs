@@ -1,8 +1,42 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::scopes::Scopes;
+use crate::user::{UserInfo, UserModel};
 use crate::CustomProvider;
 
+/// User of the Twitter `/2/users/me` endpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TwitterUser {
+    pub id: String,
+    pub name: String,
+    pub username: String,
+    pub profile_image_url: Option<String>,
+}
+
+/// The user is inside the `data` field
+impl UserModel for TwitterUser {
+    fn from_raw(mut raw: Value) -> Result<Self, OauthError> {
+        serde_json::from_value(
+            raw.get_mut("data")
+                .ok_or(OauthError::UserInfoFailed)?
+                .take(),
+        )
+        .map_err(|_| OauthError::UserInfoFailed)
+    }
+}
+
 pub struct TwitterProvider {}
 
+/// Scopes needed to read the user and get a refresh token
+pub const DEFAULT_SCOPES: [&str; 3] = ["tweet.read", "users.read", "offline.access"];
+
 impl TwitterProvider {
+    /// Create the provider for a confidential client, the client id and secret are sent with Basic authentication.
+    /// Without scopes, `DEFAULT_SCOPES` is requested, so the token comes with a refresh token
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
         CustomProvider::new(
             String::from("https://twitter.com/i/oauth2/authorize"),
@@ -11,5 +45,42 @@ impl TwitterProvider {
             client_secret,
             redirect_url,
         )
+        .set_default_scopes(DEFAULT_SCOPES)
+        .set_user_info(UserInfo::with_pointers(
+            "https://api.twitter.com/2/users/me?user.fields=profile_image_url",
+            "/data/id",
+            None,
+            Some("/data/name"),
+            Some("/data/profile_image_url"),
+        ))
+    }
+
+    /// Create the provider for a public client (native and single page apps), only the client id is sent
+    pub fn new_public(client_id: String, redirect_url: String) -> CustomProvider {
+        TwitterProvider::new(client_id, String::new(), redirect_url).set_public_client()
+    }
+
+    /// Add `offline.access` and the scopes to read the user in the scopes, the refresh token is only
+    /// returned with `offline.access`
+    pub fn offline_scopes(scopes: impl Into<Scopes>) -> Scopes {
+        DEFAULT_SCOPES
+            .into_iter()
+            .fold(scopes.into(), |scopes, scope| scopes.with(scope))
+    }
+}
+
+impl ProviderPreset for TwitterProvider {
+    const NAME: &'static str = "twitter";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(TwitterProvider::new(client_id, client_secret, redirect_url))
     }
 }
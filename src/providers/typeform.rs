@@ -0,0 +1,40 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct TypeformProvider {}
+
+impl TypeformProvider {
+    /// Create a new TypeformProvider, request the `offline` scope to receive the refresh token
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://api.typeform.com/oauth/authorize"),
+            String::from("https://api.typeform.com/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for TypeformProvider {
+    const NAME: &'static str = "typeform";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(TypeformProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
@@ -0,0 +1,41 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct UnsplashProvider {}
+
+impl UnsplashProvider {
+    /// Create a new UnsplashProvider, the `public` scope is used when no scope is requested
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://unsplash.com/oauth/authorize"),
+            String::from("https://unsplash.com/oauth/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_default_scopes(["public"])
+    }
+}
+
+impl ProviderPreset for UnsplashProvider {
+    const NAME: &'static str = "unsplash";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(UnsplashProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
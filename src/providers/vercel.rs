@@ -0,0 +1,43 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::token::TokenSet;
+use crate::{AuthType, CustomProvider};
+
+pub struct VercelProvider {}
+
+impl VercelProvider {
+    /// Create a new VercelProvider, the team of the installation is returned with the token, check `team_id`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://vercel.com/oauth/authorize"),
+            String::from("https://api.vercel.com/v2/oauth/access_token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+
+    /// Get the `team_id` returned with the token, it is None when the integration was installed in a personal account.
+    /// Send it as the `teamId` query parameter in the API calls of the team
+    pub fn team_id(token: &TokenSet) -> Option<&str> {
+        token.extra_str("team_id")
+    }
+}
+
+impl ProviderPreset for VercelProvider {
+    const NAME: &'static str = "vercel";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(VercelProvider::new(client_id, client_secret, redirect_url))
+    }
+}
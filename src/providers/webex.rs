@@ -0,0 +1,36 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct WebexProvider {}
+
+impl WebexProvider {
+    /// Create a new WebexProvider, the access token expires in 14 days and can be renewed with `refresh_token`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://webexapis.com/v1/authorize"),
+            String::from("https://webexapis.com/v1/access_token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for WebexProvider {
+    const NAME: &'static str = "webex";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(WebexProvider::new(client_id, client_secret, redirect_url))
+    }
+}
@@ -0,0 +1,63 @@
+use crate::error::OauthError;
+use serde_json::Value;
+
+use crate::providers::ProviderPreset;
+use crate::quirks::Quirks;
+use crate::{AuthType, CustomProvider};
+
+pub struct WithingsProvider {}
+
+impl WithingsProvider {
+    /// Create a new WithingsProvider
+    ///
+    /// Withings requires `action=requesttoken` in the token requests and returns the token inside a `body` field,
+    /// both are handled by the quirks. The `userid` is returned in the extra fields of the token
+    ///
+    /// # Arguments
+    /// * `client_id` - The client id
+    /// * `client_secret` - The consumer secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://account.withings.com/oauth2_user/authorize2"),
+            String::from("https://wbsapi.withings.net/v2/oauth2"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+        .set_quirks(
+            Quirks::new()
+                .token_param("action", "requesttoken")
+                .refresh_param("action", "requesttoken")
+                .map_response(|fields| {
+                    // {"status": 0, "body": {"access_token": ...}}, any other status is an error without the token
+                    if let Some(Value::Object(body)) = fields.remove("body") {
+                        *fields = body;
+                    }
+                }),
+        )
+    }
+}
+
+impl ProviderPreset for WithingsProvider {
+    const NAME: &'static str = "withings";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(WithingsProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
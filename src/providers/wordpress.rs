@@ -0,0 +1,40 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+pub struct WordPressProvider {}
+
+impl WordPressProvider {
+    /// Create a new WordPressProvider, the `blog_id` and `blog_url` of the authorized site are returned in the extra fields of the token
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://public-api.wordpress.com/oauth2/authorize"),
+            String::from("https://public-api.wordpress.com/oauth2/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for WordPressProvider {
+    const NAME: &'static str = "wordpress";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(WordPressProvider::new(
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
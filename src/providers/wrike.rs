@@ -0,0 +1,68 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::template::ProviderTemplate;
+use crate::token::TokenSet;
+use crate::{AuthType, CustomProvider};
+
+pub struct WrikeProvider {}
+
+impl WrikeProvider {
+    /// Create a new WrikeProvider, the API calls need the host returned with the token, check `api_base_url`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(client_id: String, client_secret: String, redirect_url: String) -> CustomProvider {
+        CustomProvider::new(
+            String::from("https://login.wrike.com/oauth2/authorize/v4"),
+            String::from("https://login.wrike.com/oauth2/token"),
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+
+    /// Get the base URL of the API from the `host` returned with the token, the account data lives in this host
+    /// # Return
+    /// `InvalidConfig` when the token has no host, or the host has characters that could change the URL
+    pub fn api_base_url(token: &TokenSet) -> Result<String, OauthError> {
+        let host = token.extra_str("host").ok_or(OauthError::InvalidConfig)?;
+        ProviderTemplate::fill_value(API_BASE_URL, "host", host)
+    }
+
+    /// Create the provider used to refresh the token, the refresh needs to be done in the `host` returned with the token
+    /// # Return
+    /// `InvalidConfig` when the host has characters that could change the URL
+    pub fn for_host(
+        host: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(CustomProvider::new(
+            String::from("https://login.wrike.com/oauth2/authorize/v4"),
+            ProviderTemplate::fill_value(TOKEN_URL, "host", host)?,
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody))
+    }
+}
+
+const API_BASE_URL: &str = "https://{host}/api/v4";
+const TOKEN_URL: &str = "https://{host}/oauth2/token";
+
+impl ProviderPreset for WrikeProvider {
+    const NAME: &'static str = "wrike";
+
+    type Config = ();
+    type Provider = CustomProvider;
+
+    fn create(
+        _config: &(),
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(WrikeProvider::new(client_id, client_secret, redirect_url))
+    }
+}
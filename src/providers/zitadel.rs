@@ -0,0 +1,82 @@
+use crate::error::OauthError;
+use crate::openid::CustomOpenIdProvider;
+use crate::providers::{Domain, ProviderPreset};
+use crate::template::ProviderTemplate;
+use crate::user::UserInfo;
+use crate::CustomProvider;
+
+pub struct ZitadelProvider {}
+
+impl ZitadelProvider {
+    /// Create a new ZitadelProvider
+    ///
+    /// # Arguments
+    /// * `instance_domain` - The domain of the instance, like `my-instance.zitadel.cloud`
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        instance_domain: &str,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        let template = template(instance_domain);
+        let base_url = template.fill(BASE_URL)?;
+        Ok(template
+            .build(client_id, client_secret, redirect_url)?
+            .set_user_info(UserInfo::new(&format!("{base_url}/oidc/v1/userinfo"))))
+    }
+
+    /// Create the provider for an application with the PKCE authentication method, the default of Zitadel
+    /// for web and native apps. Only the client id and the PKCE verifier are sent
+    pub fn new_public(
+        instance_domain: &str,
+        client_id: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(
+            ZitadelProvider::new(instance_domain, client_id, String::new(), redirect_url)?
+                .set_public_client(),
+        )
+    }
+
+    /// Wrap the provider with the OpenID behavior, with the issuer and keys of the instance
+    pub fn openid(
+        instance_domain: &str,
+        provider: CustomProvider,
+    ) -> Result<CustomOpenIdProvider, OauthError> {
+        let base_url = template(instance_domain).fill(BASE_URL)?;
+        Ok(provider
+            .into_openid(&base_url, &format!("{base_url}/oauth/v2/keys"))
+            .set_user_url(&format!("{base_url}/oidc/v1/userinfo")))
+    }
+}
+
+const BASE_URL: &str = "https://{instance_domain}";
+
+fn template(instance_domain: &str) -> ProviderTemplate {
+    ProviderTemplate::new(
+        &format!("{BASE_URL}/oauth/v2/authorize"),
+        &format!("{BASE_URL}/oauth/v2/token"),
+    )
+    .set("instance_domain", instance_domain)
+}
+
+impl ProviderPreset for ZitadelProvider {
+    const NAME: &'static str = "zitadel";
+
+    type Config = Domain;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &Domain,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        ZitadelProvider::new(&config.0, client_id, client_secret, redirect_url)
+    }
+}
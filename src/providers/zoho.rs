@@ -0,0 +1,84 @@
+use crate::error::OauthError;
+use crate::providers::ProviderPreset;
+use crate::{AuthType, CustomProvider};
+
+/// Data center of the Zoho account, the credentials only work in the data center where the app was created
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Datacenter {
+    #[default]
+    Us,
+    Eu,
+    In,
+    Au,
+    Jp,
+    Ca,
+}
+
+impl Datacenter {
+    /// Top-level domain of the accounts server
+    pub fn domain(&self) -> &'static str {
+        match self {
+            Datacenter::Us => "com",
+            Datacenter::Eu => "eu",
+            Datacenter::In => "in",
+            Datacenter::Au => "com.au",
+            Datacenter::Jp => "jp",
+            Datacenter::Ca => "ca",
+        }
+    }
+}
+
+pub struct ZohoProvider {}
+
+impl ZohoProvider {
+    /// Create a new ZohoProvider
+    ///
+    /// Request the `access_type=offline` with `set_auth_param` to receive the refresh token.
+    /// The `api_domain` of the account is returned in the extra fields of the token
+    ///
+    /// # Arguments
+    /// * `datacenter` - The data center of the account
+    /// * `client_id` - The client id
+    /// * `client_secret` - The client secret
+    /// * `redirect_url` - The redirect url
+    ///
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        datacenter: Datacenter,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let base_url = format!("https://accounts.zoho.{}/oauth/v2", datacenter.domain());
+        CustomProvider::new(
+            base_url.clone() + "/auth",
+            base_url + "/token",
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .set_auth_type(AuthType::RequestBody)
+    }
+}
+
+impl ProviderPreset for ZohoProvider {
+    const NAME: &'static str = "zoho";
+    const ISSUER: Option<&'static str> = Some("https://accounts.zoho.com");
+
+    type Config = Datacenter;
+    type Provider = CustomProvider;
+
+    fn create(
+        config: &Datacenter,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        Ok(ZohoProvider::new(
+            *config,
+            client_id,
+            client_secret,
+            redirect_url,
+        ))
+    }
+}
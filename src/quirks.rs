@@ -0,0 +1,268 @@
+//! Quirks
+//!
+//! Some providers don't follow the OAuth2 spec in every detail. The quirks are applied by the lib
+//! in the authorization URL and in the token request, so these providers can still be created as a preset.
+
+use std::fmt;
+use std::sync::Arc;
+
+use oauth2::http::header::{HeaderValue, CONTENT_TYPE};
+use oauth2::url::{form_urlencoded, Url};
+use oauth2::HttpResponse;
+use serde_json::{Map, Value};
+
+/// Format of the body returned by the token endpoint
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TokenResponseFormat {
+    #[default]
+    Json,
+    /// `access_token=...&expires=...`, converted to JSON before being parsed
+    FormUrlEncoded,
+}
+
+type MapperFn = dyn Fn(&mut Map<String, Value>) + Send + Sync;
+
+/// How the spaces are encoded in the query of the authorization URL
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueryEncoding {
+    /// `scope=openid+email`, the `application/x-www-form-urlencoded` format
+    #[default]
+    Plus,
+    /// `scope=openid%20email`, for the providers and WAFs that don't decode `+` as space
+    Percent,
+}
+
+/// Function that changes the fields of the token response before it is parsed
+#[derive(Clone)]
+pub struct ResponseMapper(Arc<MapperFn>);
+
+impl fmt::Debug for ResponseMapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ResponseMapper")
+    }
+}
+
+/// Differences from the spec that the provider has
+#[derive(Clone, Debug, Default)]
+pub struct Quirks {
+    param_names: Vec<(String, String)>,
+    auth_params: Vec<(String, String)>,
+    token_params: Vec<(String, String)>,
+    refresh_params: Vec<(String, String)>,
+    token_response_format: TokenResponseFormat,
+    field_names: Vec<(String, String)>,
+    response_mapper: Option<ResponseMapper>,
+    query_encoding: QueryEncoding,
+    dedupe_params: bool,
+    param_order: Vec<String>,
+}
+
+impl Quirks {
+    pub fn new() -> Self {
+        Quirks::default()
+    }
+
+    /// Send the standard parameter with another name, like `client_id` as `client_key` in TikTok
+    /// # Arguments
+    /// * `standard` - &str - The name used in the spec
+    /// * `provider` - &str - The name that the provider expects
+    pub fn rename_param(mut self, standard: &str, provider: &str) -> Self {
+        self.param_names
+            .push((standard.to_string(), provider.to_string()));
+        self
+    }
+
+    /// Add a parameter that the provider requires in the authorization URL
+    pub fn auth_param(mut self, name: &str, value: &str) -> Self {
+        self.auth_params.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Add a parameter that the provider requires in the token request of the code exchange
+    pub fn token_param(mut self, name: &str, value: &str) -> Self {
+        self.token_params
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Add a parameter that the provider requires in the token request of the refresh token
+    pub fn refresh_param(mut self, name: &str, value: &str) -> Self {
+        self.refresh_params
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set how the spaces are encoded in the authorization URL
+    pub fn query_encoding(mut self, query_encoding: QueryEncoding) -> Self {
+        self.query_encoding = query_encoding;
+        self
+    }
+
+    /// Send every parameter of the authorization URL only once. When a parameter is added twice,
+    /// like a `prompt` set by the preset and by the app, the last value is sent in the place of the first one
+    pub fn dedupe_params(mut self) -> Self {
+        self.dedupe_params = true;
+        self
+    }
+
+    /// Send these parameters first in the authorization URL, in this order, for the providers that check the order
+    pub fn param_order(mut self, names: &[&str]) -> Self {
+        self.param_order = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Set the format of the body returned by the token endpoint
+    pub fn token_response_format(mut self, token_response_format: TokenResponseFormat) -> Self {
+        self.token_response_format = token_response_format;
+        self
+    }
+
+    /// Read the field of the token response with the standard name, like `expires` as `expires_in`
+    /// # Arguments
+    /// * `provider` - &str - The name returned by the provider
+    /// * `standard` - &str - The name used in the spec
+    pub fn rename_field(mut self, provider: &str, standard: &str) -> Self {
+        self.field_names
+            .push((provider.to_string(), standard.to_string()));
+        self
+    }
+
+    /// Change the fields of the token response before it is parsed, for the cases that a rename is not enough
+    pub fn map_response<F>(mut self, mapper: F) -> Self
+    where
+        F: Fn(&mut Map<String, Value>) + Send + Sync + 'static,
+    {
+        self.response_mapper = Some(ResponseMapper(Arc::new(mapper)));
+        self
+    }
+
+    fn param_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.param_names
+            .iter()
+            .find(|(standard, _)| standard == name)
+            .map(|(_, provider)| provider.as_str())
+            .unwrap_or(name)
+    }
+
+    fn rename_pairs<'a>(
+        &self,
+        pairs: impl Iterator<Item = (std::borrow::Cow<'a, str>, std::borrow::Cow<'a, str>)>,
+    ) -> Vec<(String, String)> {
+        pairs
+            .map(|(name, value)| (self.param_name(&name).to_string(), value.into_owned()))
+            .collect()
+    }
+
+    /// Apply the quirks in the authorization URL
+    pub(crate) fn apply_to_url(&self, mut url: Url) -> Url {
+        if self.param_names.is_empty()
+            && self.auth_params.is_empty()
+            && self.query_encoding == QueryEncoding::Plus
+            && !self.dedupe_params
+            && self.param_order.is_empty()
+        {
+            return url;
+        }
+        let mut pairs = self.rename_pairs(url.query_pairs());
+        pairs.extend(self.auth_params.iter().cloned());
+        if self.dedupe_params {
+            let mut unique: Vec<(String, String)> = Vec::with_capacity(pairs.len());
+            for (name, value) in pairs {
+                match unique.iter_mut().find(|(other, _)| *other == name) {
+                    Some(pair) => pair.1 = value,
+                    None => unique.push((name, value)),
+                }
+            }
+            pairs = unique;
+        }
+        if !self.param_order.is_empty() {
+            // The sort is stable, so the parameters that are not in the list keep their order
+            pairs.sort_by_key(|(name, _)| {
+                self.param_order
+                    .iter()
+                    .position(|ordered| ordered == name)
+                    .unwrap_or(self.param_order.len())
+            });
+        }
+        let query = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(pairs)
+            .finish();
+        // A `+` in the values is encoded as `%2B`, so every `+` left is a space
+        let query = match self.query_encoding {
+            QueryEncoding::Plus => query,
+            QueryEncoding::Percent => query.replace('+', "%20"),
+        };
+        url.set_query(Some(&query));
+        url
+    }
+
+    /// Apply the quirks in the form body of the token request
+    pub(crate) fn apply_to_body(&self, body: Vec<u8>) -> Vec<u8> {
+        if self.param_names.is_empty()
+            && self.token_params.is_empty()
+            && self.refresh_params.is_empty()
+        {
+            return body;
+        }
+        let mut pairs = self.rename_pairs(form_urlencoded::parse(&body));
+        let grant_type = pairs
+            .iter()
+            .find(|(name, _)| name == "grant_type")
+            .map(|(_, value)| value.clone());
+        match grant_type.as_deref() {
+            Some("authorization_code") => pairs.extend(self.token_params.iter().cloned()),
+            Some("refresh_token") => pairs.extend(self.refresh_params.iter().cloned()),
+            _ => {}
+        }
+        form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(pairs)
+            .finish()
+            .into_bytes()
+    }
+
+    /// Apply the quirks in the response of the token request
+    pub(crate) fn apply_to_response(&self, mut response: HttpResponse) -> HttpResponse {
+        let is_form = self.token_response_format == TokenResponseFormat::FormUrlEncoded;
+        if !response.status_code.is_success()
+            || (!is_form && self.field_names.is_empty() && self.response_mapper.is_none())
+        {
+            return response;
+        }
+        let mut fields = if is_form {
+            let mut fields = form_urlencoded::parse(&response.body)
+                .map(|(name, value)| (name.into_owned(), Value::String(value.into_owned())))
+                .collect::<Map<String, Value>>();
+            // The oauth2 lib requires the token_type, the providers that return form don't send it
+            fields
+                .entry("token_type")
+                .or_insert_with(|| Value::String("bearer".to_string()));
+            fields
+        } else {
+            match serde_json::from_slice::<Map<String, Value>>(&response.body) {
+                Ok(fields) => fields,
+                Err(_) => return response,
+            }
+        };
+        for (provider, standard) in &self.field_names {
+            if let Some(value) = fields.remove(provider) {
+                fields.entry(standard.as_str()).or_insert(value);
+            }
+        }
+        if let Some(ResponseMapper(mapper)) = &self.response_mapper {
+            mapper(&mut fields);
+        }
+        // Form values are always strings, the oauth2 lib requires a number
+        let expires_in = fields
+            .get("expires_in")
+            .and_then(Value::as_str)
+            .and_then(|expires_in| expires_in.parse::<u64>().ok());
+        if let Some(expires_in) = expires_in {
+            fields.insert("expires_in".to_string(), Value::from(expires_in));
+        }
+        response.body = Value::Object(fields).to_string().into_bytes();
+        response
+            .headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        response
+    }
+}
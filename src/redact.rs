@@ -0,0 +1,47 @@
+//! Masking of the secrets in the `Debug` output, so logging a provider or a token with `{:?}` doesn't leak them
+
+use std::fmt;
+
+/// Secret shown with only its last 4 characters, the short secrets are hidden entirely
+pub(crate) struct Redacted<'a>(pub &'a str);
+
+impl fmt::Debug for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.0.chars().count();
+        match count > 8 {
+            true => {
+                let last: String = self.0.chars().skip(count - 4).collect();
+                write!(f, "\"****{last}\"")
+            }
+            false => f.write_str("\"****\""),
+        }
+    }
+}
+
+/// Optional secret, `None` is shown as it is
+pub(crate) fn redacted(secret: &Option<String>) -> Option<Redacted<'_>> {
+    secret.as_deref().map(Redacted)
+}
+
+/// URL shown without the query, where the login URLs have the `state` and the PKCE challenge
+pub(crate) struct RedactedUrl<'a>(pub &'a str);
+
+impl fmt::Debug for RedactedUrl<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.split_once('?') {
+            Some((url, _)) => write!(f, "\"{url}?****\""),
+            None => write!(f, "{:?}", self.0),
+        }
+    }
+}
+
+/// Parameters shown with the values masked, they can have tokens, like the `id_token_hint`
+pub(crate) struct RedactedParams<'a>(pub &'a [(String, String)]);
+
+impl fmt::Debug for RedactedParams<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(name, value)| (name, Redacted(value))))
+            .finish()
+    }
+}
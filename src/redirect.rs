@@ -0,0 +1,89 @@
+//! Redirect URL validation
+//!
+//! A redirect URL that is not safe (http, with fragment or pointing to an unknown host) can be used to leak the code,
+//! and a `return_to` value sent by the user can be used as an open redirect after the login.
+
+use oauth2::url::{Host, Url};
+
+use crate::error::OauthError;
+
+/// Rules used to validate the redirect URL of the provider and the `return_to` values
+#[derive(Clone, Debug, Default)]
+pub struct RedirectPolicy {
+    allow_insecure_localhost: bool,
+    allowed_hosts: Vec<String>,
+}
+
+impl RedirectPolicy {
+    pub fn new() -> Self {
+        RedirectPolicy::default()
+    }
+
+    /// Accept http URLs when the host is localhost, 127.0.0.1 or ::1, used in development
+    pub fn allow_insecure_localhost(mut self, allow: bool) -> Self {
+        self.allow_insecure_localhost = allow;
+        self
+    }
+
+    /// Add a host to the allowlist, when the list is empty any host is accepted in the redirect URL
+    /// and only relative paths are accepted in `return_to`
+    pub fn allow_host(mut self, host: &str) -> Self {
+        self.allowed_hosts.push(host.to_lowercase());
+        self
+    }
+
+    fn is_localhost(url: &Url) -> bool {
+        match url.host() {
+            Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+            Some(Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(Host::Ipv6(ip)) => ip.is_loopback(),
+            None => false,
+        }
+    }
+
+    fn is_allowed_host(&self, url: &Url) -> bool {
+        url.host_str()
+            .map(|host| self.allowed_hosts.iter().any(|allowed| allowed == host))
+            .unwrap_or(false)
+    }
+
+    fn is_allowed_scheme(&self, url: &Url) -> bool {
+        url.scheme() == "https"
+            || (url.scheme() == "http" && self.allow_insecure_localhost && Self::is_localhost(url))
+    }
+
+    /// Validate the redirect URL configured in the provider
+    pub fn validate_redirect_url(&self, redirect_url: &str) -> Result<(), OauthError> {
+        let url = Url::parse(redirect_url).map_err(|_| OauthError::RedirectUrlCreationFailed)?;
+        if !self.is_allowed_scheme(&url)
+            || url.fragment().is_some()
+            || (!self.allowed_hosts.is_empty() && !self.is_allowed_host(&url))
+        {
+            return Err(OauthError::UnsafeRedirectUrl);
+        }
+        Ok(())
+    }
+
+    /// Validate a `return_to` value sent by the user, relative paths like "/dashboard" are always accepted
+    /// # Return
+    /// The `return_to` value that is safe to redirect after the login
+    pub fn validate_return_to(&self, return_to: &str) -> Result<String, OauthError> {
+        // The browsers remove tabs and new lines, so "/\t/host" would become "//host"
+        if return_to.chars().any(|c| c.is_control()) {
+            return Err(OauthError::UnsafeRedirectUrl);
+        }
+        // "//host" and "/\host" are relative for the URL parser but the browsers handle them as another host
+        if return_to.starts_with('/')
+            && !return_to.starts_with("//")
+            && !return_to.starts_with("/\\")
+        {
+            return Ok(return_to.to_string());
+        }
+        let url = Url::parse(return_to).map_err(|_| OauthError::UnsafeRedirectUrl)?;
+        if self.is_allowed_scheme(&url) && self.is_allowed_host(&url) {
+            Ok(url.to_string())
+        } else {
+            Err(OauthError::UnsafeRedirectUrl)
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! OAuth 2.0 Dynamic Client Registration
+//!
+//! Register a new client in the provider (RFC 7591) and manage it after that (RFC 7592),
+//! so products that need one client per customer IdP can create it without going to the provider dashboard.
+//!
+//! The provider needs to support dynamic registration, check the `registration_endpoint` in its discovery document.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::OauthError;
+use crate::redact::redacted;
+use crate::{http, CustomProvider};
+
+/// Metadata of the client that will be registered, all the fields are optional in the RFC
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ClientMetadata {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirect_uris: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_endpoint_auth_method: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grant_types: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response_types: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contacts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tos_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwks_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub software_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub software_version: Option<String>,
+    /// Provider specific fields
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Client returned by the registration endpoint
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RegisteredClient {
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id_issued_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret_expires_at: Option<u64>,
+    /// Token used in the RFC 7592 management operations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registration_access_token: Option<String>,
+    /// URL used in the RFC 7592 management operations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registration_client_uri: Option<String>,
+    #[serde(flatten)]
+    pub metadata: ClientMetadata,
+}
+
+impl fmt::Debug for RegisteredClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegisteredClient")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &redacted(&self.client_secret))
+            .field("client_id_issued_at", &self.client_id_issued_at)
+            .field("client_secret_expires_at", &self.client_secret_expires_at)
+            .field(
+                "registration_access_token",
+                &redacted(&self.registration_access_token),
+            )
+            .field("registration_client_uri", &self.registration_client_uri)
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
+
+/// Register a new client in the provider
+/// # Arguments
+/// * `registration_endpoint` - &str - The registration endpoint of the provider
+/// * `metadata` - &ClientMetadata - The metadata of the new client
+/// # Return
+/// The client registered, with the client_id and client_secret generated by the provider
+pub async fn register_client(
+    registration_endpoint: &str,
+    metadata: &ClientMetadata,
+) -> Result<RegisteredClient, OauthError> {
+    send(http::client()?.post(registration_endpoint).json(metadata)).await
+}
+
+/// Same as `register_client`, but using the initial access token required by some providers
+pub async fn register_client_with_token(
+    registration_endpoint: &str,
+    initial_access_token: &str,
+    metadata: &ClientMetadata,
+) -> Result<RegisteredClient, OauthError> {
+    send(
+        http::client()?
+            .post(registration_endpoint)
+            .bearer_auth(initial_access_token)
+            .json(metadata),
+    )
+    .await
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<RegisteredClient, OauthError> {
+    let response = http::execute("oauth.registration", request)
+        .await
+        .map_err(|_| OauthError::RegistrationFailed)?;
+    if !response.status().is_success() {
+        return Err(OauthError::RegistrationFailed);
+    }
+    response
+        .json::<RegisteredClient>()
+        .await
+        .map_err(|_| OauthError::RegistrationFailed)
+}
+
+impl RegisteredClient {
+    /// Create a provider with the credentials of this client, a public client when the provider didn't return
+    /// a secret or the `token_endpoint_auth_method` is `none`
+    pub fn to_provider(
+        &self,
+        auth_url: String,
+        token_url: String,
+        redirect_url: String,
+    ) -> CustomProvider {
+        let provider = CustomProvider::new(
+            auth_url,
+            token_url,
+            self.client_id.clone(),
+            self.client_secret.clone().unwrap_or_default(),
+            redirect_url,
+        );
+        let public_client = self.client_secret.as_deref().unwrap_or_default().is_empty()
+            || self.metadata.token_endpoint_auth_method.as_deref() == Some("none");
+        match public_client {
+            true => provider.set_public_client(),
+            false => provider,
+        }
+    }
+
+    fn management(&self) -> Result<(&str, &str), OauthError> {
+        match (
+            &self.registration_client_uri,
+            &self.registration_access_token,
+        ) {
+            (Some(uri), Some(token)) => Ok((uri, token)),
+            _ => Err(OauthError::RegistrationManagementNotSupported),
+        }
+    }
+
+    /// Read the current configuration of the client (RFC 7592)
+    pub async fn read(&self) -> Result<RegisteredClient, OauthError> {
+        let (uri, token) = self.management()?;
+        send(http::client()?.get(uri).bearer_auth(token)).await
+    }
+
+    /// Replace the metadata of the client (RFC 7592)
+    pub async fn update(&self, metadata: &ClientMetadata) -> Result<RegisteredClient, OauthError> {
+        let (uri, token) = self.management()?;
+        let body = RegisteredClient {
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            client_id_issued_at: None,
+            client_secret_expires_at: None,
+            registration_access_token: None,
+            registration_client_uri: None,
+            metadata: metadata.clone(),
+        };
+        send(http::client()?.put(uri).bearer_auth(token).json(&body)).await
+    }
+
+    /// Delete the client from the provider (RFC 7592)
+    pub async fn delete(&self) -> Result<(), OauthError> {
+        let (uri, token) = self.management()?;
+        let response = http::execute(
+            "oauth.registration",
+            http::client()?.delete(uri).bearer_auth(token),
+        )
+        .await
+        .map_err(|_| OauthError::RegistrationFailed)?;
+        if !response.status().is_success() {
+            return Err(OauthError::RegistrationFailed);
+        }
+        Ok(())
+    }
+}
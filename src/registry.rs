@@ -0,0 +1,631 @@
+//! Registry of providers
+//!
+//! Create all the providers of the application from a config document, so the providers can be changed
+//! without changing the code. The values can use `${VAR}` to read the credentials from the environment:
+//! ```json
+//! {
+//!   "providers": {
+//!     "github": {
+//!       "kind": "github",
+//!       "client_id": "${GITHUB_CLIENT_ID}",
+//!       "client_secret": "${GITHUB_SECRET}",
+//!       "redirect_url": "http://localhost:3000/api/v1/github/callback",
+//!       "scopes": ["read:user"]
+//!     }
+//!   }
+//! }
+//! ```
+//! The same document can be written in TOML, with the `toml` feature, or in YAML, with the `yaml` feature:
+//! ```toml
+//! [providers.github]
+//! kind = "github"
+//! client_id = "${GITHUB_CLIENT_ID}"
+//! client_secret = "${GITHUB_SECRET}"
+//! redirect_url = "http://localhost:3000/api/v1/github/callback"
+//! scopes = ["read:user"]
+//! ```
+//! For other formats, deserialize the file into [`RegistryConfig`] and use [`ProviderRegistry::from_config_document`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::error::OauthError;
+use crate::providers::adobe::AdobeProvider;
+use crate::providers::authentik::AuthentikProvider;
+use crate::providers::autodesk::AutodeskProvider;
+use crate::providers::azureb2c::{AzureB2CConfig, AzureB2CProvider};
+use crate::providers::basecamp::BasecampProvider;
+use crate::providers::calendly::CalendlyProvider;
+use crate::providers::canva::CanvaProvider;
+use crate::providers::casdoor::CasdoorProvider;
+use crate::providers::clickup::ClickUpProvider;
+use crate::providers::cognito::{CognitoConfig, CognitoProvider};
+use crate::providers::deezer::DeezerProvider;
+use crate::providers::discord::DiscordProvider;
+use crate::providers::docusign::DocuSignProvider;
+use crate::providers::dribbble::DribbbleProvider;
+use crate::providers::etsy::EtsyProvider;
+use crate::providers::eventbrite::EventbriteProvider;
+use crate::providers::facebook::FacebookProvider;
+use crate::providers::fusionauth::{FusionAuthConfig, FusionAuthProvider};
+use crate::providers::gitea::GiteaProvider;
+use crate::providers::github::GithubProvider;
+use crate::providers::google::GoogleProvider;
+use crate::providers::imgur::ImgurProvider;
+use crate::providers::intuit::IntuitProvider;
+use crate::providers::jumpcloud::JumpCloudProvider;
+use crate::providers::linear::LinearProvider;
+use crate::providers::mastodon::MastodonProvider;
+use crate::providers::meetup::MeetupProvider;
+use crate::providers::microsoft::{MicrosoftProvider, Tenant};
+use crate::providers::miro::MiroProvider;
+use crate::providers::monday::MondayProvider;
+use crate::providers::nextcloud::NextcloudProvider;
+use crate::providers::onelogin::OneLoginProvider;
+use crate::providers::oryhydra::{OryHydraConfig, OryHydraProvider};
+use crate::providers::osu::OsuProvider;
+use crate::providers::paypal::PayPalProvider;
+use crate::providers::pingone::{PingOneConfig, PingOneProvider, Region};
+use crate::providers::pocket::PocketProvider;
+use crate::providers::salesforce::SalesforceProvider;
+use crate::providers::spotify::SpotifyProvider;
+use crate::providers::stackexchange::StackExchangeProvider;
+use crate::providers::surveymonkey::SurveyMonkeyProvider;
+use crate::providers::tiktok::TikTokProvider;
+use crate::providers::todoist::TodoistProvider;
+use crate::providers::tumblr::TumblrProvider;
+use crate::providers::twitter::TwitterProvider;
+use crate::providers::typeform::TypeformProvider;
+use crate::providers::uber::UberProvider;
+use crate::providers::unsplash::UnsplashProvider;
+use crate::providers::vercel::VercelProvider;
+use crate::providers::webex::WebexProvider;
+use crate::providers::withings::WithingsProvider;
+use crate::providers::wordpress::WordPressProvider;
+use crate::providers::wrike::WrikeProvider;
+use crate::providers::yelp::YelpProvider;
+use crate::providers::zitadel::ZitadelProvider;
+use crate::providers::zoho::{Datacenter, ZohoProvider};
+use crate::providers::{BaseUrl, Domain, Environment, ProviderPreset};
+use crate::redact::Redacted;
+use crate::scopes::Scopes;
+use crate::CustomProvider;
+
+/// Config document with the providers by name
+#[derive(Clone, Debug, Deserialize)]
+pub struct RegistryConfig {
+    pub providers: HashMap<String, ProviderConfig>,
+}
+
+/// Config of a provider
+#[derive(Clone, Deserialize)]
+pub struct ProviderConfig {
+    /// Name of the preset module, like `github`, or `custom` to use `auth_url` and `token_url`
+    pub kind: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_url: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    pub auth_url: Option<String>,
+    pub token_url: Option<String>,
+    /// Tenant of the `microsoft` preset, the default is `common`, and the optional `tenantId` of the `fusionauth` preset
+    pub tenant: Option<String>,
+    /// `sandbox` or `production`, used by the presets that have environments
+    pub environment: Option<String>,
+    /// `us`, `eu`, `in`, `au`, `jp` or `ca`, used by the `zoho` preset, the default is `us`
+    pub datacenter: Option<String>,
+    /// URL of the self-hosted presets, like `gitea`
+    pub base_url: Option<String>,
+    /// Domain of the presets that are per customer, like the domain prefix of the `cognito` user pool
+    pub domain: Option<String>,
+    /// Region of the presets that are per region, like `us-east-1` in the `cognito` preset
+    /// or `com`, `eu`, `asia` and `ca` in the `pingone` preset, where the environment id is in `domain`
+    pub region: Option<String>,
+    /// User flow or custom policy of the `azureb2c` preset, the B2C tenant name is in `tenant`
+    pub user_flow: Option<String>,
+    /// Other URLs of the token endpoint, used when the connection to the token URL fails
+    #[serde(default)]
+    pub token_url_fallbacks: Vec<String>,
+}
+
+impl fmt::Debug for ProviderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProviderConfig")
+            .field("kind", &self.kind)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &Redacted(&self.client_secret))
+            .field("redirect_url", &self.redirect_url)
+            .field("scopes", &self.scopes)
+            .field("auth_url", &self.auth_url)
+            .field("token_url", &self.token_url)
+            .field("tenant", &self.tenant)
+            .field("environment", &self.environment)
+            .field("datacenter", &self.datacenter)
+            .field("base_url", &self.base_url)
+            .field("domain", &self.domain)
+            .field("region", &self.region)
+            .field("user_flow", &self.user_flow)
+            .field("token_url_fallbacks", &self.token_url_fallbacks)
+            .finish()
+    }
+}
+
+/// Provider created from the config, Pocket doesn't follow OAuth2 so it is not a `CustomProvider`
+#[derive(Clone)]
+enum RegistryEntry {
+    OAuth2(Box<CustomProvider>),
+    Pocket(Box<PocketProvider>),
+}
+
+impl From<CustomProvider> for RegistryEntry {
+    fn from(provider: CustomProvider) -> Self {
+        RegistryEntry::OAuth2(Box::new(provider))
+    }
+}
+
+impl From<PocketProvider> for RegistryEntry {
+    fn from(provider: PocketProvider) -> Self {
+        RegistryEntry::Pocket(Box::new(provider))
+    }
+}
+
+#[derive(Clone)]
+struct RegisteredProvider {
+    provider: RegistryEntry,
+    scopes: Scopes,
+}
+
+/// Providers of the application by name
+#[derive(Clone, Default)]
+pub struct ProviderRegistry {
+    providers: HashMap<String, RegisteredProvider>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        ProviderRegistry::default()
+    }
+
+    /// Load the registry from a config file, or from a JSON document when the argument starts with `{`.
+    /// The format of the file is chosen by the extension: `.toml` with the `toml` feature,
+    /// `.yaml` and `.yml` with the `yaml` feature, and JSON for the others
+    /// # Return
+    /// `ConfigReadFailed` when the file can't be read, `InvalidConfig` when the document is not valid
+    pub fn from_config(path_or_json: &str) -> Result<Self, OauthError> {
+        if path_or_json.trim_start().starts_with('{') {
+            return ProviderRegistry::from_json(path_or_json);
+        }
+        let document =
+            std::fs::read_to_string(path_or_json).map_err(|_| OauthError::ConfigReadFailed)?;
+        match Path::new(path_or_json)
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            #[cfg(feature = "toml")]
+            Some("toml") => ProviderRegistry::from_toml(&document),
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => ProviderRegistry::from_yaml(&document),
+            _ => ProviderRegistry::from_json(&document),
+        }
+    }
+
+    /// Create the registry from a JSON document
+    pub fn from_json(document: &str) -> Result<Self, OauthError> {
+        ProviderRegistry::from_config_document(
+            serde_json::from_str(document).map_err(|_| OauthError::InvalidConfig)?,
+        )
+    }
+
+    /// Create the registry from a TOML document, with a `[providers.<name>]` table for each provider
+    #[cfg(feature = "toml")]
+    pub fn from_toml(document: &str) -> Result<Self, OauthError> {
+        ProviderRegistry::from_config_document(
+            toml::from_str(document).map_err(|_| OauthError::InvalidConfig)?,
+        )
+    }
+
+    /// Create the registry from a YAML document
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(document: &str) -> Result<Self, OauthError> {
+        ProviderRegistry::from_config_document(
+            serde_yaml::from_str(document).map_err(|_| OauthError::InvalidConfig)?,
+        )
+    }
+
+    /// Create the registry from a parsed config document
+    pub fn from_config_document(config: RegistryConfig) -> Result<Self, OauthError> {
+        config
+            .providers
+            .iter()
+            .try_fold(ProviderRegistry::new(), |mut registry, (name, config)| {
+                let provider = match build_provider(config)? {
+                    RegistryEntry::OAuth2(provider) => provider
+                        .set_token_url_fallbacks(
+                            &config
+                                .token_url_fallbacks
+                                .iter()
+                                .map(String::as_str)
+                                .collect::<Vec<_>>(),
+                        )
+                        .into(),
+                    // Pocket has no token endpoint of OAuth2 to fail over
+                    provider => provider,
+                };
+                registry.providers.insert(
+                    name.to_string(),
+                    RegisteredProvider {
+                        provider,
+                        scopes: config.scopes.iter().cloned().collect(),
+                    },
+                );
+                Ok(registry)
+            })
+    }
+
+    /// Add a provider with the scopes used in the login
+    pub fn register(mut self, name: &str, provider: CustomProvider, scopes: Scopes) -> Self {
+        self.providers.insert(
+            name.to_string(),
+            RegisteredProvider {
+                provider: provider.into(),
+                scopes,
+            },
+        );
+        self
+    }
+
+    /// Add a Pocket provider, it is not returned by `get` and `SelectedProvider`, get it with `get_pocket`
+    pub fn register_pocket(mut self, name: &str, provider: PocketProvider) -> Self {
+        self.providers.insert(
+            name.to_string(),
+            RegisteredProvider {
+                provider: provider.into(),
+                scopes: Scopes::new(),
+            },
+        );
+        self
+    }
+
+    /// Get the provider, it can be cloned to generate the URL. None for the Pocket providers
+    pub fn get(&self, name: &str) -> Option<&CustomProvider> {
+        match &self.providers.get(name)?.provider {
+            RegistryEntry::OAuth2(provider) => Some(provider.as_ref()),
+            RegistryEntry::Pocket(_) => None,
+        }
+    }
+
+    /// Get the Pocket provider, created with the `pocket` kind in the config
+    pub fn get_pocket(&self, name: &str) -> Option<&PocketProvider> {
+        match &self.providers.get(name)?.provider {
+            RegistryEntry::Pocket(provider) => Some(provider.as_ref()),
+            RegistryEntry::OAuth2(_) => None,
+        }
+    }
+
+    /// Get the scopes configured for the provider
+    pub fn scopes(&self, name: &str) -> Option<&Scopes> {
+        self.providers
+            .get(name)
+            .map(|registered| &registered.scopes)
+    }
+
+    /// Names of the registered providers
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.providers.keys().map(String::as_str)
+    }
+}
+
+/// Replace `${VAR}` with the environment variable, a missing variable is an error
+fn interpolate(value: &str) -> Result<String, OauthError> {
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..].find('}').ok_or(OauthError::InvalidConfig)? + start;
+        result.push_str(&rest[..start]);
+        result.push_str(
+            &std::env::var(&rest[start + 2..end]).map_err(|_| OauthError::InvalidConfig)?,
+        );
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Config of a preset read from the fields of the registry config
+trait FromProviderConfig: Sized {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError>;
+}
+
+impl FromProviderConfig for () {
+    fn from_provider_config(_config: &ProviderConfig) -> Result<Self, OauthError> {
+        Ok(())
+    }
+}
+
+impl FromProviderConfig for Environment {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        match config.environment.as_deref() {
+            None | Some("production") => Ok(Environment::Production),
+            Some("sandbox") => Ok(Environment::Sandbox),
+            Some(_) => Err(OauthError::InvalidConfig),
+        }
+    }
+}
+
+impl FromProviderConfig for Datacenter {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        match config.datacenter.as_deref() {
+            None | Some("us") => Ok(Datacenter::Us),
+            Some("eu") => Ok(Datacenter::Eu),
+            Some("in") => Ok(Datacenter::In),
+            Some("au") => Ok(Datacenter::Au),
+            Some("jp") => Ok(Datacenter::Jp),
+            Some("ca") => Ok(Datacenter::Ca),
+            Some(_) => Err(OauthError::InvalidConfig),
+        }
+    }
+}
+
+impl FromProviderConfig for Region {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        match config.region.as_deref() {
+            None | Some("com") => Ok(Region::NorthAmerica),
+            Some("eu") => Ok(Region::Europe),
+            Some("asia") => Ok(Region::AsiaPacific),
+            Some("ca") => Ok(Region::Canada),
+            Some(_) => Err(OauthError::InvalidConfig),
+        }
+    }
+}
+
+impl FromProviderConfig for Tenant {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        Ok(optional(&config.tenant)?
+            .map(Tenant::from)
+            .unwrap_or_default())
+    }
+}
+
+impl FromProviderConfig for BaseUrl {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        Ok(BaseUrl(required(&config.base_url)?))
+    }
+}
+
+impl FromProviderConfig for Domain {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        Ok(Domain(required(&config.domain)?))
+    }
+}
+
+impl FromProviderConfig for FusionAuthConfig {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        Ok(FusionAuthConfig {
+            base_url: required(&config.base_url)?,
+            tenant_id: optional(&config.tenant)?,
+        })
+    }
+}
+
+impl FromProviderConfig for OryHydraConfig {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        // The Ory Network projects are configured with the slug of the project in `domain`
+        match &config.domain {
+            Some(project_slug) => Ok(OryHydraConfig::Project(interpolate(project_slug)?)),
+            None => Ok(OryHydraConfig::PublicUrl(required(&config.base_url)?)),
+        }
+    }
+}
+
+impl FromProviderConfig for PingOneConfig {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        Ok(PingOneConfig {
+            environment_id: required(&config.domain)?,
+            region: Region::from_provider_config(config)?,
+        })
+    }
+}
+
+impl FromProviderConfig for AzureB2CConfig {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        Ok(AzureB2CConfig {
+            tenant_name: required(&config.tenant)?,
+            user_flow: required(&config.user_flow)?,
+        })
+    }
+}
+
+impl FromProviderConfig for CognitoConfig {
+    fn from_provider_config(config: &ProviderConfig) -> Result<Self, OauthError> {
+        let domain = required(&config.domain)?;
+        // The custom domains have no region
+        let region = match domain.contains('.') {
+            true => None,
+            false => Some(required(&config.region)?),
+        };
+        Ok(CognitoConfig { domain, region })
+    }
+}
+
+/// Value of a field that the preset requires, with the variables replaced
+fn required(value: &Option<String>) -> Result<String, OauthError> {
+    interpolate(value.as_deref().ok_or(OauthError::InvalidConfig)?)
+}
+
+/// Value of an optional field, with the variables replaced
+fn optional(value: &Option<String>) -> Result<Option<String>, OauthError> {
+    value.as_deref().map(interpolate).transpose()
+}
+
+type BuildPreset = fn(&ProviderConfig, String, String, String) -> Result<RegistryEntry, OauthError>;
+
+fn build_preset<P>(
+    config: &ProviderConfig,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+) -> Result<RegistryEntry, OauthError>
+where
+    P: ProviderPreset,
+    P::Config: FromProviderConfig,
+    P::Provider: Into<RegistryEntry>,
+{
+    Ok(P::create(
+        &P::Config::from_provider_config(config)?,
+        client_id,
+        client_secret,
+        redirect_url,
+    )?
+    .into())
+}
+
+/// Presets that can be used in the `kind` of the config, by `ProviderPreset::NAME`
+const PRESETS: &[(&str, BuildPreset)] = &[
+    (AdobeProvider::NAME, build_preset::<AdobeProvider>),
+    (AuthentikProvider::NAME, build_preset::<AuthentikProvider>),
+    (AutodeskProvider::NAME, build_preset::<AutodeskProvider>),
+    (AzureB2CProvider::NAME, build_preset::<AzureB2CProvider>),
+    (BasecampProvider::NAME, build_preset::<BasecampProvider>),
+    (CalendlyProvider::NAME, build_preset::<CalendlyProvider>),
+    (CanvaProvider::NAME, build_preset::<CanvaProvider>),
+    (CasdoorProvider::NAME, build_preset::<CasdoorProvider>),
+    (ClickUpProvider::NAME, build_preset::<ClickUpProvider>),
+    (CognitoProvider::NAME, build_preset::<CognitoProvider>),
+    (DeezerProvider::NAME, build_preset::<DeezerProvider>),
+    (DiscordProvider::NAME, build_preset::<DiscordProvider>),
+    (DocuSignProvider::NAME, build_preset::<DocuSignProvider>),
+    (DribbbleProvider::NAME, build_preset::<DribbbleProvider>),
+    (EtsyProvider::NAME, build_preset::<EtsyProvider>),
+    (EventbriteProvider::NAME, build_preset::<EventbriteProvider>),
+    (FacebookProvider::NAME, build_preset::<FacebookProvider>),
+    (FusionAuthProvider::NAME, build_preset::<FusionAuthProvider>),
+    (GiteaProvider::NAME, build_preset::<GiteaProvider>),
+    (GithubProvider::NAME, build_preset::<GithubProvider>),
+    (GoogleProvider::NAME, build_preset::<GoogleProvider>),
+    (ImgurProvider::NAME, build_preset::<ImgurProvider>),
+    (IntuitProvider::NAME, build_preset::<IntuitProvider>),
+    (JumpCloudProvider::NAME, build_preset::<JumpCloudProvider>),
+    (LinearProvider::NAME, build_preset::<LinearProvider>),
+    (MastodonProvider::NAME, build_preset::<MastodonProvider>),
+    (MeetupProvider::NAME, build_preset::<MeetupProvider>),
+    (MicrosoftProvider::NAME, build_preset::<MicrosoftProvider>),
+    (MiroProvider::NAME, build_preset::<MiroProvider>),
+    (MondayProvider::NAME, build_preset::<MondayProvider>),
+    (NextcloudProvider::NAME, build_preset::<NextcloudProvider>),
+    (OneLoginProvider::NAME, build_preset::<OneLoginProvider>),
+    (OryHydraProvider::NAME, build_preset::<OryHydraProvider>),
+    (OsuProvider::NAME, build_preset::<OsuProvider>),
+    (PayPalProvider::NAME, build_preset::<PayPalProvider>),
+    (PingOneProvider::NAME, build_preset::<PingOneProvider>),
+    (PocketProvider::NAME, build_preset::<PocketProvider>),
+    (SalesforceProvider::NAME, build_preset::<SalesforceProvider>),
+    (SpotifyProvider::NAME, build_preset::<SpotifyProvider>),
+    (
+        StackExchangeProvider::NAME,
+        build_preset::<StackExchangeProvider>,
+    ),
+    (
+        SurveyMonkeyProvider::NAME,
+        build_preset::<SurveyMonkeyProvider>,
+    ),
+    (TikTokProvider::NAME, build_preset::<TikTokProvider>),
+    (TodoistProvider::NAME, build_preset::<TodoistProvider>),
+    (TumblrProvider::NAME, build_preset::<TumblrProvider>),
+    (TwitterProvider::NAME, build_preset::<TwitterProvider>),
+    (TypeformProvider::NAME, build_preset::<TypeformProvider>),
+    (UberProvider::NAME, build_preset::<UberProvider>),
+    (UnsplashProvider::NAME, build_preset::<UnsplashProvider>),
+    (VercelProvider::NAME, build_preset::<VercelProvider>),
+    (WebexProvider::NAME, build_preset::<WebexProvider>),
+    (WithingsProvider::NAME, build_preset::<WithingsProvider>),
+    (WordPressProvider::NAME, build_preset::<WordPressProvider>),
+    (WrikeProvider::NAME, build_preset::<WrikeProvider>),
+    (YelpProvider::NAME, build_preset::<YelpProvider>),
+    (ZitadelProvider::NAME, build_preset::<ZitadelProvider>),
+    (ZohoProvider::NAME, build_preset::<ZohoProvider>),
+];
+
+fn build_provider(config: &ProviderConfig) -> Result<RegistryEntry, OauthError> {
+    let client_id = interpolate(&config.client_id)?;
+    let client_secret = interpolate(&config.client_secret)?;
+    let redirect_url = interpolate(&config.redirect_url)?;
+    if config.kind == "custom" {
+        return Ok(CustomProvider::new(
+            required(&config.auth_url)?,
+            required(&config.token_url)?,
+            client_id,
+            client_secret,
+            redirect_url,
+        )
+        .into());
+    }
+    let (_, build) = PRESETS
+        .iter()
+        .find(|(name, _)| *name == config.kind)
+        .ok_or(OauthError::InvalidConfig)?;
+    build(config, client_id, client_secret, redirect_url)
+}
+
+/// Provider selected by the `:provider` path parameter, from the registry in the app state.
+/// The registry is kept in an `Arc`, so only the selected provider is cloned in each request:
+/// ```ignore
+/// #[derive(Clone, FromRef)]
+/// struct AppState {
+///     registry: Arc<ProviderRegistry>,
+///     store: Arc<dyn StateStore>,
+/// }
+///
+/// async fn login(selected: SelectedProvider, State(store): State<Arc<dyn StateStore>>) -> Redirect { ... }
+///
+/// let app = Router::new().route("/login/:provider", get(login)).with_state(app_state);
+/// ```
+#[derive(Clone)]
+pub struct SelectedProvider {
+    pub name: String,
+    pub provider: CustomProvider,
+    pub scopes: Scopes,
+}
+
+#[cfg(feature = "axum")]
+mod extract {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use axum::extract::{FromRef, FromRequestParts, Path};
+    use axum::http::request::Parts;
+    use axum::http::StatusCode;
+
+    use super::{ProviderRegistry, RegistryEntry, SelectedProvider};
+
+    #[async_trait]
+    impl<S> FromRequestParts<S> for SelectedProvider
+    where
+        S: Send + Sync,
+        Arc<ProviderRegistry>: FromRef<S>,
+    {
+        type Rejection = StatusCode;
+
+        async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+            let Path(params) = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+                .await
+                .map_err(|_| StatusCode::NOT_FOUND)?;
+            let name = params.get("provider").ok_or(StatusCode::NOT_FOUND)?;
+            let registry = Arc::<ProviderRegistry>::from_ref(state);
+            let registered = registry.providers.get(name).ok_or(StatusCode::NOT_FOUND)?;
+            // The Pocket providers can't be used in the OAuth2 login helpers
+            let RegistryEntry::OAuth2(provider) = &registered.provider else {
+                return Err(StatusCode::NOT_FOUND);
+            };
+            Ok(SelectedProvider {
+                name: name.clone(),
+                provider: provider.as_ref().clone(),
+                scopes: registered.scopes.clone(),
+            })
+        }
+    }
+}
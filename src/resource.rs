@@ -0,0 +1,504 @@
+//! Resource server
+//!
+//! Validate the Bearer access tokens received by the APIs, so the same crate covers the client and the resource
+//! server. The JWT access tokens are verified with the JWKS of the issuer, and the `iss`, `aud`, `exp` and `nbf`
+//! claims are checked. The opaque tokens are checked in the introspection endpoint, with the results cached.
+//! With the `axum` feature, `BearerClaims` is an extractor that reads the `Authorization` header.
+
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::discovery::ProviderMetadata;
+use crate::error::OauthError;
+use crate::http;
+use crate::jwt::JwksCache;
+use crate::openid::Audience;
+use crate::scopes::Scopes;
+use crate::validation::{now, ValidationOptions};
+
+/// Claims of a valid access token
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BearerClaims {
+    pub iss: Option<String>,
+    pub sub: Option<String>,
+    pub aud: Option<Audience>,
+    pub exp: Option<u64>,
+    pub nbf: Option<u64>,
+    pub client_id: Option<String>,
+    /// Scopes separated by spaces, read them with `scopes`
+    pub scope: Option<String>,
+    /// Claims that are not in the spec
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl BearerClaims {
+    /// Scopes of the token, from the `scope` claim or from the `scp` claim used by some providers
+    pub fn scopes(&self) -> Scopes {
+        match (&self.scope, self.extra.get("scp")) {
+            (Some(scope), _) => Scopes::parse(scope),
+            (None, Some(Value::String(scp))) => Scopes::parse(scp),
+            (None, Some(Value::Array(scp))) => scp.iter().filter_map(Value::as_str).collect(),
+            _ => Scopes::new(),
+        }
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().iter().any(|item| item == scope)
+    }
+}
+
+/// Response of the introspection endpoint (RFC 7662)
+#[derive(Deserialize)]
+struct IntrospectionResponse {
+    active: bool,
+    #[serde(flatten)]
+    claims: BearerClaims,
+}
+
+struct CachedIntrospection {
+    expires_at: Instant,
+    /// None when the token is not active, so the invalid tokens are cached too
+    claims: Option<BearerClaims>,
+}
+
+/// Results of the introspection by the SHA-256 of the token, with at most `capacity` items
+struct IntrospectionCache {
+    items: HashMap<String, CachedIntrospection>,
+    /// Keys in the order they were inserted, the oldest are removed first
+    order: VecDeque<(String, Instant)>,
+    capacity: usize,
+}
+
+impl IntrospectionCache {
+    fn new(capacity: usize) -> Self {
+        IntrospectionCache {
+            items: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn get(&self, key: &str, now: Instant) -> Option<&CachedIntrospection> {
+        self.items.get(key).filter(|cached| cached.expires_at > now)
+    }
+
+    /// Remove the expired items and the keys of the queue that were inserted again
+    fn compact(&mut self, now: Instant) {
+        let items = &mut self.items;
+        self.order.retain(|(key, expires_at)| {
+            let current = items
+                .get(key)
+                .is_some_and(|item| item.expires_at == *expires_at);
+            if current && *expires_at <= now {
+                items.remove(key);
+                return false;
+            }
+            current
+        });
+    }
+
+    /// Insert the result, removing the expired items and the oldest ones when the cache is full
+    fn insert(&mut self, key: String, cached: CachedIntrospection, now: Instant) {
+        // An item that doesn't expire in the front keeps the expired ones behind it, and a key inserted again
+        // leaves its old entry in the queue, so the queue is compacted before it grows over twice the capacity
+        if self.order.len() >= self.capacity.saturating_mul(2) {
+            self.compact(now);
+        }
+        while let Some((oldest, expires_at)) = self.order.front() {
+            if self.items.len() < self.capacity && *expires_at > now {
+                break;
+            }
+            // The key can be in the queue twice, remove it only if it was not inserted again
+            if self
+                .items
+                .get(oldest)
+                .is_some_and(|item| item.expires_at == *expires_at)
+            {
+                self.items.remove(oldest);
+            }
+            self.order.pop_front();
+        }
+        if self.capacity == 0 {
+            return;
+        }
+        self.order.push_back((key.clone(), cached.expires_at));
+        self.items.insert(key, cached);
+    }
+}
+
+/// Client of the introspection endpoint, with the results cached by the SHA-256 of the token
+#[derive(Clone)]
+struct Introspector {
+    endpoint: String,
+    client_id: String,
+    client_secret: String,
+    ttl: Duration,
+    cache: Arc<Mutex<IntrospectionCache>>,
+}
+
+impl Introspector {
+    fn lock(&self) -> MutexGuard<'_, IntrospectionCache> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    async fn introspect(&self, token: &str) -> Result<Option<BearerClaims>, OauthError> {
+        let key = URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()));
+        if let Some(cached) = self.lock().get(&key, Instant::now()) {
+            return Ok(cached.claims.clone());
+        }
+        let request = http::client()?
+            .post(&self.endpoint)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&[("token", token), ("token_type_hint", "access_token")]);
+        let response = http::execute("oauth.introspection", request)
+            .await
+            .map_err(|_| OauthError::IntrospectionFailed)?;
+        if !response.status().is_success() {
+            return Err(OauthError::IntrospectionFailed);
+        }
+        let response = response
+            .json::<IntrospectionResponse>()
+            .await
+            .map_err(|_| OauthError::IntrospectionFailed)?;
+        let claims = response.active.then_some(response.claims);
+        // An active token is not cached after it expires
+        let ttl = claims
+            .as_ref()
+            .and_then(|claims| claims.exp)
+            .map(|exp| Duration::from_secs(exp.saturating_sub(now())).min(self.ttl))
+            .unwrap_or(self.ttl);
+        let checked_at = Instant::now();
+        self.lock().insert(
+            key,
+            CachedIntrospection {
+                expires_at: checked_at + ttl,
+                claims: claims.clone(),
+            },
+            checked_at,
+        );
+        Ok(claims)
+    }
+}
+
+#[derive(Clone)]
+enum Mode {
+    Jwt {
+        jwks: JwksCache,
+        /// Accepted `typ` headers
+        types: Vec<String>,
+    },
+    Introspection(Introspector),
+}
+
+/// Validate the access tokens of an issuer for this API, the JWTs with the JWKS of the issuer
+/// and the opaque tokens with the introspection endpoint
+#[derive(Clone)]
+pub struct BearerValidator {
+    mode: Mode,
+    issuer: Option<String>,
+    audience: String,
+    /// Tolerance in the time claims
+    validation: ValidationOptions,
+}
+
+impl BearerValidator {
+    /// Create the validator of JWT access tokens, their `typ` header needs to be `at+jwt` (RFC 9068),
+    /// so an ID token can't be used as access token
+    /// # Arguments
+    /// * `jwks` - JwksCache - The keys of the issuer
+    /// * `issuer` - &str - The expected `iss` of the tokens
+    /// * `audience` - &str - The identifier of this API, it needs to be in the `aud` of the tokens
+    pub fn new(jwks: JwksCache, issuer: &str, audience: &str) -> Self {
+        BearerValidator {
+            mode: Mode::Jwt {
+                jwks,
+                types: vec!["at+jwt".to_string(), "application/at+jwt".to_string()],
+            },
+            issuer: Some(issuer.to_string()),
+            audience: audience.to_string(),
+            validation: ValidationOptions::default(),
+        }
+    }
+
+    /// Create the validator with the `jwks_uri` of the discovery document of the issuer
+    pub async fn discover(issuer: &str, audience: &str) -> Result<Self, OauthError> {
+        let metadata = ProviderMetadata::discover(issuer).await?;
+        let jwks_uri = metadata.jwks_uri.ok_or(OauthError::DiscoveryFailed)?;
+        Ok(BearerValidator::new(
+            JwksCache::new(&jwks_uri),
+            &metadata.issuer,
+            audience,
+        ))
+    }
+
+    /// Create the validator of opaque access tokens, checked in the introspection endpoint (RFC 7662).
+    /// The results of the last 10000 tokens are cached for 60 seconds, or until the token expires, change it with
+    /// `set_introspection_ttl` and `set_introspection_cache_size`.
+    /// The `iss` and `aud` are checked only when the endpoint returns them
+    /// # Arguments
+    /// * `endpoint` - &str - The introspection endpoint of the provider
+    /// * `client_id` - &str - The client id of this API in the provider
+    /// * `client_secret` - &str - The client secret of this API in the provider
+    /// * `audience` - &str - The identifier of this API
+    pub fn introspection(
+        endpoint: &str,
+        client_id: &str,
+        client_secret: &str,
+        audience: &str,
+    ) -> Self {
+        BearerValidator {
+            mode: Mode::Introspection(Introspector {
+                endpoint: endpoint.to_string(),
+                client_id: client_id.to_string(),
+                client_secret: client_secret.to_string(),
+                ttl: Duration::from_secs(60),
+                cache: Arc::new(Mutex::new(IntrospectionCache::new(10_000))),
+            }),
+            issuer: None,
+            audience: audience.to_string(),
+            validation: ValidationOptions::default(),
+        }
+    }
+
+    /// Set the expected `iss` of the tokens returned by the introspection endpoint
+    pub fn set_issuer(mut self, issuer: &str) -> Self {
+        self.issuer = Some(issuer.to_string());
+        self
+    }
+
+    /// Set how long the introspection results are cached, a revoked token is accepted until its result expires
+    pub fn set_introspection_ttl(mut self, ttl: Duration) -> Self {
+        if let Mode::Introspection(introspector) = &mut self.mode {
+            introspector.ttl = ttl;
+        }
+        self
+    }
+
+    /// Set the accepted `typ` headers of the JWT access tokens, for providers that don't send `at+jwt`.
+    /// Any type is accepted when the list is empty
+    pub fn set_token_types(mut self, token_types: &[&str]) -> Self {
+        if let Mode::Jwt { types, .. } = &mut self.mode {
+            *types = token_types.iter().map(|typ| typ.to_string()).collect();
+        }
+        self
+    }
+
+    /// Set how many introspection results are cached, the oldest are removed when the cache is full.
+    /// Use 0 to disable the cache
+    pub fn set_introspection_cache_size(mut self, size: usize) -> Self {
+        if let Mode::Introspection(introspector) = &mut self.mode {
+            introspector.cache = Arc::new(Mutex::new(IntrospectionCache::new(size)));
+        }
+        self
+    }
+
+    /// Set the clock skew accepted in the `exp` and `nbf`, the default is 60 seconds
+    pub fn set_validation_options(mut self, validation: ValidationOptions) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Validate the token and return its claims
+    pub async fn validate(&self, token: &str) -> Result<BearerClaims, OauthError> {
+        let (claims, required): (BearerClaims, bool) = match &self.mode {
+            Mode::Jwt { jwks, types } => (
+                jwks.verify_typed(token, types)
+                    .await
+                    .map_err(|error| match error {
+                        // The keys can't be fetched, the token is not the problem
+                        OauthError::JwksFetchFailed | OauthError::HttpClientCreationFailed => error,
+                        _ => OauthError::InvalidAccessToken,
+                    })?,
+                true,
+            ),
+            Mode::Introspection(introspector) => (
+                introspector
+                    .introspect(token)
+                    .await?
+                    .ok_or(OauthError::InvalidAccessToken)?,
+                false,
+            ),
+        };
+        // The JWT access tokens without `iss`, `aud` or `exp` are never accepted
+        let expired = match claims.exp {
+            Some(exp) => self.validation.is_expired(exp),
+            None => required,
+        };
+        let wrong_issuer = match (&self.issuer, &claims.iss) {
+            (Some(issuer), Some(iss)) => issuer != iss,
+            (Some(_), None) => required,
+            (None, _) => false,
+        };
+        let wrong_audience = match &claims.aud {
+            Some(aud) => !aud.contains(&self.audience),
+            None => required,
+        };
+        if wrong_issuer
+            || wrong_audience
+            || expired
+            || claims
+                .nbf
+                .is_some_and(|nbf| self.validation.is_in_future(nbf))
+        {
+            return Err(OauthError::InvalidAccessToken);
+        }
+        Ok(claims)
+    }
+}
+
+/// Scope required by the `RequireScope` extractor, create the types with the `scope!` macro
+pub trait ScopeName: Send + Sync + 'static {
+    const SCOPE: &'static str;
+}
+
+/// Create a type that implements `ScopeName`, used as `RequireScope<ReadRepo>`:
+/// ```ignore
+/// scope!(ReadRepo, "repo:read");
+///
+/// async fn list_repos(RequireScope(claims, _): RequireScope<ReadRepo>) -> Json<Vec<Repo>> { ... }
+/// ```
+#[macro_export]
+macro_rules! scope {
+    ($name:ident, $scope:literal) => {
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name;
+
+        impl $crate::resource::ScopeName for $name {
+            const SCOPE: &'static str = $scope;
+        }
+    };
+}
+
+/// Claims of a valid token that has the scope of `T`
+#[derive(Clone, Debug)]
+pub struct RequireScope<T: ScopeName>(pub BearerClaims, pub PhantomData<T>);
+
+#[cfg(feature = "axum")]
+mod extract {
+    use std::marker::PhantomData;
+
+    use async_trait::async_trait;
+    use axum::extract::{FromRef, FromRequestParts, Request, State};
+    use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+    use axum::http::request::Parts;
+    use axum::http::StatusCode;
+    use axum::middleware::Next;
+    use axum::response::{IntoResponse, Response};
+
+    use super::{BearerClaims, BearerValidator, RequireScope, ScopeName};
+    use crate::error::OauthError;
+
+    /// Error of the Bearer extractors, returned with the `WWW-Authenticate` header of RFC 6750
+    #[derive(Clone, Debug)]
+    pub enum BearerRejection {
+        /// The request has no Bearer token
+        MissingToken,
+        /// The token is not valid or is expired
+        InvalidToken,
+        /// The token is valid but it doesn't have the scope
+        InsufficientScope(String),
+        /// The token can't be checked, because the keys or the introspection endpoint are not available
+        Unavailable,
+    }
+
+    impl IntoResponse for BearerRejection {
+        fn into_response(self) -> Response {
+            let (status, challenge) = match self {
+                BearerRejection::MissingToken => (StatusCode::UNAUTHORIZED, String::from("Bearer")),
+                BearerRejection::InvalidToken => (
+                    StatusCode::UNAUTHORIZED,
+                    String::from("Bearer error=\"invalid_token\""),
+                ),
+                BearerRejection::InsufficientScope(scope) => (
+                    StatusCode::FORBIDDEN,
+                    format!("Bearer error=\"insufficient_scope\", scope=\"{scope}\""),
+                ),
+                BearerRejection::Unavailable => {
+                    return StatusCode::SERVICE_UNAVAILABLE.into_response()
+                }
+            };
+            (status, [(WWW_AUTHENTICATE, challenge)]).into_response()
+        }
+    }
+
+    /// Validate the Bearer token of the request with the `BearerValidator` of the app state
+    #[async_trait]
+    impl<S> FromRequestParts<S> for BearerClaims
+    where
+        S: Send + Sync,
+        BearerValidator: FromRef<S>,
+    {
+        type Rejection = BearerRejection;
+
+        async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+            let token = parts
+                .headers
+                .get(AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split_once(' '))
+                .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Bearer"))
+                .map(|(_, token)| token)
+                .ok_or(BearerRejection::MissingToken)?;
+            BearerValidator::from_ref(state)
+                .validate(token.trim())
+                .await
+                .map_err(|error| match error {
+                    OauthError::InvalidAccessToken => BearerRejection::InvalidToken,
+                    _ => BearerRejection::Unavailable,
+                })
+        }
+    }
+
+    /// Validate the token and check the scope, rejecting with `403` and `error="insufficient_scope"` without it
+    #[async_trait]
+    impl<S, T> FromRequestParts<S> for RequireScope<T>
+    where
+        S: Send + Sync,
+        T: ScopeName,
+        BearerValidator: FromRef<S>,
+    {
+        type Rejection = BearerRejection;
+
+        async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+            let claims = BearerClaims::from_request_parts(parts, state).await?;
+            match claims.has_scope(T::SCOPE) {
+                true => Ok(RequireScope(claims, PhantomData)),
+                false => Err(BearerRejection::InsufficientScope(T::SCOPE.to_string())),
+            }
+        }
+    }
+
+    /// Middleware that requires the scope in all the routes of a router, the claims are added in the extensions:
+    /// ```ignore
+    /// let admin = Router::new()
+    ///     .route("/users", get(list_users))
+    ///     .route_layer(middleware::from_fn_with_state((validator, "admin"), scope_guard));
+    /// ```
+    pub async fn scope_guard(
+        State((validator, scope)): State<(BearerValidator, &'static str)>,
+        request: Request,
+        next: Next,
+    ) -> Result<Response, BearerRejection> {
+        let (mut parts, body) = request.into_parts();
+        let claims = BearerClaims::from_request_parts(&mut parts, &validator).await?;
+        if !claims.has_scope(scope) {
+            return Err(BearerRejection::InsufficientScope(scope.to_string()));
+        }
+        parts.extensions.insert(claims);
+        Ok(next.run(Request::from_parts(parts, body)).await)
+    }
+}
+
+#[cfg(feature = "axum")]
+pub use extract::{scope_guard, BearerRejection};
@@ -0,0 +1,126 @@
+use crate::error::OauthError;
+
+/// Character used to join the scopes in the URL, most of the providers use space, but some use comma
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ScopeDelimiter {
+    #[default]
+    Space,
+    Comma,
+    /// Other delimiter required by the provider
+    /// ```
+    /// use oauth_axum::scopes::{ScopeDelimiter, Scopes};
+    ///
+    /// let scopes = Scopes::from(["read", "write"]);
+    /// assert_eq!(scopes.join(ScopeDelimiter::Custom(";")), "read;write");
+    /// ```
+    Custom(&'static str),
+}
+
+impl ScopeDelimiter {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ScopeDelimiter::Space => " ",
+            ScopeDelimiter::Comma => ",",
+            ScopeDelimiter::Custom(delimiter) => delimiter,
+        }
+    }
+}
+
+/// List of scopes sent to the provider, duplicated scopes are ignored
+///
+/// It can be created from a Vec/array of anything that converts to String (including your own scope enums):
+/// ```
+/// use oauth_axum::scopes::Scopes;
+///
+/// let scopes = Scopes::from(["openid", "email", "openid"]).with("profile");
+/// assert_eq!(scopes.len(), 3);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Scopes {
+    scopes: Vec<String>,
+}
+
+impl Scopes {
+    pub fn new() -> Self {
+        Scopes { scopes: Vec::new() }
+    }
+
+    /// Add a scope to the list, if it's not there yet
+    pub fn with(mut self, scope: impl Into<String>) -> Self {
+        self.push(scope.into());
+        self
+    }
+
+    /// Create the list from a string with the scopes separated by spaces or commas, like "openid profile email"
+    pub fn parse(scopes: &str) -> Self {
+        scopes
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|scope| !scope.is_empty())
+            .collect()
+    }
+
+    /// Add a scope to the list, if it's not there yet
+    pub fn push(&mut self, scope: String) {
+        if !self.scopes.contains(&scope) {
+            self.scopes.push(scope);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.scopes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.scopes.iter()
+    }
+
+    /// Check that every scope follows the RFC 6749 format and doesn't have the delimiter inside it.
+    /// A scope like "openid profile email" is rejected, use `Scopes::parse` to split it
+    pub fn validate(&self, delimiter: ScopeDelimiter) -> Result<(), OauthError> {
+        let valid = self.scopes.iter().all(|scope| {
+            !scope.is_empty()
+                && !delimiter.as_str().is_empty()
+                && !scope.contains(delimiter.as_str())
+                && scope.chars().all(|c| {
+                    c == '\x21' || ('\x23'..='\x5b').contains(&c) || ('\x5d'..='\x7e').contains(&c)
+                })
+        });
+        if valid {
+            Ok(())
+        } else {
+            Err(OauthError::InvalidScope)
+        }
+    }
+
+    /// Join the scopes with the delimiter used by the provider
+    pub fn join(&self, delimiter: ScopeDelimiter) -> String {
+        self.scopes.join(delimiter.as_str())
+    }
+}
+
+impl<T: Into<String>> FromIterator<T> for Scopes {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut scopes = Scopes::new();
+        for scope in iter {
+            scopes.push(scope.into());
+        }
+        scopes
+    }
+}
+
+impl<T: Into<String>> From<Vec<T>> for Scopes {
+    fn from(scopes: Vec<T>) -> Self {
+        scopes.into_iter().collect()
+    }
+}
+
+impl<T: Into<String>, const N: usize> From<[T; N]> for Scopes {
+    fn from(scopes: [T; N]) -> Self {
+        scopes.into_iter().collect()
+    }
+}
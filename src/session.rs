@@ -0,0 +1,292 @@
+//! JWT sessions
+//!
+//! API-first backends that don't keep sessions in the server can issue their own JWT after `complete_login`,
+//! with the claims of the OAuth profile, and return it in a cookie or in a JSON response.
+//! The token is signed with `HS256` (shared secret) or `RS256` (private key in PKCS#8).
+//! `SessionIssuer::verify` validates the tokens with the same key, the `HS256` tokens can't be validated
+//! by the `BearerValidator`, that only accepts the asymmetric keys of a JWKS.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::hmac;
+use ring::rand::SystemRandom;
+use ring::signature::{
+    KeyPair, RsaKeyPair, UnparsedPublicKey, RSA_PKCS1_2048_8192_SHA256, RSA_PKCS1_SHA256,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::error::OauthError;
+use crate::login::LoginOutcome;
+use crate::redact::Redacted;
+use crate::validation::{self, ValidationOptions};
+
+/// Key used to sign the session tokens
+#[derive(Clone)]
+pub enum SigningKey {
+    Hs256(hmac::Key),
+    Rs256(Arc<RsaKeyPair>),
+}
+
+impl SigningKey {
+    /// Key with a shared secret, the same secret is used to validate the tokens
+    pub fn hs256(secret: &[u8]) -> Self {
+        SigningKey::Hs256(hmac::Key::new(hmac::HMAC_SHA256, secret))
+    }
+
+    /// Key with a RSA private key in PKCS#8 DER, the tokens can be validated with the public key
+    pub fn rs256_pkcs8(der: &[u8]) -> Result<Self, OauthError> {
+        RsaKeyPair::from_pkcs8(der)
+            .map(|key| SigningKey::Rs256(Arc::new(key)))
+            .map_err(|_| OauthError::InvalidConfig)
+    }
+
+    fn alg(&self) -> &'static str {
+        match self {
+            SigningKey::Hs256(_) => "HS256",
+            SigningKey::Rs256(_) => "RS256",
+        }
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, OauthError> {
+        match self {
+            SigningKey::Hs256(key) => Ok(hmac::sign(key, message).as_ref().to_vec()),
+            SigningKey::Rs256(key) => {
+                let mut signature = vec![0; key.public().modulus_len()];
+                key.sign(
+                    &RSA_PKCS1_SHA256,
+                    &SystemRandom::new(),
+                    message,
+                    &mut signature,
+                )
+                .map_err(|_| OauthError::SessionIssueFailed)?;
+                Ok(signature)
+            }
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), OauthError> {
+        match self {
+            SigningKey::Hs256(key) => hmac::verify(key, message, signature),
+            SigningKey::Rs256(key) => {
+                UnparsedPublicKey::new(&RSA_PKCS1_2048_8192_SHA256, key.public_key().as_ref())
+                    .verify(message, signature)
+            }
+        }
+        .map_err(|_| OauthError::InvalidAccessToken)
+    }
+}
+
+type ClaimsFn = dyn Fn(&str, &LoginOutcome) -> Map<String, Value> + Send + Sync;
+
+/// Session token returned in the JSON response
+#[derive(Clone, Serialize)]
+pub struct SessionToken {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub expires_in: u64,
+}
+
+impl fmt::Debug for SessionToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionToken")
+            .field("access_token", &Redacted(&self.access_token))
+            .field("token_type", &self.token_type)
+            .field("expires_in", &self.expires_in)
+            .finish()
+    }
+}
+
+/// Issue the session JWTs after the login
+#[derive(Clone)]
+pub struct SessionIssuer {
+    key: SigningKey,
+    issuer: String,
+    audience: Option<String>,
+    kid: Option<String>,
+    ttl: Duration,
+    claims: Option<Arc<ClaimsFn>>,
+}
+
+impl SessionIssuer {
+    /// Create the issuer, the tokens are valid for 1 hour
+    /// # Arguments
+    /// * `key` - SigningKey - The key used to sign the tokens
+    /// * `issuer` - &str - The `iss` claim, usually the URL of the app
+    pub fn new(key: SigningKey, issuer: &str) -> Self {
+        SessionIssuer {
+            key,
+            issuer: issuer.to_string(),
+            audience: None,
+            kid: None,
+            ttl: Duration::from_secs(3600),
+            claims: None,
+        }
+    }
+
+    /// Set the `aud` claim
+    pub fn set_audience(mut self, audience: &str) -> Self {
+        self.audience = Some(audience.to_string());
+        self
+    }
+
+    /// Set the `kid` of the header, used by the APIs to find the key when it is rotated
+    pub fn set_kid(mut self, kid: &str) -> Self {
+        self.kid = Some(kid.to_string());
+        self
+    }
+
+    /// Set how long the tokens are valid
+    pub fn set_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Add claims from the login, like the roles of the user. The function receives the name of the provider and the
+    /// outcome of the login, and the claims returned replace the `provider`, `email` and `name`.
+    /// The reserved `iss`, `sub`, `iat`, `exp` and `aud` are always the ones of the issuer
+    pub fn set_claims<F>(mut self, claims: F) -> Self
+    where
+        F: Fn(&str, &LoginOutcome) -> Map<String, Value> + Send + Sync + 'static,
+    {
+        self.claims = Some(Arc::new(claims));
+        self
+    }
+
+    /// Issue the token for the user of the login.
+    /// The `sub` is the `user_id` of the `UserUpserter`, or `{provider}:{id}` with the id of the user in the provider
+    /// with `fetch_user`, so the same id in two providers is not the same user.
+    /// The `provider`, `email` and `name` of the profile are added to the claims
+    /// # Arguments
+    /// * `provider` - &str - The name of the provider of the login
+    /// * `outcome` - &LoginOutcome - The outcome returned by `complete_login`
+    pub fn issue(&self, provider: &str, outcome: &LoginOutcome) -> Result<String, OauthError> {
+        let sub = outcome
+            .user_id
+            .clone()
+            .or_else(|| {
+                outcome
+                    .user
+                    .as_ref()
+                    .map(|user| format!("{provider}:{}", user.id))
+            })
+            .ok_or(OauthError::SessionIssueFailed)?;
+        let mut claims = Map::new();
+        claims.insert("provider".into(), json!(provider));
+        if let Some(user) = &outcome.user {
+            if let Some(email) = &user.email {
+                claims.insert("email".into(), json!(email));
+            }
+            if let Some(name) = &user.name {
+                claims.insert("name".into(), json!(name));
+            }
+        }
+        if let Some(extra) = &self.claims {
+            claims.extend(extra(provider, outcome));
+        }
+        // The reserved claims are added after the custom ones, so they can't be replaced
+        let iat = validation::now();
+        claims.insert("iss".into(), json!(self.issuer));
+        claims.insert("sub".into(), json!(sub));
+        claims.insert("iat".into(), json!(iat));
+        claims.insert("exp".into(), json!(iat + self.ttl.as_secs()));
+        match &self.audience {
+            Some(audience) => claims.insert("aud".into(), json!(audience)),
+            None => claims.remove("aud"),
+        };
+        self.sign(&Value::Object(claims))
+    }
+
+    /// Issue the token in the format of a JSON response
+    pub fn issue_json(
+        &self,
+        provider: &str,
+        outcome: &LoginOutcome,
+    ) -> Result<SessionToken, OauthError> {
+        Ok(SessionToken {
+            access_token: self.issue(provider, outcome)?,
+            token_type: "Bearer",
+            expires_in: self.ttl.as_secs(),
+        })
+    }
+
+    /// Issue the token in a `HttpOnly` cookie, with the same max age of the token
+    #[cfg(feature = "cookie")]
+    pub fn issue_cookie(
+        &self,
+        name: &'static str,
+        provider: &str,
+        outcome: &LoginOutcome,
+    ) -> Result<axum_extra::extract::cookie::Cookie<'static>, OauthError> {
+        use axum_extra::extract::cookie::{Cookie, SameSite};
+
+        Ok(Cookie::build((name, self.issue(provider, outcome)?))
+            .path("/")
+            .http_only(true)
+            .secure(true)
+            .same_site(SameSite::Lax)
+            .max_age(self.ttl.try_into().unwrap_or_default())
+            .build())
+    }
+
+    /// Validate a token issued with the same key: the signature, `iss`, `aud` and `exp`
+    /// # Arguments
+    /// * `token` - &str - The token sent by the client
+    /// * `validation` - &ValidationOptions - The clock skew accepted in the `exp`
+    /// # Return
+    /// The claims of the token, `InvalidAccessToken` when it is not valid
+    pub fn verify(
+        &self,
+        token: &str,
+        validation: &ValidationOptions,
+    ) -> Result<Map<String, Value>, OauthError> {
+        let (message, signature) = token
+            .rsplit_once('.')
+            .ok_or(OauthError::InvalidAccessToken)?;
+        let (header, payload) = message
+            .split_once('.')
+            .ok_or(OauthError::InvalidAccessToken)?;
+        let decode = |part: &str| {
+            URL_SAFE_NO_PAD
+                .decode(part)
+                .map_err(|_| OauthError::InvalidAccessToken)
+        };
+        let header: Value =
+            serde_json::from_slice(&decode(header)?).map_err(|_| OauthError::InvalidAccessToken)?;
+        if header["alg"] != self.key.alg() {
+            return Err(OauthError::InvalidAccessToken);
+        }
+        self.key.verify(message.as_bytes(), &decode(signature)?)?;
+        let claims: Map<String, Value> = serde_json::from_slice(&decode(payload)?)
+            .map_err(|_| OauthError::InvalidAccessToken)?;
+        let expired = match claims.get("exp").and_then(Value::as_u64) {
+            Some(exp) => validation.is_expired(exp),
+            None => true,
+        };
+        if expired
+            || claims.get("iss").and_then(Value::as_str) != Some(self.issuer.as_str())
+            || claims.get("aud").and_then(Value::as_str) != self.audience.as_deref()
+        {
+            return Err(OauthError::InvalidAccessToken);
+        }
+        Ok(claims)
+    }
+
+    fn sign(&self, claims: &Value) -> Result<String, OauthError> {
+        let mut header = json!({ "alg": self.key.alg(), "typ": "JWT" });
+        if let Some(kid) = &self.kid {
+            header["kid"] = json!(kid);
+        }
+        let message = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let signature = self.key.sign(message.as_bytes())?;
+        Ok(format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature)))
+    }
+}
@@ -0,0 +1,365 @@
+//! State stores
+//!
+//! A store keeps the state and verifier generated in the first step until the callback.
+//! `MemoryStateStore` is enough for apps with one instance, for more instances implement `StateStore` with your DB.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::OauthError;
+use crate::task::BackgroundTask;
+use crate::validation::ValidationOptions;
+use crate::StateAuth;
+
+/// Authorization attempt saved in the store
+#[derive(Clone, Debug)]
+pub struct StoredState {
+    /// Name of the provider that generated the state
+    pub provider: String,
+    pub state: StateAuth,
+    pub created_at: SystemTime,
+}
+
+impl StoredState {
+    pub fn new(provider: &str, state: StateAuth) -> Self {
+        StoredState {
+            provider: provider.to_string(),
+            state,
+            created_at: SystemTime::now(),
+        }
+    }
+
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.created_at)
+            .unwrap_or_default()
+    }
+}
+
+/// Pending authorization attempt, without the verifier and the state, so it is safe to show it to the operators
+#[derive(Clone, Serialize)]
+pub struct PendingState {
+    /// Hash of the state, enough to find the attempt in the logs without allowing to use it
+    pub state_hash: String,
+    pub provider: String,
+    pub age_secs: u64,
+    /// Key in the store, used by `Namespaced` to filter the attempts
+    #[serde(skip)]
+    key: String,
+}
+
+/// First 12 characters of the SHA-256 of the state
+pub fn state_hash(state: &str) -> String {
+    let mut hash = URL_SAFE_NO_PAD.encode(Sha256::digest(state.as_bytes()));
+    hash.truncate(12);
+    hash
+}
+
+impl From<&StoredState> for PendingState {
+    fn from(stored: &StoredState) -> Self {
+        PendingState {
+            state_hash: state_hash(&stored.state.state),
+            provider: stored.provider.clone(),
+            age_secs: stored.age().as_secs(),
+            key: stored.state.state.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for PendingState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingState")
+            .field("state_hash", &self.state_hash)
+            .field("provider", &self.provider)
+            .field("age_secs", &self.age_secs)
+            .finish()
+    }
+}
+
+/// Numbers of the pending authorization attempts
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StoreStats {
+    pub count: usize,
+    pub oldest_age_secs: Option<u64>,
+    pub per_provider: HashMap<String, usize>,
+}
+
+impl StoreStats {
+    /// Create the stats from the pending attempts, useful to implement `StateStore::stats`
+    pub fn from_pending(pending: &[PendingState]) -> Self {
+        let mut per_provider = HashMap::new();
+        for item in pending {
+            *per_provider.entry(item.provider.clone()).or_insert(0) += 1;
+        }
+        StoreStats {
+            count: pending.len(),
+            oldest_age_secs: pending.iter().map(|item| item.age_secs).max(),
+            per_provider,
+        }
+    }
+}
+
+/// Place where the state and verifier are saved between the two steps
+#[async_trait]
+pub trait StateStore: Send + Sync {
+    /// Save the state generated in the `generate_url`
+    async fn save(&self, provider: &str, state: StateAuth) -> Result<(), OauthError>;
+
+    /// Get and remove the state, so it can't be used twice
+    async fn take(&self, state: &str) -> Result<Option<StoredState>, OauthError>;
+
+    /// List the pending attempts, the verifiers are never returned
+    async fn list_pending(&self) -> Result<Vec<PendingState>, OauthError>;
+
+    /// Save many states at once, used by the `UrlPool`. Override it to save them in one write to the DB
+    async fn save_many(&self, provider: &str, states: Vec<StateAuth>) -> Result<(), OauthError> {
+        for state in states {
+            self.save(provider, state).await?;
+        }
+        Ok(())
+    }
+
+    /// Remove the states that will not be used, like the unused URLs of the `UrlPool`
+    async fn remove_many(&self, states: &[String]) -> Result<(), OauthError> {
+        for state in states {
+            self.take(state).await?;
+        }
+        Ok(())
+    }
+
+    /// Count of the pending attempts, the age of the oldest one and the count per provider
+    async fn stats(&self) -> Result<StoreStats, OauthError> {
+        Ok(StoreStats::from_pending(&self.list_pending().await?))
+    }
+
+    /// Same of `take`, but fails with `StateMismatch` when the state was saved by other provider
+    async fn take_for(
+        &self,
+        provider: &str,
+        state: &str,
+    ) -> Result<Option<StoredState>, OauthError> {
+        match self.take(state).await? {
+            Some(item) if item.provider != provider => Err(OauthError::StateMismatch),
+            item => Ok(item),
+        }
+    }
+}
+
+/// Store that adds a prefix in the keys of other store, so many apps can share the same DB without collisions
+#[derive(Clone)]
+pub struct Namespaced<S> {
+    inner: S,
+    prefix: String,
+}
+
+impl<S: StateStore> Namespaced<S> {
+    /// Wrap the store, the keys are saved as `<namespace>:<state>`
+    /// # Return
+    /// `InvalidConfig` when the namespace is empty or has a `:`, so a namespace is never the prefix of other
+    pub fn new(namespace: &str, inner: S) -> Result<Self, OauthError> {
+        if namespace.is_empty() || namespace.contains(':') {
+            return Err(OauthError::InvalidConfig);
+        }
+        Ok(Namespaced {
+            inner,
+            prefix: format!("{}:", namespace),
+        })
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<S: StateStore> StateStore for Namespaced<S> {
+    async fn save(&self, provider: &str, mut state: StateAuth) -> Result<(), OauthError> {
+        state.state = format!("{}{}", self.prefix, state.state);
+        self.inner.save(provider, state).await
+    }
+
+    async fn save_many(&self, provider: &str, states: Vec<StateAuth>) -> Result<(), OauthError> {
+        let states = states
+            .into_iter()
+            .map(|mut state| {
+                state.state = format!("{}{}", self.prefix, state.state);
+                state
+            })
+            .collect();
+        self.inner.save_many(provider, states).await
+    }
+
+    async fn remove_many(&self, states: &[String]) -> Result<(), OauthError> {
+        let states: Vec<String> = states
+            .iter()
+            .map(|state| format!("{}{}", self.prefix, state))
+            .collect();
+        self.inner.remove_many(&states).await
+    }
+
+    async fn take(&self, state: &str) -> Result<Option<StoredState>, OauthError> {
+        let item = self
+            .inner
+            .take(&format!("{}{}", self.prefix, state))
+            .await?;
+        Ok(item.map(|mut item| {
+            item.state.state = state.to_string();
+            item
+        }))
+    }
+
+    async fn list_pending(&self) -> Result<Vec<PendingState>, OauthError> {
+        Ok(self
+            .inner
+            .list_pending()
+            .await?
+            .into_iter()
+            .filter_map(|mut item| {
+                item.key = item.key.strip_prefix(&self.prefix)?.to_string();
+                item.state_hash = state_hash(&item.key);
+                Some(item)
+            })
+            .collect())
+    }
+}
+
+/// Store that keeps the states in memory, only works when the app runs in one instance
+#[derive(Clone)]
+pub struct MemoryStateStore {
+    items: Arc<Mutex<HashMap<String, StoredState>>>,
+    ttl: Duration,
+    validation: ValidationOptions,
+}
+
+impl MemoryStateStore {
+    /// Create the store, the states older than the ttl are ignored in the `take`
+    pub fn new(ttl: Duration) -> Self {
+        MemoryStateStore {
+            items: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+            validation: ValidationOptions::default(),
+        }
+    }
+
+    /// Set the clock skew accepted after the ttl
+    pub fn set_validation_options(mut self, validation: ValidationOptions) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, StoredState>>, OauthError> {
+        self.items.lock().map_err(|_| OauthError::StateStoreFailed)
+    }
+
+    /// Remove the expired states
+    pub fn sweep(&self) -> Result<(), OauthError> {
+        let ttl = self.ttl;
+        let validation = self.validation;
+        self.lock()?
+            .retain(|_, item| !validation.is_stale(item.age(), ttl));
+        Ok(())
+    }
+
+    /// Spawn a task that removes the expired states in every interval, it stops when the handle is dropped or shutdown
+    pub fn spawn_sweeper(&self, interval: Duration) -> BackgroundTask {
+        let store = self.clone();
+        BackgroundTask::spawn_interval(interval, move || {
+            let _ = store.sweep();
+        })
+    }
+}
+
+impl Default for MemoryStateStore {
+    /// Store with a ttl of 15 minutes
+    fn default() -> Self {
+        MemoryStateStore::new(Duration::from_secs(900))
+    }
+}
+
+#[async_trait]
+impl StateStore for MemoryStateStore {
+    async fn save(&self, provider: &str, state: StateAuth) -> Result<(), OauthError> {
+        self.lock()?
+            .insert(state.state.clone(), StoredState::new(provider, state));
+        Ok(())
+    }
+
+    async fn save_many(&self, provider: &str, states: Vec<StateAuth>) -> Result<(), OauthError> {
+        let mut items = self.lock()?;
+        for state in states {
+            items.insert(state.state.clone(), StoredState::new(provider, state));
+        }
+        Ok(())
+    }
+
+    async fn remove_many(&self, states: &[String]) -> Result<(), OauthError> {
+        let mut items = self.lock()?;
+        for state in states {
+            items.remove(state);
+        }
+        Ok(())
+    }
+
+    async fn take(&self, state: &str) -> Result<Option<StoredState>, OauthError> {
+        Ok(self
+            .lock()?
+            .remove(state)
+            .filter(|item| !self.validation.is_stale(item.age(), self.ttl)))
+    }
+
+    async fn list_pending(&self) -> Result<Vec<PendingState>, OauthError> {
+        Ok(self.lock()?.values().map(PendingState::from).collect())
+    }
+}
+
+/// Router with the debug routes of the store: `GET /stats` and `GET /pending`.
+/// It shows internal data, so protect it before nesting in your app
+#[cfg(feature = "axum")]
+pub fn debug_router(store: Arc<dyn StateStore>) -> axum::Router {
+    debug_routes().with_state(store)
+}
+
+/// Same routes of `debug_router`, reading the store from the app state with `FromRef`
+#[cfg(feature = "axum")]
+pub fn debug_routes<S>() -> axum::Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    Arc<dyn StateStore>: axum::extract::FromRef<S>,
+{
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::Json;
+
+    async fn stats(
+        State(store): State<Arc<dyn StateStore>>,
+    ) -> Result<Json<StoreStats>, StatusCode> {
+        store
+            .stats()
+            .await
+            .map(Json)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    async fn pending(
+        State(store): State<Arc<dyn StateStore>>,
+    ) -> Result<Json<Vec<PendingState>>, StatusCode> {
+        store
+            .list_pending()
+            .await
+            .map(Json)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    axum::Router::new()
+        .route("/stats", get(stats))
+        .route("/pending", get(pending))
+}
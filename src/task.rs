@@ -0,0 +1,120 @@
+//! Background tasks
+//!
+//! Every task spawned by the lib returns a `BackgroundTask`, that stops the task gracefully with `shutdown`
+//! or right away with `abort`. Dropping the handle aborts the task, so tests don't leak tasks.
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Shortest interval of the tasks, a zero interval would run the task in a busy loop
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Signal received by the task when the shutdown is requested
+#[derive(Clone)]
+pub struct ShutdownSignal(watch::Receiver<bool>);
+
+impl ShutdownSignal {
+    /// Wait until the shutdown is requested
+    pub async fn wait(&mut self) {
+        while !*self.0.borrow() {
+            if self.0.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Handle of a task running in background
+pub struct BackgroundTask {
+    sender: watch::Sender<bool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BackgroundTask {
+    /// Spawn the task in the tokio runtime, the task needs to finish when the signal is received
+    pub fn spawn<F, Fut>(task: F) -> Self
+    where
+        F: FnOnce(ShutdownSignal) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let (sender, receiver) = watch::channel(false);
+        BackgroundTask {
+            sender,
+            handle: Some(tokio::spawn(task(ShutdownSignal(receiver)))),
+        }
+    }
+
+    /// Spawn a task that runs the function in every interval until the shutdown.
+    /// The interval is at least 1 second
+    pub fn spawn_interval<F>(interval: Duration, mut run: F) -> Self
+    where
+        F: FnMut() + Send + 'static,
+    {
+        BackgroundTask::spawn_interval_async(interval, move || {
+            run();
+            async {}
+        })
+    }
+
+    /// Same as `spawn_interval`, but the function returns a future that is awaited before the next interval
+    pub fn spawn_interval_async<F, Fut>(interval: Duration, mut run: F) -> Self
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        BackgroundTask::spawn(|mut signal| async move {
+            let mut interval = tokio::time::interval(interval.max(MIN_INTERVAL));
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => run().await,
+                    _ = signal.wait() => break,
+                }
+            }
+        })
+    }
+
+    /// Request the shutdown and wait until the task finishes
+    pub async fn shutdown(mut self) {
+        let _ = self.sender.send(true);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+    }
+
+    /// Wait for the signal and shutdown the task, it can be used in `axum::serve(..).with_graceful_shutdown(..)`:
+    /// ```ignore
+    /// axum::serve(listener, app)
+    ///     .with_graceful_shutdown(sweeper.shutdown_on(async { tokio::signal::ctrl_c().await.unwrap() }))
+    ///     .await
+    /// ```
+    pub async fn shutdown_on<F>(self, signal: F)
+    where
+        F: Future<Output = ()>,
+    {
+        signal.await;
+        self.shutdown().await;
+    }
+
+    /// Stop the task right away
+    pub fn abort(&self) {
+        if let Some(handle) = &self.handle {
+            handle.abort();
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle
+            .as_ref()
+            .map(|handle| handle.is_finished())
+            .unwrap_or(true)
+    }
+}
+
+impl Drop for BackgroundTask {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
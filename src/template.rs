@@ -0,0 +1,119 @@
+//! URL templates
+//!
+//! Some providers have the tenant, shop, region or realm in the endpoints. A `ProviderTemplate` has the URLs with
+//! placeholders like `{tenant}`, that are filled when the provider is built, so the call sites don't need to
+//! concatenate the URLs.
+
+use crate::error::OauthError;
+use crate::CustomProvider;
+
+/// Function that applies the configuration of the preset (auth type, quirks, user info) in the built provider
+pub type ConfigureFn = fn(CustomProvider) -> CustomProvider;
+
+/// Authorization and token URLs with placeholders
+#[derive(Clone, Debug)]
+pub struct ProviderTemplate {
+    auth_url: String,
+    token_url: String,
+    values: Vec<(String, String)>,
+    configure: Option<ConfigureFn>,
+}
+
+macro_rules! placeholder_setters {
+    ($($name:ident),*) => {
+        $(
+            #[doc = concat!("Set the value of the `{", stringify!($name), "}` placeholder")]
+            pub fn $name(self, value: &str) -> Self {
+                self.set(stringify!($name), value)
+            }
+        )*
+    };
+}
+
+impl ProviderTemplate {
+    /// Create the template
+    /// # Arguments
+    /// * `auth_url` - &str - The authorization URL, like `https://{shop}.example.com/authorize`
+    /// * `token_url` - &str - The token URL
+    pub fn new(auth_url: &str, token_url: &str) -> Self {
+        ProviderTemplate {
+            auth_url: auth_url.to_string(),
+            token_url: token_url.to_string(),
+            values: Vec::new(),
+            configure: None,
+        }
+    }
+
+    /// Set the function that configures the provider after it is built
+    pub fn set_configure(mut self, configure: ConfigureFn) -> Self {
+        self.configure = Some(configure);
+        self
+    }
+
+    /// Set the value of a placeholder, the value of the same placeholder is replaced
+    pub fn set(mut self, name: &str, value: &str) -> Self {
+        self.values.retain(|(key, _)| key != name);
+        self.values.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    placeholder_setters!(tenant, shop, region, realm);
+
+    /// Replace the placeholders of the URL.
+    /// Fails with `InvalidConfig` when a placeholder has no value, the value has characters other than
+    /// letters, digits, `.`, `_` and `-`, that could change the host, port or path of the URL,
+    /// or it has only dots, like `..`, that would move up in the path
+    pub fn fill(&self, url: &str) -> Result<String, OauthError> {
+        let mut url = url.to_string();
+        for (name, value) in &self.values {
+            if value.chars().all(|c| c == '.')
+                || !value
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+            {
+                return Err(OauthError::InvalidConfig);
+            }
+            url = url.replace(&format!("{{{name}}}"), value);
+        }
+        match url.contains('{') {
+            true => Err(OauthError::InvalidConfig),
+            false => Ok(url),
+        }
+    }
+
+    /// Replace one placeholder of a URL that is not an endpoint of the template, like the API URL of the account,
+    /// with the same checks of `fill`
+    /// # Arguments
+    /// * `url` - &str - The URL, like `https://{host}/api/v4`
+    /// * `name` - &str - The name of the placeholder, like `host`
+    /// * `value` - &str - The value of the placeholder
+    pub fn fill_value(url: &str, name: &str, value: &str) -> Result<String, OauthError> {
+        ProviderTemplate::new(url, url).set(name, value).fill(url)
+    }
+
+    /// Create the provider with the filled URLs
+    /// # Arguments
+    /// * `client_id` - String - The client id
+    /// * `client_secret` - String - The client secret
+    /// * `redirect_url` - String - The redirect url
+    /// # Return
+    /// The CustomProvider, or `InvalidConfig` when a placeholder is not filled
+    pub fn build(
+        &self,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Result<CustomProvider, OauthError> {
+        let provider = CustomProvider::new(
+            self.fill(&self.auth_url)?,
+            self.fill(&self.token_url)?,
+            client_id,
+            client_secret,
+            redirect_url,
+        );
+        Ok(match self.configure {
+            Some(configure) => configure(provider),
+            None => provider,
+        })
+    }
+}
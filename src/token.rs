@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use oauth2::basic::{
+    BasicErrorResponse, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse,
+    BasicTokenType,
+};
+use oauth2::{ExtraTokenFields, StandardRevocableToken, StandardTokenResponse, TokenResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::redact::{redacted, Redacted};
+
+/// Fields of the token response that are not in the spec
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ExtraFields {
+    #[serde(flatten)]
+    pub fields: HashMap<String, Value>,
+}
+
+/// Only the names of the fields are shown, the values can be tokens
+impl fmt::Debug for ExtraFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtraFields")
+            .field("fields", &self.fields.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ExtraTokenFields for ExtraFields {}
+
+pub type ProviderTokenResponse = StandardTokenResponse<ExtraFields, BasicTokenType>;
+
+/// oauth2 client used by the providers, it keeps the extra fields of the token response
+pub type ProviderClient = oauth2::Client<
+    BasicErrorResponse,
+    ProviderTokenResponse,
+    BasicTokenType,
+    BasicTokenIntrospectionResponse,
+    StandardRevocableToken,
+    BasicRevocationErrorResponse,
+>;
+
+/// Token returned by the provider
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TokenSet {
+    pub access_token: String,
+    pub token_type: String,
+    pub refresh_token: Option<String>,
+    /// Seconds until the access token expires
+    pub expires_in: Option<u64>,
+    pub scopes: Option<Vec<String>>,
+    /// Fields that are not in the spec, like the `id_token` or provider specific fields
+    pub extra: HashMap<String, Value>,
+}
+
+/// The tokens are masked, and only the names of the extra fields are shown because they can have tokens too, like the `id_token`
+impl fmt::Debug for TokenSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenSet")
+            .field("access_token", &Redacted(&self.access_token))
+            .field("token_type", &self.token_type)
+            .field("refresh_token", &redacted(&self.refresh_token))
+            .field("expires_in", &self.expires_in)
+            .field("scopes", &self.scopes)
+            .field("extra", &self.extra.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl TokenSet {
+    /// Get a field that is not in the spec as string
+    pub fn extra_str(&self, name: &str) -> Option<&str> {
+        self.extra.get(name).and_then(Value::as_str)
+    }
+
+    /// Raw ID token returned in the OpenID flow, keep it to use as `id_token_hint` in the logout
+    pub fn id_token(&self) -> Option<&str> {
+        self.extra_str("id_token")
+    }
+}
+
+impl From<ProviderTokenResponse> for TokenSet {
+    fn from(response: ProviderTokenResponse) -> Self {
+        TokenSet {
+            access_token: response.access_token().secret().to_string(),
+            token_type: response.token_type().as_ref().to_string(),
+            refresh_token: response
+                .refresh_token()
+                .map(|refresh_token| refresh_token.secret().to_string()),
+            expires_in: response.expires_in().map(|expires_in| expires_in.as_secs()),
+            scopes: response
+                .scopes()
+                .map(|scopes| scopes.iter().map(|scope| scope.to_string()).collect()),
+            extra: response.extra_fields().fields.clone(),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! User profile
+//!
+//! The presets know the user endpoint of the provider and how to read the id, email, name and avatar from it,
+//! so `get_user` returns the same [`OAuthUser`] for every provider.
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::OauthError;
+use crate::{http, CustomProvider};
+
+/// Profile of the user, with the same fields for every provider
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OAuthUser {
+    pub id: String,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub avatar_url: Option<String>,
+    /// Response of the user endpoint, for the fields that are not normalized
+    pub raw: Value,
+}
+
+type UserMapperFn = dyn Fn(&Value) -> Option<OAuthUser> + Send + Sync;
+
+#[derive(Clone)]
+enum UserMapping {
+    /// JSON pointers of the id, email, name and avatar
+    Pointers {
+        id: String,
+        email: Option<String>,
+        name: Option<String>,
+        avatar_url: Option<String>,
+    },
+    Custom(Arc<UserMapperFn>),
+}
+
+/// User endpoint of the provider and how to read the profile from its response
+#[derive(Clone)]
+pub struct UserInfo {
+    url: String,
+    mapping: UserMapping,
+}
+
+impl fmt::Debug for UserInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserInfo").field("url", &self.url).finish()
+    }
+}
+
+/// Read the value of the pointer as string, numeric ids are converted
+fn pointer_str(raw: &Value, pointer: &str) -> Option<String> {
+    match raw.pointer(pointer)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+impl UserInfo {
+    /// Endpoint with the OpenID claims: `sub`, `email`, `name` and `picture`
+    pub fn new(url: &str) -> Self {
+        UserInfo {
+            url: url.to_string(),
+            mapping: UserMapping::Pointers {
+                id: String::from("/sub"),
+                email: Some(String::from("/email")),
+                name: Some(String::from("/name")),
+                avatar_url: Some(String::from("/picture")),
+            },
+        }
+    }
+
+    /// Endpoint with other fields, each field is a JSON pointer, like `/data/id`, None when the endpoint doesn't return it
+    pub fn with_pointers(
+        url: &str,
+        id: &str,
+        email: Option<&str>,
+        name: Option<&str>,
+        avatar_url: Option<&str>,
+    ) -> Self {
+        UserInfo {
+            url: url.to_string(),
+            mapping: UserMapping::Pointers {
+                id: id.to_string(),
+                email: email.map(String::from),
+                name: name.map(String::from),
+                avatar_url: avatar_url.map(String::from),
+            },
+        }
+    }
+
+    /// Endpoint that needs a function to create the profile, the function returns None when the id is missing
+    pub fn custom<F>(url: &str, mapper: F) -> Self
+    where
+        F: Fn(&Value) -> Option<OAuthUser> + Send + Sync + 'static,
+    {
+        UserInfo {
+            url: url.to_string(),
+            mapping: UserMapping::Custom(Arc::new(mapper)),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Create the profile from the response of the endpoint
+    pub fn map(&self, raw: Value) -> Result<OAuthUser, OauthError> {
+        match &self.mapping {
+            UserMapping::Pointers {
+                id,
+                email,
+                name,
+                avatar_url,
+            } => Ok(OAuthUser {
+                id: pointer_str(&raw, id).ok_or(OauthError::UserInfoFailed)?,
+                email: email.as_ref().and_then(|email| pointer_str(&raw, email)),
+                name: name.as_ref().and_then(|name| pointer_str(&raw, name)),
+                avatar_url: avatar_url
+                    .as_ref()
+                    .and_then(|avatar_url| pointer_str(&raw, avatar_url)),
+                raw,
+            }),
+            UserMapping::Custom(mapper) => mapper(&raw).ok_or(OauthError::UserInfoFailed),
+        }
+    }
+
+    /// Call the endpoint with the access token
+    pub(crate) async fn fetch(&self, access_token: &str) -> Result<Value, OauthError> {
+        let response = http::execute(
+            "oauth.userinfo",
+            http::client()?.get(&self.url).bearer_auth(access_token),
+        )
+        .await
+        .map_err(|_| OauthError::UserInfoFailed)?;
+        if !response.status().is_success() {
+            return Err(OauthError::UserInfoFailed);
+        }
+        response
+            .json::<Value>()
+            .await
+            .map_err(|_| OauthError::UserInfoFailed)
+    }
+}
+
+/// Typed response of the user endpoint, like `GithubUser`
+pub trait UserModel: DeserializeOwned {
+    /// Create the model from the response, override it when the user is inside an envelope
+    fn from_raw(raw: Value) -> Result<Self, OauthError> {
+        serde_json::from_value(raw).map_err(|_| OauthError::UserInfoFailed)
+    }
+}
+
+impl CustomProvider {
+    /// Get the profile of the user from the user endpoint of the provider
+    /// # Arguments
+    /// * `access_token` - &str - The access token generated in the callback
+    /// # Return
+    /// OAuthUser - The id, email, name and avatar of the user, and the raw response
+    pub async fn get_user(&self, access_token: &str) -> Result<OAuthUser, OauthError> {
+        let user_info = self
+            .user_info
+            .as_ref()
+            .ok_or(OauthError::UserInfoNotSupported)?;
+        user_info.map(user_info.fetch(access_token).await?)
+    }
+
+    /// Same as `get_user`, but returning the typed model of the provider, with all its fields
+    /// ```ignore
+    /// let user: GithubUser = provider.get_user_typed(&token).await?;
+    /// println!("{}", user.login);
+    /// ```
+    pub async fn get_user_typed<T: UserModel>(&self, access_token: &str) -> Result<T, OauthError> {
+        let user_info = self
+            .user_info
+            .as_ref()
+            .ok_or(OauthError::UserInfoNotSupported)?;
+        T::from_raw(user_info.fetch(access_token).await?)
+    }
+}
@@ -0,0 +1,54 @@
+//! Time validations
+//!
+//! The clocks of the app and of the provider are never exactly the same, so all the time validations (state TTL,
+//! `exp`, `nbf` and `iat` of the tokens, `auth_time`) accept a tolerance. `ValidationOptions` has this tolerance,
+//! set the same options in the stores, verifiers and validators to apply it everywhere.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Options of the time validations
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationOptions {
+    /// Tolerance between the clocks, the default is 60 seconds
+    pub clock_skew: Duration,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        ValidationOptions::new(Duration::from_secs(60))
+    }
+}
+
+impl ValidationOptions {
+    pub fn new(clock_skew: Duration) -> Self {
+        ValidationOptions { clock_skew }
+    }
+
+    /// The time claim (`exp`) is in the past
+    pub fn is_expired(&self, exp: u64) -> bool {
+        exp.saturating_add(self.clock_skew.as_secs()) < now()
+    }
+
+    /// The time claim (`nbf` or `iat`) is in the future
+    pub fn is_in_future(&self, time: u64) -> bool {
+        time > now().saturating_add(self.clock_skew.as_secs())
+    }
+
+    /// The time claim (`auth_time`) is older than the `max_age` seconds
+    pub fn is_older_than(&self, time: u64, max_age: u64) -> bool {
+        now().saturating_sub(time) > max_age.saturating_add(self.clock_skew.as_secs())
+    }
+
+    /// Something with the `age` (like a saved state) passed its `ttl`
+    pub fn is_stale(&self, age: Duration, ttl: Duration) -> bool {
+        age >= ttl.saturating_add(self.clock_skew)
+    }
+}
+
+/// Current unix time in seconds
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,32 @@
+use oauth_axum::callback::CallbackParams;
+
+#[test]
+fn debug_masks_the_code_and_id_token() {
+    let params = CallbackParams {
+        code: Some("code-of-the-callback-1234".to_string()),
+        state: Some("state-1".to_string()),
+        id_token: Some("header.payload.signature-abcd".to_string()),
+        error: None,
+        error_description: None,
+    };
+    let debug = format!("{params:?}");
+    assert!(!debug.contains("code-of-the-callback"));
+    assert!(!debug.contains("header.payload"));
+    assert!(debug.contains("\"****1234\""));
+    assert!(debug.contains("\"****abcd\""));
+    assert!(debug.contains("state-1"));
+}
+
+#[test]
+fn debug_shows_the_errors() {
+    let params = CallbackParams {
+        state: Some("state-1".to_string()),
+        error: Some("access_denied".to_string()),
+        error_description: Some("The user denied the access".to_string()),
+        ..CallbackParams::default()
+    };
+    let debug = format!("{params:?}");
+    assert!(debug.contains("code: None"));
+    assert!(debug.contains("access_denied"));
+    assert!(debug.contains("The user denied the access"));
+}
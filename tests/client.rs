@@ -0,0 +1,53 @@
+use oauth_axum::error::OauthError;
+use oauth_axum::redirect::RedirectPolicy;
+use oauth_axum::{CustomProvider, OAuthClient};
+
+fn provider(auth_url: &str, token_url: &str) -> CustomProvider {
+    CustomProvider::new(
+        auth_url.to_string(),
+        token_url.to_string(),
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        "https://app.example.com/callback".to_string(),
+    )
+}
+
+#[test]
+fn valid_provider_is_built() {
+    let provider = provider(
+        "https://auth.example.com/authorize",
+        "https://auth.example.com/token",
+    )
+    .build()
+    .unwrap();
+    assert_eq!(
+        provider.get_client().unwrap().auth_url().as_str(),
+        "https://auth.example.com/authorize"
+    );
+}
+
+#[test]
+fn invalid_urls_fail_in_the_build() {
+    assert!(matches!(
+        provider("not a url", "https://auth.example.com/token").build(),
+        Err(OauthError::AuthUrlCreationFailed)
+    ));
+    assert!(matches!(
+        provider("https://auth.example.com/authorize", "not a url").build(),
+        Err(OauthError::TokenUrlCreationFailed)
+    ));
+}
+
+#[test]
+fn client_is_created_again_by_the_setters() {
+    let provider = provider(
+        "https://auth.example.com/authorize",
+        "https://auth.example.com/token",
+    );
+    let clone = provider.clone();
+    // The unsafe redirect URL is rejected by the policy, the clone keeps the previous client
+    let provider = provider.set_redirect_policy(RedirectPolicy::new().allow_host("other.com"));
+    assert!(provider.get_client().is_err());
+    assert!(provider.build().is_err());
+    assert!(clone.get_client().is_ok());
+}
@@ -0,0 +1,55 @@
+//! Token endpoint, provider and exchange shared by the tests of the token request
+#![allow(dead_code)]
+
+use axum::http::StatusCode;
+use axum::{Json, Router};
+use oauth_axum::error::OauthError;
+use oauth_axum::flow::PendingAuthorization;
+use oauth_axum::token::TokenSet;
+use oauth_axum::{CustomProvider, StateAuth};
+use serde_json::{json, Value};
+
+/// Serve the routes on a free port of localhost
+/// # Return
+/// The URL of the `/token` route
+pub async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}/token")
+}
+
+/// Response of the token endpoint with the access token
+pub fn token_response(access_token: &str) -> Json<Value> {
+    Json(json!({
+        "access_token": access_token,
+        "token_type": "bearer",
+    }))
+}
+
+/// Error of the token endpoint with the OAuth `error` code
+pub fn token_error(status: StatusCode, error: &str) -> (StatusCode, Json<Value>) {
+    (status, Json(json!({ "error": error })))
+}
+
+pub fn provider(token_url: &str) -> CustomProvider {
+    CustomProvider::new(
+        "https://auth.example.com/authorize".to_string(),
+        token_url.to_string(),
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        "https://app.example.com/callback".to_string(),
+    )
+}
+
+/// Exchange the code with a state loaded from the store, like the callback of the app
+pub async fn exchange(provider: CustomProvider, code: &str) -> Result<TokenSet, OauthError> {
+    let state = StateAuth::restore(
+        "state".to_string(),
+        "verifier-of-the-test-with-more-than-43-characters".to_string(),
+    )
+    .unwrap();
+    PendingAuthorization::resume(provider, state)?
+        .exchange(code.to_string())
+        .await
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::post;
+use axum::Router;
+use axum_extra::extract::cookie::{Key, PrivateCookieJar};
+use common::{provider, token_error, token_response};
+use oauth_axum::cookie::{
+    generate_token_from_cookies, generate_url_with_cookies, take_verifier_from_cookies,
+    STATE_COOKIE, VERIFIER_COOKIE,
+};
+use oauth_axum::error::OauthError;
+use oauth_axum::CustomProvider;
+
+/// Token endpoint that fails when `fail` is set
+async fn token_server(fail: bool) -> String {
+    let app = Router::new().route(
+        "/token",
+        post(move || async move {
+            if fail {
+                return Err(token_error(StatusCode::BAD_REQUEST, "invalid_grant"));
+            }
+            Ok(token_response("access-token"))
+        }),
+    );
+    common::serve(app).await
+}
+
+fn jar() -> PrivateCookieJar {
+    PrivateCookieJar::new(Key::from(&[7; 64]))
+}
+
+/// Start the login and return the jar of the callback request, with the cookies sent by the browser
+async fn begin(provider: CustomProvider) -> (PrivateCookieJar, String) {
+    let response = generate_url_with_cookies(provider, ["read"], jar())
+        .await
+        .unwrap()
+        .into_response();
+    let cookies = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .map(|value| value.to_str().unwrap().split(';').next().unwrap())
+        .collect::<Vec<_>>()
+        .join("; ");
+    let mut headers = HeaderMap::new();
+    headers.insert(header::COOKIE, HeaderValue::from_str(&cookies).unwrap());
+    let jar = PrivateCookieJar::from_headers(&headers, Key::from(&[7; 64]));
+    let state = jar.get(STATE_COOKIE).unwrap().value().to_string();
+    (jar, state)
+}
+
+/// The jar removes the state and verifier cookies in the response
+fn removes_cookies(jar: PrivateCookieJar) -> bool {
+    assert!(jar.get(STATE_COOKIE).is_none());
+    assert!(jar.get(VERIFIER_COOKIE).is_none());
+    let response = jar.into_response();
+    let set_cookies: Vec<&str> = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    [STATE_COOKIE, VERIFIER_COOKIE].iter().all(|name| {
+        set_cookies
+            .iter()
+            .any(|cookie| cookie.starts_with(&format!("{name}=")) && cookie.contains("Max-Age=0"))
+    })
+}
+
+#[tokio::test]
+async fn login_sets_the_cookies() {
+    let (jar, redirect) =
+        generate_url_with_cookies(provider("https://auth.example.com/token"), ["read"], jar())
+            .await
+            .unwrap();
+    let state = jar.get(STATE_COOKIE).unwrap();
+    let verifier = jar.get(VERIFIER_COOKIE).unwrap();
+    assert!(!state.value().is_empty());
+    assert!(verifier.value().len() >= 43);
+
+    let response = (jar, redirect).into_response();
+    let location = response.headers()[header::LOCATION].to_str().unwrap();
+    assert!(location.contains(&format!("state={}", state.value())));
+    let set_cookies: Vec<&str> = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    assert_eq!(set_cookies.len(), 2);
+    for cookie in set_cookies {
+        // Encrypted, so the value of the verifier is not in the cookie
+        assert!(!cookie.contains(verifier.value()));
+        assert!(cookie.contains("HttpOnly"));
+        assert!(cookie.contains("Secure"));
+        assert!(cookie.contains("SameSite=Lax"));
+    }
+}
+
+#[tokio::test]
+async fn callback_removes_the_cookies_on_success() {
+    let token_url = token_server(false).await;
+    let (jar, state) = begin(provider(&token_url)).await;
+    let (jar, token) =
+        generate_token_from_cookies(&provider(&token_url), jar, "code".to_string(), &state).await;
+    assert_eq!(token.unwrap(), "access-token");
+    assert!(removes_cookies(jar));
+}
+
+#[tokio::test]
+async fn callback_removes_the_cookies_when_the_exchange_fails() {
+    let token_url = token_server(true).await;
+    let (jar, state) = begin(provider(&token_url)).await;
+    let (jar, token) =
+        generate_token_from_cookies(&provider(&token_url), jar, "code".to_string(), &state).await;
+    assert!(token.is_err());
+    assert!(removes_cookies(jar));
+}
+
+#[tokio::test]
+async fn callback_removes_the_cookies_on_state_mismatch() {
+    let token_url = token_server(false).await;
+    let (jar, _) = begin(provider(&token_url)).await;
+    let (jar, token) =
+        generate_token_from_cookies(&provider(&token_url), jar, "code".to_string(), "other").await;
+    assert!(matches!(token, Err(OauthError::StateMismatch)));
+    assert!(removes_cookies(jar));
+}
+
+#[tokio::test]
+async fn callback_without_cookies_is_rejected() {
+    let token_url = token_server(false).await;
+    let (jar, token) =
+        generate_token_from_cookies(&provider(&token_url), jar(), "code".to_string(), "state")
+            .await;
+    assert!(matches!(token, Err(OauthError::StateMismatch)));
+    assert!(jar.iter().next().is_none());
+
+    let (jar, state) = begin(provider(&token_url)).await;
+    let jar = jar.remove(VERIFIER_COOKIE);
+    let (jar, token) =
+        generate_token_from_cookies(&provider(&token_url), jar, "code".to_string(), &state).await;
+    assert!(matches!(token, Err(OauthError::VerifierNotFound)));
+    assert!(removes_cookies(jar));
+}
+
+#[tokio::test]
+async fn verifier_is_taken_once() {
+    let (jar, state) = begin(provider("https://auth.example.com/token")).await;
+    let (jar, verifier) = take_verifier_from_cookies(jar, &state).unwrap();
+    assert!(verifier.len() >= 43);
+    assert!(matches!(
+        take_verifier_from_cookies(jar, &state),
+        Err(OauthError::StateMismatch)
+    ));
+}
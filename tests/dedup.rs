@@ -0,0 +1,116 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::Router;
+use common::{exchange, provider, token_error, token_response};
+use oauth_axum::inflight::Dedup;
+
+/// Token endpoint that counts the requests, answers after a delay and fails when `fail` is set
+async fn token_server(fail: bool) -> (String, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/token",
+            post(move |State(requests): State<Arc<AtomicUsize>>| async move {
+                let request = requests.fetch_add(1, Ordering::SeqCst) + 1;
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                if fail {
+                    return Err(token_error(
+                        StatusCode::BAD_REQUEST,
+                        "temporarily_unavailable",
+                    ));
+                }
+                Ok(token_response(&format!("token-{request}")))
+            }),
+        )
+        .with_state(requests.clone());
+    (common::serve(app).await, requests)
+}
+
+#[tokio::test]
+async fn concurrent_duplicates_are_exchanged_once() {
+    let (token_url, requests) = token_server(false).await;
+    let provider = provider(&token_url);
+    let (first, second) = tokio::join!(
+        exchange(provider.clone(), "concurrent-code"),
+        exchange(provider.clone(), "concurrent-code"),
+    );
+    assert_eq!(first.unwrap().access_token, "token-1");
+    assert_eq!(second.unwrap().access_token, "token-1");
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn default_dedup_is_per_provider_and_keeps_no_result() {
+    let (token_url, requests) = token_server(false).await;
+    let (first, second) = tokio::join!(
+        exchange(provider(&token_url), "separate-code"),
+        exchange(provider(&token_url), "separate-code"),
+    );
+    assert!(first.is_ok() && second.is_ok());
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+    let provider = provider(&token_url);
+    exchange(provider.clone(), "finished-code").await.unwrap();
+    let after = exchange(provider, "finished-code").await.unwrap();
+    assert_eq!(after.access_token, "token-4");
+}
+
+#[tokio::test]
+async fn shared_dedup_deduplicates_between_providers() {
+    let (token_url, requests) = token_server(false).await;
+    let dedup = Dedup::new(Duration::from_secs(5));
+    let (first, second) = tokio::join!(
+        exchange(provider(&token_url).set_dedup(dedup.clone()), "shared-code"),
+        exchange(provider(&token_url).set_dedup(dedup), "shared-code"),
+    );
+    assert_eq!(first.unwrap().access_token, "token-1");
+    assert_eq!(second.unwrap().access_token, "token-1");
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn late_duplicate_gets_the_finished_result() {
+    let (token_url, requests) = token_server(false).await;
+    let dedup = Dedup::new(Duration::from_secs(5));
+    let provider = provider(&token_url).set_dedup(dedup);
+    let first = exchange(provider.clone(), "late-code").await.unwrap();
+    let late = exchange(provider.clone(), "late-code").await.unwrap();
+    assert_eq!(first.access_token, late.access_token);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    let other = exchange(provider, "other-code").await.unwrap();
+    assert_eq!(other.access_token, "token-2");
+}
+
+#[tokio::test]
+async fn result_is_dropped_after_the_window() {
+    let (token_url, requests) = token_server(false).await;
+    let dedup = Dedup::new(Duration::from_millis(100));
+    exchange(provider(&token_url).set_dedup(dedup.clone()), "window-code")
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let after = exchange(provider(&token_url).set_dedup(dedup), "window-code")
+        .await
+        .unwrap();
+    assert_eq!(after.access_token, "token-2");
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn failed_exchange_is_not_kept() {
+    let (token_url, requests) = token_server(true).await;
+    let dedup = Dedup::new(Duration::from_secs(60));
+    let first = exchange(provider(&token_url).set_dedup(dedup.clone()), "failed-code").await;
+    assert!(first.is_err());
+    let retry = exchange(provider(&token_url).set_dedup(dedup), "failed-code").await;
+    assert!(retry.is_err());
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
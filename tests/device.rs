@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::body::{to_bytes, Body};
+use axum::extract::State;
+use axum::http::{Request, StatusCode};
+use axum::routing::post;
+use axum::{Form, Json, Router};
+use oauth_axum::device::{self, DeviceSessions, DeviceStatus};
+use oauth_axum::scopes::Scopes;
+use oauth_axum::CustomProvider;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+type Polls = Arc<Mutex<Vec<Instant>>>;
+
+/// Device and token endpoints, the token endpoint answers the polls with the `responses` in order
+async fn device_server(responses: &'static [&'static str]) -> (String, Polls) {
+    let polls = Polls::default();
+    let app = Router::new()
+        .route(
+            "/device",
+            post(|| async {
+                Json(json!({
+                    "device_code": "device-code",
+                    "user_code": "ABCD-EFGH",
+                    "verification_uri": "https://auth.example.com/device",
+                    "expires_in": 60,
+                    "interval": 0,
+                }))
+            }),
+        )
+        .route(
+            "/token",
+            post(
+                move |State(polls): State<Polls>, Form(form): Form<HashMap<String, String>>| async move {
+                    assert_eq!(form["device_code"], "device-code");
+                    let poll = {
+                        let mut polls = polls.lock().unwrap();
+                        polls.push(Instant::now());
+                        polls.len() - 1
+                    };
+                    match responses[poll.min(responses.len() - 1)] {
+                        "success" => Ok(Json::<Value>(json!({
+                            "access_token": "device-token",
+                            "token_type": "bearer",
+                        }))),
+                        error => Err((StatusCode::BAD_REQUEST, Json(json!({ "error": error })))),
+                    }
+                },
+            ),
+        )
+        .with_state(polls.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}"), polls)
+}
+
+fn provider(base_url: &str) -> CustomProvider {
+    CustomProvider::new(
+        "https://auth.example.com/authorize".to_string(),
+        format!("{base_url}/token"),
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        "https://app.example.com/callback".to_string(),
+    )
+    .set_device_authorization_url(&format!("{base_url}/device"))
+}
+
+/// Wait until the session is not pending anymore
+async fn final_status(sessions: &DeviceSessions, user_code: &str) -> Option<DeviceStatus> {
+    let started = Instant::now();
+    while sessions.status(user_code) == Some(DeviceStatus::Pending)
+        && started.elapsed() < Duration::from_secs(15)
+    {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    sessions.status(user_code)
+}
+
+/// Status returned by the status route
+async fn route_status(sessions: &DeviceSessions, user_code: &str) -> (StatusCode, Option<Value>) {
+    let request = Request::get(format!("/{user_code}"))
+        .body(Body::empty())
+        .unwrap();
+    let response = device::status_router(sessions.clone())
+        .oneshot(request)
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).ok())
+}
+
+#[tokio::test]
+async fn polls_until_the_user_approves() {
+    let (base_url, polls) = device_server(&["authorization_pending", "slow_down", "success"]).await;
+    let sessions = DeviceSessions::new();
+    let authorization = sessions
+        .start(provider(&base_url), Scopes::from(["read"]))
+        .await
+        .unwrap();
+    assert_eq!(authorization.user_code, "ABCD-EFGH");
+    assert_eq!(
+        authorization.verification_uri,
+        "https://auth.example.com/device"
+    );
+    assert_eq!(sessions.status("ABCD-EFGH"), Some(DeviceStatus::Pending));
+    assert_eq!(
+        route_status(&sessions, "ABCD-EFGH").await,
+        (
+            StatusCode::OK,
+            Some(json!({ "user_code": "ABCD-EFGH", "status": "pending" }))
+        )
+    );
+
+    assert_eq!(
+        final_status(&sessions, "ABCD-EFGH").await,
+        Some(DeviceStatus::Complete)
+    );
+    let polls = polls.lock().unwrap().clone();
+    assert_eq!(polls.len(), 3);
+    // `slow_down` adds 5 seconds to the interval
+    assert!(polls[2] - polls[1] >= Duration::from_secs(5));
+    assert_eq!(
+        route_status(&sessions, "ABCD-EFGH").await.1,
+        Some(json!({ "user_code": "ABCD-EFGH", "status": "complete" }))
+    );
+
+    let token = sessions.take_token("ABCD-EFGH").unwrap();
+    assert_eq!(token.access_token, "device-token");
+    assert_eq!(sessions.status("ABCD-EFGH"), None);
+    assert!(sessions.take_token("ABCD-EFGH").is_none());
+    assert_eq!(
+        route_status(&sessions, "ABCD-EFGH").await.0,
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn denied_session_has_no_token() {
+    let (base_url, polls) = device_server(&["authorization_pending", "access_denied"]).await;
+    let sessions = DeviceSessions::new();
+    sessions
+        .start(provider(&base_url), Scopes::new())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        final_status(&sessions, "ABCD-EFGH").await,
+        Some(DeviceStatus::Denied)
+    );
+    assert_eq!(polls.lock().unwrap().len(), 2);
+    assert!(sessions.take_token("ABCD-EFGH").is_none());
+    assert_eq!(
+        route_status(&sessions, "ABCD-EFGH").await.1,
+        Some(json!({ "user_code": "ABCD-EFGH", "status": "denied" }))
+    );
+
+    sessions.remove("ABCD-EFGH");
+    assert_eq!(sessions.status("ABCD-EFGH"), None);
+}
+
+#[tokio::test]
+async fn expired_device_code_is_reported() {
+    let (base_url, _) = device_server(&["expired_token"]).await;
+    let sessions = DeviceSessions::new();
+    sessions
+        .start(provider(&base_url), Scopes::new())
+        .await
+        .unwrap();
+    assert_eq!(
+        final_status(&sessions, "ABCD-EFGH").await,
+        Some(DeviceStatus::Expired)
+    );
+}
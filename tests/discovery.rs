@@ -0,0 +1,113 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use oauth_axum::discovery::{DiscoveryCache, ProviderMetadata};
+use serde_json::json;
+
+/// `If-None-Match` of every request, None when the request was sent without it
+type Requests = Arc<Mutex<Vec<Option<String>>>>;
+
+/// Discovery endpoint with the ETag `"v1"`, it answers 304 to the requests with the same ETag
+async fn discovery_server() -> (String, Requests) {
+    let requests = Requests::default();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let issuer = format!("http://{}", listener.local_addr().unwrap());
+    let document = json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{issuer}/authorize"),
+        "token_endpoint": format!("{issuer}/token"),
+        "jwks_uri": format!("{issuer}/jwks"),
+    });
+    let app = Router::new()
+        .route(
+            "/.well-known/openid-configuration",
+            get(
+                move |State(requests): State<Requests>, headers: HeaderMap| async move {
+                    let etag = headers
+                        .get(header::IF_NONE_MATCH)
+                        .and_then(|etag| etag.to_str().ok())
+                        .map(str::to_string);
+                    requests.lock().unwrap().push(etag.clone());
+                    match etag.as_deref() {
+                        Some("\"v1\"") => StatusCode::NOT_MODIFIED.into_response(),
+                        _ => ([(header::ETAG, "\"v1\"")], Json(document)).into_response(),
+                    }
+                },
+            ),
+        )
+        .with_state(requests.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (issuer, requests)
+}
+
+#[tokio::test]
+async fn metadata_is_cached_for_the_ttl() {
+    let (issuer, requests) = discovery_server().await;
+    let cache = DiscoveryCache::new(Duration::from_millis(200));
+
+    let metadata = cache.get(&issuer).await.unwrap();
+    assert_eq!(metadata.issuer, issuer);
+    assert_eq!(metadata.token_endpoint, Some(format!("{issuer}/token")));
+    let cached = cache.clone().get(&issuer).await.unwrap();
+    assert_eq!(
+        cached.authorization_endpoint,
+        metadata.authorization_endpoint
+    );
+    assert_eq!(*requests.lock().unwrap(), vec![None]);
+
+    // After the TTL the document is revalidated with the ETag, and cached again
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    let revalidated = cache.get(&issuer).await.unwrap();
+    assert_eq!(revalidated.issuer, issuer);
+    cache.get(&issuer).await.unwrap();
+    assert_eq!(
+        *requests.lock().unwrap(),
+        vec![None, Some("\"v1\"".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn force_refresh_ignores_the_cache() {
+    let (issuer, requests) = discovery_server().await;
+    let cache = DiscoveryCache::new(Duration::from_secs(3600));
+    cache.get(&issuer).await.unwrap();
+    cache.force_refresh(&issuer).await.unwrap();
+    assert_eq!(*requests.lock().unwrap(), vec![None, None]);
+
+    cache.invalidate(&issuer);
+    cache.get(&issuer).await.unwrap();
+    assert_eq!(requests.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn other_issuer_is_rejected() {
+    let (issuer, _) = discovery_server().await;
+    let cache = DiscoveryCache::default();
+    assert!(cache.get(&format!("{issuer}/tenant")).await.is_err());
+}
+
+#[tokio::test]
+async fn templated_issuer_is_rejected() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server = format!("http://{}", listener.local_addr().unwrap());
+    // Multi-tenant document, the issuer of the tokens is the tenant one
+    let document = json!({
+        "issuer": format!("{server}/{{tenantid}}"),
+        "authorization_endpoint": format!("{server}/authorize"),
+        "token_endpoint": format!("{server}/token"),
+    });
+    let app = Router::new().route(
+        "/contoso/.well-known/openid-configuration",
+        get(move || async move { Json(document) }),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let issuer = format!("{server}/contoso");
+    assert!(DiscoveryCache::default().get(&issuer).await.is_err());
+    assert!(ProviderMetadata::discover(&issuer).await.is_err());
+}
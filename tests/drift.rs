@@ -0,0 +1,42 @@
+use oauth_axum::discovery::ProviderMetadata;
+use oauth_axum::drift::compare_endpoints;
+use oauth_axum::providers::microsoft::MicrosoftProvider;
+use oauth_axum::providers::ProviderPreset;
+
+const MICROSOFT_DOCUMENT: &str = include_str!("data/microsoft-openid-configuration.json");
+
+fn microsoft_metadata() -> ProviderMetadata {
+    serde_json::from_str(MICROSOFT_DOCUMENT).unwrap()
+}
+
+#[test]
+fn microsoft_templated_issuer_matches_the_preset_issuer() {
+    let metadata = microsoft_metadata();
+    assert!(metadata.matches_issuer(MicrosoftProvider::ISSUER.unwrap()));
+    assert!(
+        metadata.matches_issuer("https://login.microsoftonline.com/contoso.onmicrosoft.com/v2.0/")
+    );
+    assert!(!metadata.matches_issuer("https://login.microsoftonline.com/v2.0"));
+    assert!(!metadata.matches_issuer("https://evil.example.com/common/v2.0"));
+}
+
+#[test]
+fn microsoft_document_has_no_drift() {
+    let drifts = compare_endpoints::<MicrosoftProvider>(microsoft_metadata()).unwrap();
+    assert!(drifts.is_empty(), "{drifts:?}");
+}
+
+#[test]
+fn changed_endpoint_is_reported() {
+    let mut metadata = microsoft_metadata();
+    metadata.token_endpoint =
+        Some("https://login.microsoftonline.com/common/oauth2/v3.0/token".to_string());
+    let drifts = compare_endpoints::<MicrosoftProvider>(metadata).unwrap();
+    assert_eq!(drifts.len(), 1);
+    assert_eq!(drifts[0].preset, "microsoft");
+    assert_eq!(drifts[0].endpoint, "token_endpoint");
+    assert_eq!(
+        drifts[0].published,
+        "https://login.microsoftonline.com/common/oauth2/v3.0/token"
+    );
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::Router;
+use common::{exchange, token_error, token_response};
+use oauth_axum::error::OauthError;
+use oauth_axum::CustomProvider;
+
+/// Token endpoint that counts the requests and answers with `status`
+async fn token_server(status: StatusCode) -> (String, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/token",
+            post(move |State(requests): State<Arc<AtomicUsize>>| async move {
+                requests.fetch_add(1, Ordering::SeqCst);
+                if status != StatusCode::OK {
+                    return Err(token_error(status, "temporarily_unavailable"));
+                }
+                Ok(token_response("access-token"))
+            }),
+        )
+        .with_state(requests.clone());
+    (common::serve(app).await, requests)
+}
+
+/// URL of a port that was closed, the connection to it is refused
+async fn unreachable_url() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{addr}/token")
+}
+
+fn provider(token_url: &str, fallback: &str) -> CustomProvider {
+    common::provider(token_url).set_token_url_fallbacks(&[fallback])
+}
+
+#[tokio::test]
+async fn unreachable_token_url_uses_the_fallback() {
+    let token_url = unreachable_url().await;
+    let (fallback, requests) = token_server(StatusCode::OK).await;
+    let token = exchange(provider(&token_url, &fallback), "code")
+        .await
+        .unwrap();
+    assert_eq!(token.access_token, "access-token");
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn http_errors_are_not_sent_to_the_fallback() {
+    for status in [StatusCode::BAD_REQUEST, StatusCode::SERVICE_UNAVAILABLE] {
+        let (token_url, primary) = token_server(status).await;
+        let (fallback, requests) = token_server(StatusCode::OK).await;
+        let result = exchange(provider(&token_url, &fallback), "code").await;
+        assert!(matches!(result, Err(OauthError::TokenRequestFailed)));
+        assert_eq!(primary.load(Ordering::SeqCst), 1);
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+    }
+}
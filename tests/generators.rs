@@ -0,0 +1,100 @@
+use oauth_axum::error::OauthError;
+use oauth_axum::generators::{PkceCharset, PkceGenerator, StateGenerator};
+use oauth_axum::{CustomProvider, OAuthClient};
+
+fn provider(pkce_generator: PkceGenerator) -> CustomProvider {
+    CustomProvider::new(
+        "https://auth.example.com/authorize".to_string(),
+        "https://auth.example.com/token".to_string(),
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        "https://app.example.com/callback".to_string(),
+    )
+    .set_pkce_generator(pkce_generator)
+}
+
+#[test]
+fn custom_verifier_is_used() {
+    for verifier in ["a".repeat(43), "-._~".repeat(32), "aZ0-._~".repeat(8)] {
+        let expected = verifier.clone();
+        let generator = PkceGenerator::custom(move || expected.clone());
+        let (_, generated) = generator.generate().unwrap();
+        assert_eq!(generated.secret(), &verifier);
+    }
+}
+
+#[test]
+fn custom_verifier_length_is_validated() {
+    for length in [0, 42, 129] {
+        let generator = PkceGenerator::custom(move || "a".repeat(length));
+        assert!(
+            matches!(
+                generator.generate(),
+                Err(OauthError::InvalidPkceVerifierLength)
+            ),
+            "{length}"
+        );
+    }
+}
+
+#[test]
+fn custom_verifier_characters_are_validated() {
+    for invalid in ["+", "/", "=", " ", "%", "é"] {
+        let verifier = format!("{}{invalid}", "a".repeat(43));
+        let generator = PkceGenerator::custom(move || verifier.clone());
+        assert!(
+            matches!(generator.generate(), Err(OauthError::InvalidPkceVerifier)),
+            "{invalid}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn invalid_custom_verifier_fails_the_url() {
+    let provider = provider(PkceGenerator::custom(|| "short".to_string()));
+    assert!(matches!(
+        provider.authorize(["openid"]).await,
+        Err(OauthError::InvalidPkceVerifierLength)
+    ));
+}
+
+#[tokio::test]
+async fn random_verifier_has_the_length_and_charset() {
+    assert!(matches!(
+        PkceGenerator::new(42, PkceCharset::Unreserved),
+        Err(OauthError::InvalidPkceVerifierLength)
+    ));
+    assert!(PkceGenerator::new(129, PkceCharset::Unreserved).is_err());
+
+    let generator = PkceGenerator::new(128, PkceCharset::Alphanumeric).unwrap();
+    let (_, verifier) = generator.generate().unwrap();
+    assert_eq!(verifier.secret().len(), 128);
+    assert!(verifier.secret().chars().all(|c| c.is_ascii_alphanumeric()));
+
+    let pending = provider(generator).authorize(["openid"]).await.unwrap();
+    assert_eq!(pending.into_state().verifier().len(), 128);
+}
+
+#[test]
+fn random_state_has_the_length_and_alphabet() {
+    for (length, alphabet) in [(15, "ab"), (16, ""), (16, "aaaa")] {
+        assert!(
+            matches!(
+                StateGenerator::with_alphabet(length, alphabet),
+                Err(OauthError::InvalidStateGenerator)
+            ),
+            "{length} {alphabet}"
+        );
+    }
+    let state = StateGenerator::with_alphabet(16, "ab").unwrap().generate();
+    assert_eq!(state.secret().len(), 16);
+    assert!(state.secret().chars().all(|c| c == 'a' || c == 'b'));
+    assert_eq!(
+        StateGenerator::random(22)
+            .unwrap()
+            .generate()
+            .secret()
+            .len(),
+        22
+    );
+}
@@ -0,0 +1,286 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use oauth_axum::error::OauthError;
+use oauth_axum::jwt::{Jwk, JwkSet, JwksCache};
+use ring::rand::SystemRandom;
+use ring::signature::{
+    EcdsaKeyPair, KeyPair, RsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING, RSA_PKCS1_SHA256,
+};
+use serde_json::{json, Value};
+
+const RSA_KEY: &[u8] = include_bytes!("data/rsa2048.der");
+
+fn encode(value: &Value) -> String {
+    URL_SAFE_NO_PAD.encode(value.to_string())
+}
+
+fn claims() -> Value {
+    json!({ "iss": "https://issuer.example.com", "sub": "user-1", "aud": "client" })
+}
+
+fn rsa_jwk(key: &RsaKeyPair) -> Jwk {
+    let public = ring::rsa::PublicKeyComponents::<Vec<u8>>::from(key.public());
+    Jwk {
+        kty: "RSA".into(),
+        kid: Some("rsa-1".into()),
+        alg: Some("RS256".into()),
+        n: Some(URL_SAFE_NO_PAD.encode(&public.n)),
+        e: Some(URL_SAFE_NO_PAD.encode(&public.e)),
+        crv: None,
+        x: None,
+        y: None,
+    }
+}
+
+fn rsa_keys() -> (RsaKeyPair, JwksCache) {
+    let key = RsaKeyPair::from_der(RSA_KEY).unwrap();
+    let jwk = rsa_jwk(&key);
+    (key, JwksCache::from_keys(JwkSet { keys: vec![jwk] }))
+}
+
+/// JWKS endpoint that answers after 200ms, with an error while `failing` is true
+async fn jwks_server() -> (String, Arc<AtomicUsize>, Arc<AtomicBool>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let failing = Arc::new(AtomicBool::new(false));
+    let jwks = JwkSet {
+        keys: vec![rsa_jwk(&RsaKeyPair::from_der(RSA_KEY).unwrap())],
+    };
+    let app = Router::new()
+        .route(
+            "/jwks",
+            get(
+                |State((requests, failing)): State<(Arc<AtomicUsize>, Arc<AtomicBool>)>| async move {
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    match failing.load(Ordering::SeqCst) {
+                        true => Err(StatusCode::SERVICE_UNAVAILABLE),
+                        false => Ok(Json(jwks)),
+                    }
+                },
+            ),
+        )
+        .with_state((requests.clone(), failing.clone()));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}/jwks"), requests, failing)
+}
+
+/// Get the key in 10 concurrent requests
+async fn concurrent_keys(jwks: &JwksCache, kid: &str) -> Vec<Result<Jwk, OauthError>> {
+    let requests = (0..10).map(|_| {
+        let jwks = jwks.clone();
+        let kid = kid.to_string();
+        tokio::spawn(async move { jwks.key(Some(&kid)).await })
+    });
+    let mut keys = Vec::new();
+    for request in requests.collect::<Vec<_>>() {
+        keys.push(request.await.unwrap());
+    }
+    keys
+}
+
+fn rsa_token(key: &RsaKeyPair, header: &Value, claims: &Value) -> String {
+    let message = format!("{}.{}", encode(header), encode(claims));
+    let mut signature = vec![0; key.public().modulus_len()];
+    key.sign(
+        &RSA_PKCS1_SHA256,
+        &SystemRandom::new(),
+        message.as_bytes(),
+        &mut signature,
+    )
+    .unwrap();
+    format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature))
+}
+
+fn ec_keys() -> (EcdsaKeyPair, JwksCache) {
+    let rng = SystemRandom::new();
+    let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+    let key =
+        EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+    // Uncompressed point: 0x04 || x || y
+    let point = key.public_key().as_ref();
+    let jwk = Jwk {
+        kty: "EC".into(),
+        kid: Some("ec-1".into()),
+        alg: Some("ES256".into()),
+        n: None,
+        e: None,
+        crv: Some("P-256".into()),
+        x: Some(URL_SAFE_NO_PAD.encode(&point[1..33])),
+        y: Some(URL_SAFE_NO_PAD.encode(&point[33..])),
+    };
+    (key, JwksCache::from_keys(JwkSet { keys: vec![jwk] }))
+}
+
+fn ec_token(key: &EcdsaKeyPair, header: &Value, claims: &Value) -> String {
+    let message = format!("{}.{}", encode(header), encode(claims));
+    let signature = key.sign(&SystemRandom::new(), message.as_bytes()).unwrap();
+    format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature.as_ref()))
+}
+
+#[tokio::test]
+async fn accepts_rs256_token() {
+    let (key, jwks) = rsa_keys();
+    let token = rsa_token(&key, &json!({ "alg": "RS256", "kid": "rsa-1" }), &claims());
+    let verified: Value = jwks.verify(&token).await.unwrap();
+    assert_eq!(verified, claims());
+}
+
+#[tokio::test]
+async fn accepts_es256_token() {
+    let (key, jwks) = ec_keys();
+    let token = ec_token(&key, &json!({ "alg": "ES256", "kid": "ec-1" }), &claims());
+    let verified: Value = jwks.verify(&token).await.unwrap();
+    assert_eq!(verified, claims());
+}
+
+#[tokio::test]
+async fn rejects_alg_none() {
+    let (_, jwks) = rsa_keys();
+    let token = format!(
+        "{}.{}.",
+        encode(&json!({ "alg": "none", "kid": "rsa-1" })),
+        encode(&claims())
+    );
+    let result = jwks.verify::<Value>(&token).await;
+    assert!(matches!(result, Err(OauthError::InvalidIdTokenSignature)));
+}
+
+#[tokio::test]
+async fn rejects_hs256_signed_with_the_public_key() {
+    let (key, jwks) = rsa_keys();
+    let message = format!(
+        "{}.{}",
+        encode(&json!({ "alg": "HS256", "kid": "rsa-1" })),
+        encode(&claims())
+    );
+    let hmac_key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, key.public().as_ref());
+    let signature = ring::hmac::sign(&hmac_key, message.as_bytes());
+    let token = format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature.as_ref()));
+    let result = jwks.verify::<Value>(&token).await;
+    assert!(matches!(result, Err(OauthError::InvalidIdTokenSignature)));
+}
+
+#[tokio::test]
+async fn rejects_unknown_kid() {
+    let (key, jwks) = rsa_keys();
+    let token = rsa_token(&key, &json!({ "alg": "RS256", "kid": "other" }), &claims());
+    let result = jwks.verify::<Value>(&token).await;
+    assert!(matches!(result, Err(OauthError::InvalidIdTokenSignature)));
+}
+
+#[tokio::test]
+async fn rejects_key_of_other_algorithm() {
+    let (key, _) = ec_keys();
+    let (_, jwks) = rsa_keys();
+    let token = ec_token(&key, &json!({ "alg": "ES256", "kid": "rsa-1" }), &claims());
+    let result = jwks.verify::<Value>(&token).await;
+    assert!(matches!(result, Err(OauthError::InvalidIdTokenSignature)));
+}
+
+#[tokio::test]
+async fn rejects_tampered_payload() {
+    let (key, jwks) = ec_keys();
+    let token = ec_token(&key, &json!({ "alg": "ES256", "kid": "ec-1" }), &claims());
+    let mut parts: Vec<&str> = token.split('.').collect();
+    let tampered =
+        encode(&json!({ "iss": "https://issuer.example.com", "sub": "admin", "aud": "client" }));
+    parts[1] = &tampered;
+    let result = jwks.verify::<Value>(&parts.join(".")).await;
+    assert!(matches!(result, Err(OauthError::InvalidIdTokenSignature)));
+}
+
+#[tokio::test]
+async fn checks_the_type_of_access_tokens() {
+    let (key, jwks) = ec_keys();
+    let access = ec_token(
+        &key,
+        &json!({ "alg": "ES256", "kid": "ec-1", "typ": "at+JWT" }),
+        &claims(),
+    );
+    let verified: Value = jwks.verify_typed(&access, &["at+jwt"]).await.unwrap();
+    assert_eq!(verified, claims());
+
+    let id_token = ec_token(
+        &key,
+        &json!({ "alg": "ES256", "kid": "ec-1", "typ": "JWT" }),
+        &claims(),
+    );
+    let result = jwks.verify_typed::<Value>(&id_token, &["at+jwt"]).await;
+    assert!(matches!(result, Err(OauthError::InvalidIdToken)));
+}
+
+#[tokio::test]
+async fn concurrent_requests_wait_for_one_fetch() {
+    let (uri, requests, _) = jwks_server().await;
+    let jwks = JwksCache::new(&uri);
+    assert!(concurrent_keys(&jwks, "rsa-1")
+        .await
+        .iter()
+        .all(|key| key.is_ok()));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    // The unknown `kid`s wait for the minimum refresh interval
+    assert!(concurrent_keys(&jwks, "other")
+        .await
+        .iter()
+        .all(|key| matches!(key, Err(OauthError::InvalidIdTokenSignature))));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn keys_are_fetched_again_after_the_ttl() {
+    let (uri, requests, _) = jwks_server().await;
+    let jwks = JwksCache::new(&uri).set_ttl(Duration::from_millis(300));
+    jwks.key(Some("rsa-1")).await.unwrap();
+    // The refetch after the TTL is not limited by the minimum refresh interval
+    tokio::time::sleep(Duration::from_millis(350)).await;
+    assert!(concurrent_keys(&jwks, "rsa-1")
+        .await
+        .iter()
+        .all(|key| key.is_ok()));
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn expired_keys_are_used_when_the_fetch_fails() {
+    let (uri, requests, failing) = jwks_server().await;
+    let jwks = JwksCache::new(&uri).set_ttl(Duration::from_millis(300));
+    jwks.key(Some("rsa-1")).await.unwrap();
+
+    failing.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(350)).await;
+    assert!(concurrent_keys(&jwks, "rsa-1")
+        .await
+        .iter()
+        .all(|key| key.is_ok()));
+    assert!(matches!(
+        jwks.key(Some("other")).await,
+        Err(OauthError::JwksFetchFailed)
+    ));
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn failed_first_fetch_is_retried() {
+    let (uri, requests, failing) = jwks_server().await;
+    let jwks = JwksCache::new(&uri);
+    failing.store(true, Ordering::SeqCst);
+    assert!(concurrent_keys(&jwks, "rsa-1")
+        .await
+        .iter()
+        .all(|key| matches!(key, Err(OauthError::JwksFetchFailed))));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    failing.store(false, Ordering::SeqCst);
+    assert!(jwks.key(Some("rsa-1")).await.is_ok());
+}
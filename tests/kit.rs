@@ -0,0 +1,249 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use axum::response::Response;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use axum_extra::extract::cookie::Key;
+use oauth2::url::Url;
+use oauth_axum::kit::{CurrentUser, LoginKit, SESSION_COOKIE};
+use oauth_axum::registry::ProviderRegistry;
+use oauth_axum::scopes::Scopes;
+use oauth_axum::store::MemoryStateStore;
+use oauth_axum::user::UserInfo;
+use oauth_axum::CustomProvider;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+/// Token and user endpoints of the provider
+async fn provider_server() -> String {
+    let app = Router::new()
+        .route(
+            "/token",
+            post(|| async {
+                Json::<Value>(json!({
+                    "access_token": "access-token",
+                    "token_type": "bearer",
+                }))
+            }),
+        )
+        .route(
+            "/user",
+            get(|| async {
+                Json::<Value>(json!({
+                    "sub": "user-1",
+                    "email": "user@example.com",
+                    "name": "User",
+                }))
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+async fn me(user: CurrentUser) -> String {
+    user.id
+}
+
+/// App with the routes of the kit in `/auth` and a page that needs the session
+async fn app() -> Router {
+    let base_url = provider_server().await;
+    let provider = CustomProvider::new(
+        "https://auth.example.com/authorize".to_string(),
+        format!("{base_url}/token"),
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        "https://app.example.com/auth/callback/test".to_string(),
+    )
+    .set_user_info(UserInfo::new(&format!("{base_url}/user")));
+    let registry = ProviderRegistry::new().register("test", provider, Scopes::from(["openid"]));
+    let key = Key::from(&[7; 64]);
+    let kit = LoginKit::new(registry, Arc::new(MemoryStateStore::default()), key.clone())
+        .set_after_login("/home")
+        .set_after_logout("/bye");
+    Router::new()
+        .route("/me", get(me))
+        .nest("/auth", kit.router())
+        .with_state(key)
+}
+
+async fn send(app: &Router, method: Method, uri: &str, cookie: Option<&str>) -> Response {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(cookie) = cookie {
+        request = request.header(header::COOKIE, cookie);
+    }
+    app.clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+fn location(response: &Response) -> &str {
+    response.headers()[header::LOCATION].to_str().unwrap()
+}
+
+/// The `name=value` part of the session cookie set in the response
+fn session_cookie(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find(|value| value.starts_with(&format!("{SESSION_COOKIE}=")))
+        .map(|value| value.split(';').next().unwrap().to_string())
+}
+
+/// Start the login and return the state sent to the provider
+async fn begin(app: &Router, uri: &str) -> String {
+    let response = send(app, Method::GET, uri, None).await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let url = Url::parse(location(&response)).unwrap();
+    assert_eq!(url.host_str(), Some("auth.example.com"));
+    url.query_pairs()
+        .find(|(name, _)| name == "state")
+        .map(|(_, value)| value.into_owned())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn login_callback_and_session() {
+    let app = app().await;
+    let state = begin(&app, "/auth/login/test?return_to=/dashboard").await;
+
+    let response = send(
+        &app,
+        Method::GET,
+        &format!("/auth/callback/test?code=the-code&state={state}"),
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(location(&response), "/dashboard");
+    let cookie = session_cookie(&response).unwrap();
+
+    let response = send(&app, Method::GET, "/me", Some(&cookie)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(&body[..], b"user-1");
+}
+
+#[tokio::test]
+async fn callback_accepts_form_post() {
+    let app = app().await;
+    let state = begin(&app, "/auth/login/test").await;
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/auth/callback/test")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(format!("code=the-code&state={state}")))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(location(&response), "/home");
+    assert!(session_cookie(&response).is_some());
+}
+
+#[tokio::test]
+async fn callback_rejects_a_state_mismatch() {
+    let app = app().await;
+    begin(&app, "/auth/login/test").await;
+
+    for uri in [
+        "/auth/callback/test?code=the-code&state=other-state",
+        "/auth/callback/test?code=the-code",
+    ] {
+        let response = send(&app, Method::GET, uri, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{uri}");
+        assert!(session_cookie(&response).is_none());
+    }
+}
+
+#[tokio::test]
+async fn callback_consumes_the_state() {
+    let app = app().await;
+    let state = begin(&app, "/auth/login/test").await;
+    let uri = format!("/auth/callback/test?error=access_denied&state={state}");
+
+    let response = send(&app, Method::GET, &uri, None).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = send(
+        &app,
+        Method::GET,
+        &format!("/auth/callback/test?code=the-code&state={state}"),
+        None,
+    )
+    .await;
+    // The callback of the failed login can't be replayed with a code, the state was consumed
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(session_cookie(&response).is_none());
+}
+
+#[tokio::test]
+async fn unknown_provider_is_not_found() {
+    let app = app().await;
+    let response = send(&app, Method::GET, "/auth/login/other", None).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn session_is_required() {
+    let app = app().await;
+    let response = send(&app, Method::GET, "/me", None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // A cookie that was not encrypted with the key of the app is ignored
+    let forged = format!("{SESSION_COOKIE}=user-1");
+    let response = send(&app, Method::GET, "/me", Some(&forged)).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn logout_is_post_only() {
+    let app = app().await;
+    let state = begin(&app, "/auth/login/test").await;
+    let response = send(
+        &app,
+        Method::GET,
+        &format!("/auth/callback/test?code=the-code&state={state}"),
+        None,
+    )
+    .await;
+    let cookie = session_cookie(&response).unwrap();
+
+    let response = send(&app, Method::GET, "/auth/logout", Some(&cookie)).await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert!(session_cookie(&response).is_none());
+
+    let response = send(&app, Method::POST, "/auth/logout", Some(&cookie)).await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(location(&response), "/bye");
+    let removed = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find(|value| value.starts_with(&format!("{SESSION_COOKIE}=")))
+        .unwrap();
+    assert!(removed.contains("Max-Age=0"), "{removed}");
+}
+
+#[tokio::test]
+async fn session_is_optional_with_option() {
+    async fn optional(user: Option<CurrentUser>) -> StatusCode {
+        match user {
+            Some(_) => StatusCode::OK,
+            None => StatusCode::NO_CONTENT,
+        }
+    }
+    let app = Router::new()
+        .route("/optional", get(optional))
+        .with_state(Key::from(&[7; 64]));
+    let response = send(&app, Method::GET, "/optional", None).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
@@ -0,0 +1,186 @@
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use oauth2::url::Url;
+use oauth_axum::error::OauthError;
+use oauth_axum::flow::PendingAuthorization;
+use oauth_axum::jwt::{Jwk, JwkSet, JwksCache};
+use oauth_axum::openid::CustomOpenIdProvider;
+use oauth_axum::{CustomProvider, OAuthClient, StateAuth};
+use ring::rand::SystemRandom;
+use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+use serde_json::{json, Value};
+
+const RSA_KEY: &[u8] = include_bytes!("data/rsa2048.der");
+const ISSUER: &str = "https://issuer.example.com";
+
+/// Claims of the ID token returned by the token endpoint
+type IdTokenClaims = Arc<Mutex<Value>>;
+
+fn encode(value: &Value) -> String {
+    URL_SAFE_NO_PAD.encode(value.to_string())
+}
+
+fn jwks(key: &RsaKeyPair) -> JwksCache {
+    let public = ring::rsa::PublicKeyComponents::<Vec<u8>>::from(key.public());
+    JwksCache::from_keys(JwkSet {
+        keys: vec![Jwk {
+            kty: "RSA".into(),
+            kid: Some("rsa-1".into()),
+            alg: Some("RS256".into()),
+            n: Some(URL_SAFE_NO_PAD.encode(&public.n)),
+            e: Some(URL_SAFE_NO_PAD.encode(&public.e)),
+            crv: None,
+            x: None,
+            y: None,
+        }],
+    })
+}
+
+fn id_token(key: &RsaKeyPair, claims: &Value) -> String {
+    let message = format!(
+        "{}.{}",
+        encode(&json!({ "alg": "RS256", "kid": "rsa-1" })),
+        encode(claims)
+    );
+    let mut signature = vec![0; key.public().modulus_len()];
+    key.sign(
+        &RSA_PKCS1_SHA256,
+        &SystemRandom::new(),
+        message.as_bytes(),
+        &mut signature,
+    )
+    .unwrap();
+    format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature))
+}
+
+/// Claims of the user, with the `nonce` when it is not None
+fn claims(nonce: Option<&str>) -> Value {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let mut claims = json!({
+        "iss": ISSUER,
+        "sub": "user-1",
+        "aud": "test-client",
+        "exp": now + 300,
+        "iat": now,
+    });
+    if let Some(nonce) = nonce {
+        claims["nonce"] = json!(nonce);
+    }
+    claims
+}
+
+/// Token endpoint that returns an ID token with the claims set by the test
+async fn token_server() -> (CustomOpenIdProvider, IdTokenClaims) {
+    let id_token_claims = IdTokenClaims::default();
+    let app = Router::new()
+        .route(
+            "/token",
+            post(|State(claims): State<IdTokenClaims>| async move {
+                let key = RsaKeyPair::from_der(RSA_KEY).unwrap();
+                let claims = claims.lock().unwrap().clone();
+                Json(json!({
+                    "access_token": "access-token",
+                    "token_type": "bearer",
+                    "id_token": id_token(&key, &claims),
+                }))
+            }),
+        )
+        .with_state(id_token_claims.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let provider = CustomProvider::new(
+        "https://issuer.example.com/authorize".to_string(),
+        format!("http://{addr}/token"),
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        "https://app.example.com/callback".to_string(),
+    );
+    let key = RsaKeyPair::from_der(RSA_KEY).unwrap();
+    (
+        CustomOpenIdProvider::new(provider, ISSUER.to_string(), jwks(&key)),
+        id_token_claims,
+    )
+}
+
+/// Start the login, returning the pending authorization and the nonce sent in the URL
+async fn authorize(
+    provider: &CustomOpenIdProvider,
+) -> (PendingAuthorization<CustomOpenIdProvider>, String) {
+    let pending = provider.authorize(["email"]).await.unwrap();
+    let url = Url::parse(pending.url()).unwrap();
+    let nonce = url
+        .query_pairs()
+        .find(|(name, _)| name == "nonce")
+        .map(|(_, nonce)| nonce.into_owned())
+        .unwrap();
+    (pending, nonce)
+}
+
+#[tokio::test]
+async fn nonce_is_saved_and_checked_in_the_exchange() {
+    let (provider, id_token_claims) = token_server().await;
+    let (pending, nonce) = authorize(&provider).await;
+    assert_eq!(pending.clone().into_state().nonce(), Some(nonce.as_str()));
+    // Every URL has a new nonce
+    let (_, other_nonce) = authorize(&provider).await;
+    assert_ne!(nonce, other_nonce);
+
+    *id_token_claims.lock().unwrap() = claims(Some(&nonce));
+    let (token, claims) = provider
+        .generate_token_with_claims("code".to_string(), &pending.clone().into_state())
+        .await
+        .unwrap();
+    assert_eq!(token.access_token, "access-token");
+    assert_eq!(claims.nonce, Some(nonce));
+    assert!(pending.exchange("code".to_string()).await.is_ok());
+}
+
+#[tokio::test]
+async fn missing_or_other_nonce_is_rejected() {
+    let (provider, id_token_claims) = token_server().await;
+    for nonce in [None, Some("other-nonce")] {
+        let (pending, _) = authorize(&provider).await;
+        *id_token_claims.lock().unwrap() = claims(nonce);
+        assert!(
+            matches!(
+                pending.exchange("code".to_string()).await,
+                Err(OauthError::InvalidIdToken)
+            ),
+            "{nonce:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn exchange_without_the_state_is_not_supported() {
+    let (provider, id_token_claims) = token_server().await;
+    let (pending, nonce) = authorize(&provider).await;
+    *id_token_claims.lock().unwrap() = claims(Some(&nonce));
+
+    // The nonce of the state is required
+    let saved = pending.into_state();
+    let state =
+        StateAuth::restore(saved.state().to_string(), saved.verifier().to_string()).unwrap();
+    assert!(matches!(
+        provider
+            .generate_token_with_claims("code".to_string(), &state)
+            .await,
+        Err(OauthError::InvalidIdToken)
+    ));
+    #[allow(deprecated)]
+    let result = provider
+        .generate_token_set("code".to_string(), state.verifier().to_string())
+        .await;
+    assert!(matches!(result, Err(OauthError::UnsupportedFlow)));
+}
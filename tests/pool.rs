@@ -0,0 +1,176 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use oauth2::url::Url;
+use oauth_axum::error::OauthError;
+use oauth_axum::pool::UrlPool;
+use oauth_axum::scopes::Scopes;
+use oauth_axum::store::{MemoryStateStore, PendingState, StateStore, StoredState};
+use oauth_axum::{CustomProvider, StateAuth};
+
+fn provider() -> CustomProvider {
+    CustomProvider::new(
+        "https://auth.example.com/authorize".to_string(),
+        "https://auth.example.com/token".to_string(),
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        "https://app.example.com/callback".to_string(),
+    )
+}
+
+fn pool(store: &MemoryStateStore) -> UrlPool {
+    UrlPool::new(
+        "test",
+        provider(),
+        Arc::new(store.clone()),
+        Scopes::from(["read"]),
+    )
+    .set_size(4)
+}
+
+/// State sent in the URL
+fn url_state(url: &str) -> String {
+    Url::parse(url)
+        .unwrap()
+        .query_pairs()
+        .find(|(name, _)| name == "state")
+        .map(|(_, value)| value.into_owned())
+        .unwrap()
+}
+
+async fn pending(store: &MemoryStateStore) -> usize {
+    store.stats().await.unwrap().count
+}
+
+#[tokio::test]
+async fn refill_generates_the_missing_urls() {
+    let store = MemoryStateStore::default();
+    let pool = pool(&store);
+    assert_eq!(pool.refill().await.unwrap(), 4);
+    assert_eq!(pool.len().unwrap(), 4);
+    assert_eq!(pending(&store).await, 4);
+
+    pool.next_url().await.unwrap();
+    assert_eq!(pool.refill().await.unwrap(), 1);
+    assert_eq!(pool.refill().await.unwrap(), 0);
+    assert_eq!(pool.len().unwrap(), 4);
+    assert_eq!(pending(&store).await, 5);
+}
+
+/// Store that takes some time to save, so the refills run at the same time
+struct SlowStore(MemoryStateStore);
+
+#[async_trait]
+impl StateStore for SlowStore {
+    async fn save(&self, provider: &str, state: StateAuth) -> Result<(), OauthError> {
+        self.0.save(provider, state).await
+    }
+
+    async fn save_many(&self, provider: &str, states: Vec<StateAuth>) -> Result<(), OauthError> {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        self.0.save_many(provider, states).await
+    }
+
+    async fn take(&self, state: &str) -> Result<Option<StoredState>, OauthError> {
+        self.0.take(state).await
+    }
+
+    async fn list_pending(&self) -> Result<Vec<PendingState>, OauthError> {
+        self.0.list_pending().await
+    }
+}
+
+#[tokio::test]
+async fn concurrent_refills_do_not_overfill() {
+    let store = MemoryStateStore::default();
+    let pool = UrlPool::new(
+        "test",
+        provider(),
+        Arc::new(SlowStore(store.clone())),
+        Scopes::from(["read"]),
+    )
+    .set_size(4);
+    let clone = pool.clone();
+    let (first, second) = tokio::join!(pool.refill(), clone.refill());
+    assert_eq!(first.unwrap() + second.unwrap(), 4);
+    assert_eq!(pool.len().unwrap(), 4);
+    assert_eq!(pending(&store).await, 4);
+}
+
+#[tokio::test]
+async fn next_url_takes_the_pooled_url() {
+    let store = MemoryStateStore::default();
+    let pool = pool(&store);
+    pool.refill().await.unwrap();
+
+    let url = pool.next_url().await.unwrap();
+    assert_eq!(pool.len().unwrap(), 3);
+    assert_eq!(pending(&store).await, 4);
+    let stored = store.take_for("test", &url_state(&url)).await.unwrap();
+    assert_eq!(stored.unwrap().state.url_generated(), Some(url.as_str()));
+}
+
+#[tokio::test]
+async fn empty_pool_generates_the_url() {
+    let store = MemoryStateStore::default();
+    let pool = pool(&store);
+    assert!(pool.is_empty().unwrap());
+
+    let url = pool.next_url().await.unwrap();
+    assert!(pool.is_empty().unwrap());
+    assert!(url.starts_with("https://auth.example.com/authorize?"));
+    assert!(store
+        .take_for("test", &url_state(&url))
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn expired_urls_are_not_used() {
+    let store = MemoryStateStore::default();
+    let pool = pool(&store).set_max_age(Duration::from_millis(50));
+    pool.refill().await.unwrap();
+    let old: Vec<String> = store
+        .list_pending()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|item| item.state_hash)
+        .collect();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // The expired URLs are removed from the pool and their states from the store
+    assert_eq!(pool.refill().await.unwrap(), 4);
+    let pending = store.list_pending().await.unwrap();
+    assert_eq!(pending.len(), 4);
+    assert!(pending.iter().all(|item| !old.contains(&item.state_hash)));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let url = pool.next_url().await.unwrap();
+    assert!(pool.is_empty().unwrap());
+    assert!(store
+        .take_for("test", &url_state(&url))
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn invalidate_removes_the_states() {
+    let store = MemoryStateStore::default();
+    let pool = pool(&store);
+    pool.refill().await.unwrap();
+    let url = pool.next_url().await.unwrap();
+
+    pool.invalidate().await.unwrap();
+    assert!(pool.is_empty().unwrap());
+    // Only the URL already taken is still valid
+    assert_eq!(pending(&store).await, 1);
+    assert!(store
+        .take_for("test", &url_state(&url))
+        .await
+        .unwrap()
+        .is_some());
+}
@@ -0,0 +1,382 @@
+use oauth2::url::Url;
+use oauth_axum::error::OauthError;
+use oauth_axum::providers::basecamp::BasecampProvider;
+use oauth_axum::providers::cognito::{CognitoConfig, CognitoProvider};
+use oauth_axum::providers::deezer::DeezerProvider;
+use oauth_axum::providers::github::GithubProvider;
+use oauth_axum::providers::google::GoogleProvider;
+use oauth_axum::providers::linear::LinearProvider;
+use oauth_axum::providers::microsoft::{MicrosoftProvider, Tenant};
+use oauth_axum::providers::pingone::{PingOneConfig, PingOneProvider, Region};
+use oauth_axum::providers::pocket::PocketProvider;
+use oauth_axum::providers::spotify::SpotifyProvider;
+use oauth_axum::providers::tiktok::TikTokProvider;
+use oauth_axum::providers::todoist::TodoistProvider;
+use oauth_axum::providers::twitter::TwitterProvider;
+use oauth_axum::providers::wrike::WrikeProvider;
+use oauth_axum::providers::zitadel::ZitadelProvider;
+use oauth_axum::providers::zoho::{Datacenter, ZohoProvider};
+use oauth_axum::providers::{Domain, PresetProvider, ProviderPreset};
+use oauth_axum::quirks::{QueryEncoding, Quirks};
+use oauth_axum::scopes::Scopes;
+use oauth_axum::template::ProviderTemplate;
+use oauth_axum::token::TokenSet;
+use oauth_axum::{CustomProvider, OAuthClient};
+
+const REDIRECT_URL: &str = "https://app.example.com/callback";
+
+fn provider<P: ProviderPreset<Provider = CustomProvider>>(config: &P::Config) -> CustomProvider {
+    P::create(
+        config,
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        REDIRECT_URL.to_string(),
+    )
+    .unwrap()
+}
+
+async fn authorize_url(provider: CustomProvider, scopes: impl Into<Scopes> + Send) -> Url {
+    let provider = provider.generate_url(scopes, |_| async {}).await.unwrap();
+    Url::parse(provider.state.unwrap().url_generated().unwrap()).unwrap()
+}
+
+fn param(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+/// The authorization URL of RFC 6749 section 4.1.1 with the PKCE challenge of RFC 7636 section 4.3
+fn assert_standard_params(url: &Url, client_id_param: &str) {
+    assert_eq!(param(url, "response_type").as_deref(), Some("code"));
+    assert_eq!(param(url, client_id_param).as_deref(), Some("test-client"));
+    assert_eq!(param(url, "redirect_uri").as_deref(), Some(REDIRECT_URL));
+    assert!(param(url, "state").is_some_and(|state| !state.is_empty()));
+    assert_eq!(param(url, "code_challenge_method").as_deref(), Some("S256"));
+    assert_eq!(param(url, "code_challenge").map(|c| c.len()), Some(43));
+}
+
+fn assert_endpoint<P: ProviderPreset>(url: &Url, config: &P::Config) {
+    let (auth_url, _) = P::endpoints(config).unwrap();
+    assert_eq!(&url[..oauth2::url::Position::AfterPath], auth_url);
+}
+
+#[tokio::test]
+async fn github_joins_the_scopes_with_space() {
+    let url = authorize_url(provider::<GithubProvider>(&()), ["read:user", "user:email"]).await;
+    assert_endpoint::<GithubProvider>(&url, &());
+    assert_standard_params(&url, "client_id");
+    assert!(url
+        .query()
+        .unwrap()
+        .contains("scope=read%3Auser+user%3Aemail"));
+}
+
+#[tokio::test]
+async fn google_joins_the_scopes_with_space() {
+    let url = authorize_url(provider::<GoogleProvider>(&()), ["openid", "email"]).await;
+    assert_endpoint::<GoogleProvider>(&url, &());
+    assert_standard_params(&url, "client_id");
+    assert_eq!(param(&url, "scope").as_deref(), Some("openid email"));
+}
+
+#[tokio::test]
+async fn spotify_joins_the_scopes_with_space() {
+    let url = authorize_url(
+        provider::<SpotifyProvider>(&()),
+        ["user-read-email", "user-read-private"],
+    )
+    .await;
+    assert_endpoint::<SpotifyProvider>(&url, &());
+    assert_standard_params(&url, "client_id");
+    assert_eq!(
+        param(&url, "scope").as_deref(),
+        Some("user-read-email user-read-private")
+    );
+}
+
+#[tokio::test]
+async fn tiktok_sends_client_key_and_comma_separated_scopes() {
+    let url = authorize_url(
+        provider::<TikTokProvider>(&()),
+        ["user.info.basic", "video.list"],
+    )
+    .await;
+    assert_endpoint::<TikTokProvider>(&url, &());
+    assert_standard_params(&url, "client_key");
+    assert_eq!(param(&url, "client_id"), None);
+    assert_eq!(
+        param(&url, "scope").as_deref(),
+        Some("user.info.basic,video.list")
+    );
+}
+
+#[tokio::test]
+async fn deezer_sends_app_id_and_perms() {
+    let url = authorize_url(provider::<DeezerProvider>(&()), ["basic_access", "email"]).await;
+    assert_endpoint::<DeezerProvider>(&url, &());
+    assert_standard_params(&url, "app_id");
+    assert_eq!(param(&url, "client_id"), None);
+    assert_eq!(param(&url, "scope"), None);
+    assert_eq!(param(&url, "perms").as_deref(), Some("basic_access,email"));
+}
+
+#[tokio::test]
+async fn linear_requests_read_by_default() {
+    let url = authorize_url(provider::<LinearProvider>(&()), Scopes::new()).await;
+    assert_endpoint::<LinearProvider>(&url, &());
+    assert_standard_params(&url, "client_id");
+    assert_eq!(param(&url, "scope").as_deref(), Some("read"));
+}
+
+#[tokio::test]
+async fn todoist_joins_the_scopes_with_comma() {
+    let url = authorize_url(provider::<TodoistProvider>(&()), ["data:read", "task:add"]).await;
+    assert_endpoint::<TodoistProvider>(&url, &());
+    assert_standard_params(&url, "client_id");
+    assert_eq!(param(&url, "scope").as_deref(), Some("data:read,task:add"));
+}
+
+#[tokio::test]
+async fn twitter_requests_the_refresh_token_by_default() {
+    let url = authorize_url(provider::<TwitterProvider>(&()), Scopes::new()).await;
+    assert_endpoint::<TwitterProvider>(&url, &());
+    assert_standard_params(&url, "client_id");
+    assert_eq!(
+        param(&url, "scope").as_deref(),
+        Some("tweet.read users.read offline.access")
+    );
+}
+
+#[tokio::test]
+async fn basecamp_sends_the_web_server_type() {
+    let url = authorize_url(provider::<BasecampProvider>(&()), Scopes::new()).await;
+    assert_endpoint::<BasecampProvider>(&url, &());
+    assert_standard_params(&url, "client_id");
+    assert_eq!(param(&url, "type").as_deref(), Some("web_server"));
+}
+
+#[tokio::test]
+async fn microsoft_has_the_tenant_in_the_path() {
+    let tenant = Tenant::Id("contoso.onmicrosoft.com".to_string());
+    let url = authorize_url(provider::<MicrosoftProvider>(&tenant), ["User.Read"]).await;
+    assert_endpoint::<MicrosoftProvider>(&url, &tenant);
+    assert_standard_params(&url, "client_id");
+    assert_eq!(url.path(), "/contoso.onmicrosoft.com/oauth2/v2.0/authorize");
+}
+
+#[tokio::test]
+async fn zoho_uses_the_accounts_server_of_the_datacenter() {
+    let url = authorize_url(
+        provider::<ZohoProvider>(&Datacenter::Eu),
+        ["ZohoCRM.users.READ"],
+    )
+    .await;
+    assert_endpoint::<ZohoProvider>(&url, &Datacenter::Eu);
+    assert_standard_params(&url, "client_id");
+    assert_eq!(url.host_str(), Some("accounts.zoho.eu"));
+}
+
+#[test]
+fn cognito_builds_the_host_of_the_user_pool() {
+    let (auth_url, token_url) = CognitoProvider::endpoints(&CognitoConfig {
+        domain: "my-app".to_string(),
+        region: Some("us-east-1".to_string()),
+    })
+    .unwrap();
+    assert_eq!(
+        auth_url,
+        "https://my-app.auth.us-east-1.amazoncognito.com/oauth2/authorize"
+    );
+    assert_eq!(
+        token_url,
+        "https://my-app.auth.us-east-1.amazoncognito.com/oauth2/token"
+    );
+
+    let (auth_url, _) = CognitoProvider::endpoints(&CognitoConfig {
+        domain: "auth.example.com".to_string(),
+        region: None,
+    })
+    .unwrap();
+    assert_eq!(auth_url, "https://auth.example.com/oauth2/authorize");
+}
+
+#[test]
+fn pingone_builds_the_url_of_the_environment() {
+    let (auth_url, _) = PingOneProvider::endpoints(&PingOneConfig {
+        environment_id: "4f5e2c3a-1b2c-4d5e-8f90-123456789abc".to_string(),
+        region: Region::Europe,
+    })
+    .unwrap();
+    assert_eq!(
+        auth_url,
+        "https://auth.pingone.eu/4f5e2c3a-1b2c-4d5e-8f90-123456789abc/as/authorize"
+    );
+}
+
+#[test]
+fn presets_reject_values_that_change_the_host() {
+    for domain in ["evil.com\\", "evil.com:8080", "evil.com/x", "a b", ""] {
+        let result = CognitoProvider::endpoints(&CognitoConfig {
+            domain: domain.to_string(),
+            region: Some("us-east-1".to_string()),
+        });
+        assert!(matches!(result, Err(OauthError::InvalidConfig)), "{domain}");
+        let result = ZitadelProvider::endpoints(&Domain(domain.to_string()));
+        assert!(matches!(result, Err(OauthError::InvalidConfig)), "{domain}");
+    }
+}
+
+#[test]
+fn templates_reject_values_that_change_the_url() {
+    for value in [
+        "..", ".", "...", "", "a/b", "a\\b", "a?b", "a#b", "a%2e", "a:b", "a@b",
+    ] {
+        let result = MicrosoftProvider::template()
+            .tenant(value)
+            .fill("https://login.microsoftonline.com/{tenant}/oauth2/v2.0/authorize");
+        assert!(matches!(result, Err(OauthError::InvalidConfig)), "{value}");
+        let result = MicrosoftProvider::create(
+            &Tenant::Id(value.to_string()),
+            "test-client".to_string(),
+            "test-secret".to_string(),
+            REDIRECT_URL.to_string(),
+        );
+        assert!(matches!(result, Err(OauthError::InvalidConfig)), "{value}");
+        let result = MicrosoftProvider::new(
+            Tenant::from(value.to_string()),
+            "test-client".to_string(),
+            "test-secret".to_string(),
+            REDIRECT_URL.to_string(),
+        );
+        assert!(matches!(result, Err(OauthError::InvalidConfig)), "{value}");
+        let result = ProviderTemplate::fill_value("https://{host}/api/v4", "host", value);
+        assert!(matches!(result, Err(OauthError::InvalidConfig)), "{value}");
+    }
+    assert_eq!(
+        ProviderTemplate::fill_value("https://{host}/api/v4", "host", "app-eu.wrike.com").unwrap(),
+        "https://app-eu.wrike.com/api/v4"
+    );
+    let url = ProviderTemplate::new("https://{shop}.example.com/authorize", "")
+        .shop("my-shop.v2")
+        .fill("https://{shop}.example.com/authorize")
+        .unwrap();
+    assert_eq!(url, "https://my-shop.v2.example.com/authorize");
+}
+
+#[tokio::test]
+async fn percent_encoding_dedupe_and_order() {
+    let provider = CustomProvider::new(
+        "https://auth.example.com/authorize".to_string(),
+        "https://auth.example.com/token".to_string(),
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        REDIRECT_URL.to_string(),
+    )
+    .set_auth_param("prompt", "login")
+    .set_quirks(
+        Quirks::new()
+            .auth_param("prompt", "consent")
+            .query_encoding(QueryEncoding::Percent)
+            .dedupe_params()
+            .param_order(&["response_type", "client_id", "redirect_uri"]),
+    );
+    let url = authorize_url(provider, ["openid", "email"]).await;
+    assert_standard_params(&url, "client_id");
+    let query = url.query().unwrap();
+    assert!(query.starts_with("response_type=code&client_id=test-client&redirect_uri="));
+    assert!(query.contains("scope=openid%20email"));
+    assert!(!query.contains('+'));
+    assert_eq!(query.matches("prompt=").count(), 1);
+    assert_eq!(param(&url, "prompt").as_deref(), Some("consent"));
+}
+
+#[tokio::test]
+async fn pocket_uses_the_consumer_key_and_no_oauth2_client() {
+    let provider = PocketProvider::create(
+        &(),
+        "consumer-key".to_string(),
+        String::new(),
+        REDIRECT_URL.to_string(),
+    )
+    .unwrap();
+    assert_eq!(PocketProvider::NAME, "pocket");
+    assert_eq!(provider.consumer_key(), "consumer-key");
+    assert_eq!(provider.redirect_url(), REDIRECT_URL);
+    assert_eq!(provider.auth_url(), "https://getpocket.com/auth/authorize");
+    assert_eq!(
+        provider.token_url(),
+        "https://getpocket.com/v3/oauth/authorize"
+    );
+    assert!(provider.default_scopes().is_empty());
+    assert!(matches!(
+        provider.get_client(),
+        Err(OauthError::UnsupportedFlow)
+    ));
+    assert!(matches!(
+        provider.refresh_token("refresh".to_string()).await,
+        Err(OauthError::UnsupportedFlow)
+    ));
+}
+
+#[test]
+fn wrike_uses_the_host_of_the_token() {
+    let token = |host: Option<&str>| TokenSet {
+        access_token: "access-token".to_string(),
+        token_type: "bearer".to_string(),
+        refresh_token: None,
+        expires_in: None,
+        scopes: None,
+        extra: host
+            .map(|host| ("host".to_string(), host.into()))
+            .into_iter()
+            .collect(),
+    };
+    assert_eq!(
+        WrikeProvider::api_base_url(&token(Some("app-eu.wrike.com"))).unwrap(),
+        "https://app-eu.wrike.com/api/v4"
+    );
+    let provider = WrikeProvider::for_host(
+        "app-eu.wrike.com",
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        REDIRECT_URL.to_string(),
+    )
+    .unwrap();
+    assert_eq!(
+        provider.token_url(),
+        "https://app-eu.wrike.com/oauth2/token"
+    );
+
+    for host in [
+        "evil.com/",
+        "evil.com:8080",
+        "user@evil.com",
+        "evil.com?",
+        "..",
+    ] {
+        assert!(
+            matches!(
+                WrikeProvider::api_base_url(&token(Some(host))),
+                Err(OauthError::InvalidConfig)
+            ),
+            "{host}"
+        );
+        assert!(
+            matches!(
+                WrikeProvider::for_host(
+                    host,
+                    "test-client".to_string(),
+                    "test-secret".to_string(),
+                    REDIRECT_URL.to_string(),
+                ),
+                Err(OauthError::InvalidConfig)
+            ),
+            "{host}"
+        );
+    }
+    assert!(matches!(
+        WrikeProvider::api_base_url(&token(None)),
+        Err(OauthError::InvalidConfig)
+    ));
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::http::header;
+use axum::routing::post;
+use axum::Router;
+use oauth2::url::{form_urlencoded, Url};
+use oauth_axum::quirks::{QueryEncoding, Quirks, TokenResponseFormat};
+use oauth_axum::token::TokenSet;
+use oauth_axum::{AuthType, CustomProvider, OAuthClient};
+use serde_json::Value;
+
+type Bodies = Arc<Mutex<Vec<Vec<(String, String)>>>>;
+
+/// Token endpoint that returns the raw response and keeps the form bodies of the requests
+async fn token_server(content_type: &'static str, response: &'static str) -> (String, Bodies) {
+    let bodies = Bodies::default();
+    let app = Router::new()
+        .route(
+            "/token",
+            post(
+                move |State(bodies): State<Bodies>, body: String| async move {
+                    let pairs = form_urlencoded::parse(body.as_bytes())
+                        .into_owned()
+                        .collect();
+                    bodies.lock().unwrap().push(pairs);
+                    ([(header::CONTENT_TYPE, content_type)], response)
+                },
+            ),
+        )
+        .with_state(bodies.clone());
+    (common::serve(app).await, bodies)
+}
+
+fn provider(token_url: &str, quirks: Quirks) -> CustomProvider {
+    common::provider(token_url).set_quirks(quirks)
+}
+
+async fn exchange(provider: CustomProvider) -> TokenSet {
+    common::exchange(provider, "code").await.unwrap()
+}
+
+fn value<'a>(body: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    body.iter()
+        .find(|(other, _)| other == name)
+        .map(|(_, value)| value.as_str())
+}
+
+#[tokio::test]
+async fn rename_field_reads_the_provider_names() {
+    let (token_url, _) = token_server(
+        "application/json",
+        r#"{"accessToken":"access-token","token_type":"bearer","expires":"3600"}"#,
+    )
+    .await;
+    let quirks = Quirks::new()
+        .rename_field("accessToken", "access_token")
+        .rename_field("expires", "expires_in");
+    let token = exchange(provider(&token_url, quirks)).await;
+    assert_eq!(token.access_token, "access-token");
+    assert_eq!(token.expires_in, Some(3600));
+    assert!(!token.extra.contains_key("accessToken"));
+}
+
+#[tokio::test]
+async fn rename_field_keeps_the_standard_field() {
+    let (token_url, _) = token_server(
+        "application/json",
+        r#"{"access_token":"standard","token":"provider","token_type":"bearer"}"#,
+    )
+    .await;
+    let quirks = Quirks::new().rename_field("token", "access_token");
+    let token = exchange(provider(&token_url, quirks)).await;
+    assert_eq!(token.access_token, "standard");
+}
+
+#[tokio::test]
+async fn map_response_changes_the_raw_json() {
+    let (token_url, _) = token_server(
+        "application/json",
+        r#"{"data":{"access_token":"access-token","expires_in":60},"status":"ok"}"#,
+    )
+    .await;
+    let quirks = Quirks::new().map_response(|fields| {
+        if let Some(Value::Object(data)) = fields.remove("data") {
+            fields.extend(data);
+        }
+        fields.insert("token_type".to_string(), Value::from("bearer"));
+    });
+    let token = exchange(provider(&token_url, quirks)).await;
+    assert_eq!(token.access_token, "access-token");
+    assert_eq!(token.expires_in, Some(60));
+    assert_eq!(token.extra_str("status"), Some("ok"));
+}
+
+#[tokio::test]
+async fn form_response_is_converted() {
+    let (token_url, _) = token_server(
+        "application/x-www-form-urlencoded",
+        "access_token=access-token&expires_in=120&scope=read",
+    )
+    .await;
+    let quirks = Quirks::new().token_response_format(TokenResponseFormat::FormUrlEncoded);
+    let token = exchange(provider(&token_url, quirks)).await;
+    assert_eq!(token.access_token, "access-token");
+    assert_eq!(token.token_type, "bearer");
+    assert_eq!(token.expires_in, Some(120));
+}
+
+#[tokio::test]
+async fn token_params_are_only_in_the_code_exchange() {
+    let (token_url, bodies) = token_server(
+        "application/json",
+        r#"{"access_token":"access-token","token_type":"bearer"}"#,
+    )
+    .await;
+    let quirks = Quirks::new()
+        .token_param("action", "exchange")
+        .refresh_param("action", "refresh")
+        .rename_param("client_id", "client_key");
+    let provider = provider(&token_url, quirks).set_auth_type(AuthType::RequestBody);
+    exchange(provider.clone()).await;
+    provider
+        .refresh_token("refresh-token".to_string())
+        .await
+        .unwrap();
+
+    let bodies = bodies.lock().unwrap();
+    assert_eq!(bodies.len(), 2);
+    let (code, refresh) = (&bodies[0], &bodies[1]);
+    assert_eq!(value(code, "grant_type"), Some("authorization_code"));
+    assert_eq!(value(code, "action"), Some("exchange"));
+    assert_eq!(value(code, "client_key"), Some("test-client"));
+    assert_eq!(value(code, "client_id"), None);
+    assert_eq!(value(code, "code"), Some("code"));
+    assert_eq!(value(refresh, "grant_type"), Some("refresh_token"));
+    assert_eq!(value(refresh, "action"), Some("refresh"));
+    assert_eq!(value(refresh, "client_key"), Some("test-client"));
+    assert_eq!(value(refresh, "refresh_token"), Some("refresh-token"));
+}
+
+#[tokio::test]
+async fn auth_params_are_in_the_url() {
+    let quirks = Quirks::new()
+        .auth_param("prompt", "consent")
+        .auth_param("prompt", "login")
+        .rename_param("client_id", "client_key")
+        .query_encoding(QueryEncoding::Percent)
+        .dedupe_params()
+        .param_order(&["client_key", "response_type"]);
+    let pending = provider("https://auth.example.com/token", quirks)
+        .authorize(["openid", "email"])
+        .await
+        .unwrap();
+    let url = Url::parse(pending.url()).unwrap();
+    let query = url.query().unwrap();
+    assert!(query.starts_with("client_key=test-client&response_type=code"));
+    assert!(query.contains("scope=openid%20email"));
+    assert!(!query.contains('+'));
+    let prompts: Vec<_> = url
+        .query_pairs()
+        .filter(|(name, _)| name == "prompt")
+        .map(|(_, value)| value.into_owned())
+        .collect();
+    assert_eq!(prompts, ["login"]);
+}
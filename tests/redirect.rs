@@ -0,0 +1,159 @@
+use oauth_axum::error::OauthError;
+use oauth_axum::redirect::RedirectPolicy;
+
+fn policy() -> RedirectPolicy {
+    RedirectPolicy::new().allow_host("app.example.com")
+}
+
+#[test]
+fn return_to_accepts_relative_paths() {
+    let policy = RedirectPolicy::new();
+    for return_to in ["/", "/dashboard", "/settings?tab=profile", "/a/b#section"] {
+        assert_eq!(policy.validate_return_to(return_to).unwrap(), return_to);
+    }
+}
+
+#[test]
+fn return_to_rejects_protocol_relative_urls() {
+    for return_to in ["//evil.com", "//evil.com/dashboard", "///evil.com"] {
+        assert!(
+            matches!(
+                policy().validate_return_to(return_to),
+                Err(OauthError::UnsafeRedirectUrl)
+            ),
+            "{return_to}"
+        );
+    }
+}
+
+#[test]
+fn return_to_rejects_backslash_variants() {
+    for return_to in [
+        "/\\evil.com",
+        "\\\\evil.com",
+        "\\/evil.com",
+        "https://evil.com\\@app.example.com",
+        "https:\\\\evil.com",
+    ] {
+        assert!(
+            matches!(
+                policy().validate_return_to(return_to),
+                Err(OauthError::UnsafeRedirectUrl)
+            ),
+            "{return_to}"
+        );
+    }
+}
+
+#[test]
+fn return_to_rejects_control_characters() {
+    for return_to in ["/\t/evil.com", "/\n/evil.com", "/\r/evil.com"] {
+        assert!(
+            matches!(
+                policy().validate_return_to(return_to),
+                Err(OauthError::UnsafeRedirectUrl)
+            ),
+            "{return_to:?}"
+        );
+    }
+}
+
+#[test]
+fn return_to_rejects_hosts_not_in_the_allowlist() {
+    for return_to in [
+        "https://evil.com/dashboard",
+        "https://app.example.com.evil.com/",
+        "https://evil.com/app.example.com",
+        "http://app.example.com/dashboard",
+        "javascript:alert(1)",
+        "data:text/html,hi",
+    ] {
+        assert!(
+            matches!(
+                policy().validate_return_to(return_to),
+                Err(OauthError::UnsafeRedirectUrl)
+            ),
+            "{return_to}"
+        );
+    }
+}
+
+#[test]
+fn return_to_rejects_absolute_urls_without_allowlist() {
+    assert!(matches!(
+        RedirectPolicy::new().validate_return_to("https://app.example.com/dashboard"),
+        Err(OauthError::UnsafeRedirectUrl)
+    ));
+}
+
+#[test]
+fn return_to_accepts_allowed_hosts() {
+    assert_eq!(
+        policy()
+            .validate_return_to("https://APP.example.com/dashboard")
+            .unwrap(),
+        "https://app.example.com/dashboard"
+    );
+}
+
+#[test]
+fn return_to_keeps_encoded_forms_on_the_same_host() {
+    // The browsers don't decode the path before resolving it, so these stay on the same host
+    for return_to in ["/%2F%2Fevil.com", "/%5Cevil.com", "/%2f%2fevil.com"] {
+        assert_eq!(policy().validate_return_to(return_to).unwrap(), return_to);
+    }
+    for return_to in [
+        "%2F%2Fevil.com",
+        "%5C%5Cevil.com",
+        "https%3A%2F%2Fevil.com",
+        "https://evil.com%2F@evil.com/",
+    ] {
+        assert!(
+            matches!(
+                policy().validate_return_to(return_to),
+                Err(OauthError::UnsafeRedirectUrl)
+            ),
+            "{return_to}"
+        );
+    }
+}
+
+#[test]
+fn redirect_url_rejects_unsafe_urls() {
+    for redirect_url in [
+        "http://app.example.com/callback",
+        "https://app.example.com/callback#fragment",
+        "https://evil.com/callback",
+        "https://app.example.com.evil.com/callback",
+        "/callback",
+        "//app.example.com/callback",
+    ] {
+        assert!(
+            policy().validate_redirect_url(redirect_url).is_err(),
+            "{redirect_url}"
+        );
+    }
+}
+
+#[test]
+fn redirect_url_accepts_localhost_when_allowed() {
+    let policy = RedirectPolicy::new().allow_insecure_localhost(true);
+    for redirect_url in [
+        "http://localhost:3000/callback",
+        "http://127.0.0.1:3000/callback",
+        "http://[::1]:3000/callback",
+    ] {
+        assert!(
+            policy.validate_redirect_url(redirect_url).is_ok(),
+            "{redirect_url}"
+        );
+    }
+    assert!(matches!(
+        policy.validate_redirect_url("http://example.com/callback"),
+        Err(OauthError::UnsafeRedirectUrl)
+    ));
+    assert!(matches!(
+        RedirectPolicy::new().validate_redirect_url("http://localhost:3000/callback"),
+        Err(OauthError::UnsafeRedirectUrl)
+    ));
+}
@@ -0,0 +1,219 @@
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::routing::{any, post};
+use axum::{Json, Router};
+use oauth_axum::error::OauthError;
+use oauth_axum::registration::{register_client, register_client_with_token, ClientMetadata};
+use serde_json::{json, Value};
+
+/// Method, Authorization header and body of every request
+type Requests = Arc<Mutex<Vec<(Method, Option<String>, Value)>>>;
+
+fn authorization(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Registration endpoint that rejects the clients without redirect URIs, and the management endpoint of the client
+async fn registration_server() -> (String, Requests) {
+    let requests = Requests::default();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let client_uri = format!("{base_url}/register/client-1");
+    let app = Router::new()
+        .route(
+            "/register",
+            post(
+                move |State(requests): State<Requests>,
+                      headers: HeaderMap,
+                      Json(body): Json<Value>| async move {
+                    requests.lock().unwrap().push((
+                        Method::POST,
+                        authorization(&headers),
+                        body.clone(),
+                    ));
+                    if body.get("redirect_uris").is_none() {
+                        return (
+                            StatusCode::BAD_REQUEST,
+                            Json(json!({
+                                "error": "invalid_redirect_uri",
+                                "error_description": "redirect_uris is required",
+                            })),
+                        );
+                    }
+                    let mut client = json!({
+                        "client_id": "client-1",
+                        "client_secret": "secret-1",
+                        "client_id_issued_at": 1700000000,
+                        "client_secret_expires_at": 0,
+                        "registration_access_token": "registration-token",
+                        "registration_client_uri": client_uri,
+                    });
+                    client
+                        .as_object_mut()
+                        .unwrap()
+                        .extend(body.as_object().unwrap().clone());
+                    (StatusCode::CREATED, Json(client))
+                },
+            ),
+        )
+        .route(
+            "/register/client-1",
+            any(
+                |State(requests): State<Requests>,
+                 method: Method,
+                 headers: HeaderMap,
+                 body: String| async move {
+                    let token = authorization(&headers);
+                    let body = serde_json::from_str(&body).unwrap_or(Value::Null);
+                    requests
+                        .lock()
+                        .unwrap()
+                        .push((method.clone(), token.clone(), body.clone()));
+                    if token.as_deref() != Some("Bearer registration-token") {
+                        return (
+                            StatusCode::UNAUTHORIZED,
+                            Json(json!({ "error": "invalid_token" })),
+                        );
+                    }
+                    match method {
+                        Method::DELETE => (StatusCode::NO_CONTENT, Json(Value::Null)),
+                        Method::PUT => (StatusCode::OK, Json(body)),
+                        _ => (
+                            StatusCode::OK,
+                            Json(json!({
+                                "client_id": "client-1",
+                                "client_name": "Read",
+                                "redirect_uris": ["https://app.example.com/callback"],
+                            })),
+                        ),
+                    }
+                },
+            ),
+        )
+        .with_state(requests.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (base_url, requests)
+}
+
+fn metadata() -> ClientMetadata {
+    let mut metadata = ClientMetadata {
+        redirect_uris: vec!["https://app.example.com/callback".to_string()],
+        client_name: Some("Customer app".to_string()),
+        grant_types: vec!["authorization_code".to_string()],
+        ..ClientMetadata::default()
+    };
+    metadata
+        .extra
+        .insert("tenant".to_string(), json!("customer-1"));
+    metadata
+}
+
+#[tokio::test]
+async fn register_sends_the_metadata_and_parses_the_client() {
+    let (base_url, requests) = registration_server().await;
+    let client = register_client(&format!("{base_url}/register"), &metadata())
+        .await
+        .unwrap();
+
+    // The empty fields are not sent, the provider would reject or store them
+    let (method, authorization, body) = requests.lock().unwrap()[0].clone();
+    assert_eq!(method, Method::POST);
+    assert_eq!(authorization, None);
+    assert_eq!(
+        body,
+        json!({
+            "redirect_uris": ["https://app.example.com/callback"],
+            "client_name": "Customer app",
+            "grant_types": ["authorization_code"],
+            "tenant": "customer-1",
+        })
+    );
+
+    assert_eq!(client.client_id, "client-1");
+    assert_eq!(client.client_secret.as_deref(), Some("secret-1"));
+    assert_eq!(client.client_id_issued_at, Some(1700000000));
+    assert_eq!(client.client_secret_expires_at, Some(0));
+    assert_eq!(
+        client.registration_client_uri,
+        Some(format!("{base_url}/register/client-1"))
+    );
+    assert_eq!(client.metadata.client_name.as_deref(), Some("Customer app"));
+    assert_eq!(client.metadata.extra["tenant"], json!("customer-1"));
+    // The secrets are masked
+    let debug = format!("{client:?}");
+    assert!(!debug.contains("secret-1") && !debug.contains("registration-token"));
+}
+
+#[tokio::test]
+async fn initial_access_token_is_sent() {
+    let (base_url, requests) = registration_server().await;
+    register_client_with_token(
+        &format!("{base_url}/register"),
+        "initial-token",
+        &metadata(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        requests.lock().unwrap()[0].1.as_deref(),
+        Some("Bearer initial-token")
+    );
+}
+
+#[tokio::test]
+async fn error_response_fails() {
+    let (base_url, _) = registration_server().await;
+    let result = register_client(&format!("{base_url}/register"), &ClientMetadata::default()).await;
+    assert!(matches!(result, Err(OauthError::RegistrationFailed)));
+}
+
+#[tokio::test]
+async fn client_is_managed_with_the_registration_token() {
+    let (base_url, requests) = registration_server().await;
+    let client = register_client(&format!("{base_url}/register"), &metadata())
+        .await
+        .unwrap();
+
+    let read = client.read().await.unwrap();
+    assert_eq!(read.metadata.client_name.as_deref(), Some("Read"));
+
+    let updated_metadata = ClientMetadata {
+        client_name: Some("Renamed".to_string()),
+        ..metadata()
+    };
+    let updated = client.update(&updated_metadata).await.unwrap();
+    assert_eq!(updated.metadata.client_name.as_deref(), Some("Renamed"));
+    client.delete().await.unwrap();
+
+    let requests = requests.lock().unwrap().clone();
+    let methods: Vec<&Method> = requests.iter().map(|(method, _, _)| method).collect();
+    assert_eq!(
+        methods,
+        [&Method::POST, &Method::GET, &Method::PUT, &Method::DELETE]
+    );
+    assert!(requests[1..]
+        .iter()
+        .all(|(_, token, _)| token.as_deref() == Some("Bearer registration-token")));
+    // The update sends the client_id and the new metadata, without the management fields
+    let body = &requests[2].2;
+    assert_eq!(body["client_id"], "client-1");
+    assert_eq!(body["client_name"], "Renamed");
+    assert!(body.get("registration_access_token").is_none());
+
+    let mut other = client.clone();
+    other.registration_access_token = Some("other-token".to_string());
+    assert!(matches!(
+        other.read().await,
+        Err(OauthError::RegistrationFailed)
+    ));
+    other.registration_client_uri = None;
+    assert!(matches!(
+        other.delete().await,
+        Err(OauthError::RegistrationManagementNotSupported)
+    ));
+}
@@ -0,0 +1,262 @@
+use oauth_axum::error::OauthError;
+use oauth_axum::registry::ProviderRegistry;
+use oauth_axum::scopes::Scopes;
+
+/// Config with a github provider, the credentials read from the variables with the `prefix`
+fn json_config(prefix: &str) -> String {
+    format!(
+        r#"{{
+            "providers": {{
+                "github": {{
+                    "kind": "github",
+                    "client_id": "${{{prefix}_CLIENT_ID}}",
+                    "client_secret": "${{{prefix}_SECRET}}",
+                    "redirect_url": "https://app.example.com/auth/${{{prefix}_CLIENT_ID}}/callback",
+                    "scopes": ["read:user"]
+                }},
+                "zoho": {{
+                    "kind": "zoho",
+                    "client_id": "zoho-client",
+                    "client_secret": "zoho-secret",
+                    "redirect_url": "https://app.example.com/callback",
+                    "datacenter": "eu"
+                }}
+            }}
+        }}"#
+    )
+}
+
+fn set_credentials(prefix: &str) {
+    std::env::set_var(format!("{prefix}_CLIENT_ID"), "github-client");
+    std::env::set_var(format!("{prefix}_SECRET"), "github-secret");
+}
+
+fn assert_registry(registry: &ProviderRegistry) {
+    let mut names: Vec<&str> = registry.names().collect();
+    names.sort();
+    assert_eq!(names, ["github", "zoho"]);
+
+    let github = registry.get("github").unwrap();
+    assert_eq!(github.client_id(), "github-client");
+    assert_eq!(
+        github.redirect_url(),
+        "https://app.example.com/auth/github-client/callback"
+    );
+    assert_eq!(
+        github.auth_url(),
+        "https://github.com/login/oauth/authorize"
+    );
+    assert_eq!(
+        registry.scopes("github"),
+        Some(&Scopes::from(["read:user"]))
+    );
+
+    let zoho = registry.get("zoho").unwrap();
+    assert_eq!(zoho.auth_url(), "https://accounts.zoho.eu/oauth/v2/auth");
+    assert_eq!(registry.scopes("zoho"), Some(&Scopes::new()));
+}
+
+fn is_invalid_config<T>(result: Result<T, OauthError>) -> bool {
+    matches!(result, Err(OauthError::InvalidConfig))
+}
+
+#[test]
+fn json_config_is_loaded() {
+    set_credentials("REGISTRY_JSON");
+    let registry = ProviderRegistry::from_config(&json_config("REGISTRY_JSON")).unwrap();
+    assert_registry(&registry);
+}
+
+#[test]
+fn config_file_is_loaded() {
+    set_credentials("REGISTRY_FILE");
+    let path = std::env::temp_dir().join("oauth-axum-registry-test.json");
+    std::fs::write(&path, json_config("REGISTRY_FILE")).unwrap();
+    let registry = ProviderRegistry::from_config(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_registry(&registry);
+
+    assert!(matches!(
+        ProviderRegistry::from_config("/nonexistent/providers.json"),
+        Err(OauthError::ConfigReadFailed)
+    ));
+}
+
+#[test]
+fn missing_variable_is_an_error() {
+    std::env::remove_var("REGISTRY_MISSING_CLIENT_ID");
+    std::env::set_var("REGISTRY_MISSING_SECRET", "github-secret");
+    assert!(is_invalid_config(ProviderRegistry::from_config(
+        &json_config("REGISTRY_MISSING")
+    )));
+}
+
+#[test]
+fn unclosed_variable_is_an_error() {
+    let config = r#"{
+        "providers": {
+            "github": {
+                "kind": "github",
+                "client_id": "${REGISTRY_UNCLOSED",
+                "client_secret": "secret",
+                "redirect_url": "https://app.example.com/callback"
+            }
+        }
+    }"#;
+    assert!(is_invalid_config(ProviderRegistry::from_json(config)));
+}
+
+#[test]
+fn unknown_kind_is_an_error() {
+    let config = r#"{
+        "providers": {
+            "other": {
+                "kind": "not-a-preset",
+                "client_id": "client",
+                "client_secret": "secret",
+                "redirect_url": "https://app.example.com/callback"
+            }
+        }
+    }"#;
+    assert!(is_invalid_config(ProviderRegistry::from_json(config)));
+}
+
+#[test]
+fn invalid_preset_fields_are_an_error() {
+    let preset = |fields: &str| {
+        format!(
+            r#"{{
+                "providers": {{
+                    "provider": {{
+                        "client_id": "client",
+                        "client_secret": "secret",
+                        "redirect_url": "https://app.example.com/callback",
+                        {fields}
+                    }}
+                }}
+            }}"#
+        )
+    };
+    // Unknown values of the preset config
+    assert!(is_invalid_config(ProviderRegistry::from_json(&preset(
+        r#""kind": "zoho", "datacenter": "mars""#
+    ))));
+    assert!(is_invalid_config(ProviderRegistry::from_json(&preset(
+        r#""kind": "paypal", "environment": "staging""#
+    ))));
+    // Required fields of the preset
+    assert!(is_invalid_config(ProviderRegistry::from_json(&preset(
+        r#""kind": "gitea""#
+    ))));
+    assert!(is_invalid_config(ProviderRegistry::from_json(&preset(
+        r#""kind": "custom", "auth_url": "https://auth.example.com/authorize""#
+    ))));
+
+    let registry = ProviderRegistry::from_json(&preset(
+        r#""kind": "gitea", "base_url": "https://git.example.com""#,
+    ))
+    .unwrap();
+    assert!(registry
+        .get("provider")
+        .unwrap()
+        .auth_url()
+        .starts_with("https://git.example.com/"));
+}
+
+#[test]
+fn invalid_document_is_an_error() {
+    assert!(is_invalid_config(ProviderRegistry::from_json(
+        r#"{ "providers": { "github": { "kind": "github" } } }"#
+    )));
+    assert!(is_invalid_config(ProviderRegistry::from_config(
+        "{ not json"
+    )));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml_config_is_loaded() {
+    set_credentials("REGISTRY_TOML");
+    let config = r#"
+        [providers.github]
+        kind = "github"
+        client_id = "${REGISTRY_TOML_CLIENT_ID}"
+        client_secret = "${REGISTRY_TOML_SECRET}"
+        redirect_url = "https://app.example.com/auth/${REGISTRY_TOML_CLIENT_ID}/callback"
+        scopes = ["read:user"]
+
+        [providers.zoho]
+        kind = "zoho"
+        client_id = "zoho-client"
+        client_secret = "zoho-secret"
+        redirect_url = "https://app.example.com/callback"
+        datacenter = "eu"
+    "#;
+    assert_registry(&ProviderRegistry::from_toml(config).unwrap());
+
+    let path = std::env::temp_dir().join("oauth-axum-registry-test.toml");
+    std::fs::write(&path, config).unwrap();
+    let registry = ProviderRegistry::from_config(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_registry(&registry);
+
+    assert!(is_invalid_config(ProviderRegistry::from_toml(
+        "[providers.github]\nkind = 1"
+    )));
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml_config_is_loaded() {
+    set_credentials("REGISTRY_YAML");
+    let config = r#"
+providers:
+  github:
+    kind: github
+    client_id: "${REGISTRY_YAML_CLIENT_ID}"
+    client_secret: "${REGISTRY_YAML_SECRET}"
+    redirect_url: "https://app.example.com/auth/${REGISTRY_YAML_CLIENT_ID}/callback"
+    scopes:
+      - "read:user"
+  zoho:
+    kind: zoho
+    client_id: zoho-client
+    client_secret: zoho-secret
+    redirect_url: "https://app.example.com/callback"
+    datacenter: eu
+"#;
+    assert_registry(&ProviderRegistry::from_yaml(config).unwrap());
+
+    let path = std::env::temp_dir().join("oauth-axum-registry-test.yml");
+    std::fs::write(&path, config).unwrap();
+    let registry = ProviderRegistry::from_config(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_registry(&registry);
+
+    assert!(is_invalid_config(ProviderRegistry::from_yaml(
+        "providers:\n  github: [1, 2]"
+    )));
+}
+
+#[test]
+fn pocket_is_loaded_apart_from_the_oauth2_providers() {
+    let registry = ProviderRegistry::from_config(
+        r#"{
+            "providers": {
+                "pocket": {
+                    "kind": "pocket",
+                    "client_id": "consumer-key",
+                    "client_secret": "",
+                    "redirect_url": "https://app.example.com/callback"
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(registry.names().collect::<Vec<_>>(), ["pocket"]);
+    let pocket = registry.get_pocket("pocket").unwrap();
+    assert_eq!(pocket.consumer_key(), "consumer-key");
+    assert_eq!(pocket.redirect_url(), "https://app.example.com/callback");
+    // Pocket can't be used where an OAuth2 provider is expected
+    assert!(registry.get("pocket").is_none());
+}
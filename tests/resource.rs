@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{header, Request, StatusCode};
+use axum::middleware;
+use axum::routing::{get, post};
+use axum::{Extension, Form, Json, Router};
+use oauth_axum::error::OauthError;
+use oauth_axum::resource::{scope_guard, BearerClaims, BearerValidator, RequireScope};
+use oauth_axum::scope;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Introspection endpoint that counts the requests, the tokens that start with `active` are active
+async fn introspection_server() -> (String, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/introspect",
+            post(
+                |State(requests): State<Arc<AtomicUsize>>,
+                 Form(form): Form<HashMap<String, String>>| async move {
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    let token = &form["token"];
+                    if !token.starts_with("active") {
+                        return Json::<Value>(json!({ "active": false }));
+                    }
+                    // `active-now` expires now, so its result is not cached, the others expire in 1 hour
+                    let exp = match token.as_str() {
+                        "active-now" => now(),
+                        _ => now() + 3600,
+                    };
+                    // `active-admin` has the `admin` scope, the others only `repo:read`
+                    let scope = match token.as_str() {
+                        "active-admin" => "repo:read admin",
+                        _ => "repo:read",
+                    };
+                    Json(json!({
+                        "active": true,
+                        "sub": token,
+                        "aud": "api",
+                        "exp": exp,
+                        "scope": scope,
+                    }))
+                },
+            ),
+        )
+        .with_state(requests.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}/introspect"), requests)
+}
+
+fn validator(endpoint: &str) -> BearerValidator {
+    BearerValidator::introspection(endpoint, "api-client", "api-secret", "api")
+}
+
+#[tokio::test]
+async fn introspection_results_are_cached() {
+    let (endpoint, requests) = introspection_server().await;
+    let validator = validator(&endpoint);
+    for _ in 0..3 {
+        let claims = validator.validate("active-1").await.unwrap();
+        assert_eq!(claims.sub.as_deref(), Some("active-1"));
+        assert!(matches!(
+            validator.validate("inactive").await,
+            Err(OauthError::InvalidAccessToken)
+        ));
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn cache_is_disabled_with_size_zero() {
+    let (endpoint, requests) = introspection_server().await;
+    let validator = validator(&endpoint).set_introspection_cache_size(0);
+    for _ in 0..3 {
+        validator.validate("active-1").await.unwrap();
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn oldest_results_are_removed_when_the_cache_is_full() {
+    let (endpoint, requests) = introspection_server().await;
+    let validator = validator(&endpoint).set_introspection_cache_size(2);
+    for token in ["active-1", "active-2", "active-3"] {
+        validator.validate(token).await.unwrap();
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+    validator.validate("active-3").await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+    validator.validate("active-1").await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn result_is_not_cached_after_the_token_expires() {
+    let (endpoint, requests) = introspection_server().await;
+    let validator = validator(&endpoint).set_introspection_ttl(Duration::from_secs(3600));
+    for _ in 0..2 {
+        validator.validate("active-long").await.unwrap();
+        validator.validate("active-now").await.unwrap();
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn expired_results_behind_a_long_one_keep_it_cached() {
+    let (endpoint, requests) = introspection_server().await;
+    let validator = validator(&endpoint)
+        .set_introspection_ttl(Duration::from_secs(3600))
+        .set_introspection_cache_size(4);
+    validator.validate("active-long").await.unwrap();
+    // Every validation of the expired token is inserted again behind the long one
+    for _ in 0..50 {
+        validator.validate("active-now").await.unwrap();
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 51);
+    validator.validate("active-long").await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 51);
+}
+
+async fn sub(claims: BearerClaims) -> String {
+    claims.sub.unwrap_or_default()
+}
+
+#[tokio::test]
+async fn bearer_scheme_is_case_insensitive() {
+    let (endpoint, _) = introspection_server().await;
+    let app = Router::new()
+        .route("/", get(sub))
+        .with_state(validator(&endpoint));
+    for (authorization, status) in [
+        ("Bearer active-1", StatusCode::OK),
+        ("bearer active-1", StatusCode::OK),
+        ("BEARER active-1", StatusCode::OK),
+        ("bEaReR active-1", StatusCode::OK),
+        ("Bearer inactive", StatusCode::UNAUTHORIZED),
+        ("Basic active-1", StatusCode::UNAUTHORIZED),
+        ("Beareractive-1", StatusCode::UNAUTHORIZED),
+        ("active-1", StatusCode::UNAUTHORIZED),
+    ] {
+        let request = Request::builder()
+            .uri("/")
+            .header(header::AUTHORIZATION, authorization)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), status, "{authorization}");
+        assert!(
+            status == StatusCode::OK || response.headers().contains_key(header::WWW_AUTHENTICATE)
+        );
+    }
+}
+
+scope!(Admin, "admin");
+
+async fn admin(RequireScope(claims, _): RequireScope<Admin>) -> String {
+    claims.sub.unwrap_or_default()
+}
+
+async fn guarded(Extension(claims): Extension<BearerClaims>) -> String {
+    claims.sub.unwrap_or_default()
+}
+
+/// Status, `WWW-Authenticate` header and body of the response
+async fn call(app: &Router, authorization: Option<&str>) -> (StatusCode, Option<String>, String) {
+    let mut request = Request::builder().uri("/");
+    if let Some(authorization) = authorization {
+        request = request.header(header::AUTHORIZATION, authorization);
+    }
+    let response = app
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let challenge = response
+        .headers()
+        .get(header::WWW_AUTHENTICATE)
+        .map(|value| value.to_str().unwrap().to_string());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, challenge, String::from_utf8(body.to_vec()).unwrap())
+}
+
+async fn assert_scope_is_enforced(app: Router) {
+    assert_eq!(
+        call(&app, Some("Bearer active-admin")).await,
+        (StatusCode::OK, None, "active-admin".to_string())
+    );
+    assert_eq!(
+        call(&app, Some("Bearer active-1")).await,
+        (
+            StatusCode::FORBIDDEN,
+            Some("Bearer error=\"insufficient_scope\", scope=\"admin\"".to_string()),
+            String::new()
+        )
+    );
+    assert_eq!(
+        call(&app, None).await,
+        (
+            StatusCode::UNAUTHORIZED,
+            Some("Bearer".to_string()),
+            String::new()
+        )
+    );
+    assert_eq!(
+        call(&app, Some("Bearer inactive")).await,
+        (
+            StatusCode::UNAUTHORIZED,
+            Some("Bearer error=\"invalid_token\"".to_string()),
+            String::new()
+        )
+    );
+}
+
+#[tokio::test]
+async fn require_scope_checks_the_scope() {
+    let (endpoint, _) = introspection_server().await;
+    let app = Router::new()
+        .route("/", get(admin))
+        .with_state(validator(&endpoint));
+    assert_scope_is_enforced(app).await;
+}
+
+#[tokio::test]
+async fn scope_guard_checks_the_scope() {
+    let (endpoint, _) = introspection_server().await;
+    let app = Router::new()
+        .route("/", get(guarded))
+        .route_layer(middleware::from_fn_with_state(
+            (validator(&endpoint), "admin"),
+            scope_guard,
+        ));
+    assert_scope_is_enforced(app).await;
+}
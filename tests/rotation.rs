@@ -0,0 +1,80 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use common::{exchange, token_error, token_response};
+use oauth_axum::error::OauthError;
+use oauth_axum::{CustomProvider, SecretUsed};
+
+/// Token endpoint that accepts only `new-secret`, the other secrets are rejected with `error`
+async fn token_server(error: &'static str) -> (String, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/token",
+            post(
+                move |State(requests): State<Arc<AtomicUsize>>, headers: HeaderMap| async move {
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    let credentials = headers
+                        .get(header::AUTHORIZATION)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.strip_prefix("Basic "))
+                        .and_then(|value| STANDARD.decode(value).ok())
+                        .and_then(|value| String::from_utf8(value).ok());
+                    if credentials.as_deref() != Some("test-client:new-secret") {
+                        return Err(token_error(StatusCode::BAD_REQUEST, error));
+                    }
+                    Ok(token_response("access-token"))
+                },
+            ),
+        )
+        .with_state(requests.clone());
+    (common::serve(app).await, requests)
+}
+
+/// Provider in the middle of a rotation, the provider only knows the secondary secret
+fn provider(token_url: &str, used: Arc<Mutex<Vec<SecretUsed>>>) -> CustomProvider {
+    common::provider(token_url)
+        .set_client_secret("old-secret".to_string())
+        .set_secondary_client_secret("new-secret".to_string())
+        .set_on_secret_used(move |secret_used| used.lock().unwrap().push(secret_used))
+}
+
+#[tokio::test]
+async fn rejected_client_is_retried_with_the_secondary_secret() {
+    let (token_url, requests) = token_server("invalid_client").await;
+    let used = Arc::new(Mutex::new(Vec::new()));
+    let token = exchange(provider(&token_url, used.clone()), "code")
+        .await
+        .unwrap();
+    assert_eq!(token.access_token, "access-token");
+    assert_eq!(*used.lock().unwrap(), vec![SecretUsed::Secondary]);
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn primary_secret_is_reported() {
+    let (token_url, requests) = token_server("invalid_client").await;
+    let used = Arc::new(Mutex::new(Vec::new()));
+    let provider = provider(&token_url, used.clone()).set_client_secret("new-secret".to_string());
+    exchange(provider, "code").await.unwrap();
+    assert_eq!(*used.lock().unwrap(), vec![SecretUsed::Primary]);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn other_errors_are_not_retried() {
+    let (token_url, requests) = token_server("invalid_grant").await;
+    let used = Arc::new(Mutex::new(Vec::new()));
+    let result = exchange(provider(&token_url, used.clone()), "code").await;
+    assert!(matches!(result, Err(OauthError::TokenRequestFailed)));
+    assert!(used.lock().unwrap().is_empty());
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
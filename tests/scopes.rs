@@ -0,0 +1,126 @@
+use oauth2::url::Url;
+use oauth_axum::error::OauthError;
+use oauth_axum::scopes::{ScopeDelimiter, Scopes};
+use oauth_axum::{CustomProvider, OAuthClient};
+
+fn rejected(scopes: &[&str], delimiter: ScopeDelimiter) -> bool {
+    matches!(
+        Scopes::from(scopes.to_vec()).validate(delimiter),
+        Err(OauthError::InvalidScope)
+    )
+}
+
+#[test]
+fn valid_scopes_are_accepted() {
+    let scopes = Scopes::from([
+        "openid",
+        "read:user",
+        "https://www.googleapis.com/auth/drive.readonly",
+        "user.read",
+        "a!#[]~",
+    ]);
+    assert!(scopes.validate(ScopeDelimiter::Space).is_ok());
+    assert!(Scopes::new().validate(ScopeDelimiter::Space).is_ok());
+}
+
+#[test]
+fn empty_scopes_are_rejected() {
+    assert!(rejected(&[""], ScopeDelimiter::Space));
+    assert!(rejected(&["openid", ""], ScopeDelimiter::Comma));
+}
+
+#[test]
+fn whitespace_is_rejected() {
+    for scope in [
+        "openid profile",
+        " openid",
+        "openid ",
+        "open\tid",
+        "open\nid",
+        "open\rid",
+        "open\u{a0}id",
+    ] {
+        assert!(rejected(&[scope], ScopeDelimiter::Comma), "{scope:?}");
+    }
+}
+
+#[test]
+fn quote_and_backslash_are_rejected() {
+    for scope in ["open\"id", "\"openid\"", "open\\id", "\\"] {
+        assert!(rejected(&[scope], ScopeDelimiter::Space), "{scope:?}");
+    }
+}
+
+#[test]
+fn control_and_non_ascii_characters_are_rejected() {
+    for scope in ["open\u{0}id", "open\u{7f}id", "ópenid", "openid✓"] {
+        assert!(rejected(&[scope], ScopeDelimiter::Space), "{scope:?}");
+    }
+}
+
+#[test]
+fn delimiter_inside_the_scope_is_rejected() {
+    assert!(rejected(&["read,write"], ScopeDelimiter::Comma));
+    assert!(rejected(&["read;write"], ScopeDelimiter::Custom(";")));
+    assert!(rejected(&["read"], ScopeDelimiter::Custom("")));
+    assert!(Scopes::from(["read,write"])
+        .validate(ScopeDelimiter::Space)
+        .is_ok());
+}
+
+#[test]
+fn parse_splits_the_scopes() {
+    let scopes = Scopes::parse(" openid,profile  email\topenid ");
+    assert_eq!(scopes, Scopes::from(["openid", "profile", "email"]));
+    assert!(scopes.validate(ScopeDelimiter::Space).is_ok());
+}
+
+#[tokio::test]
+async fn invalid_scopes_are_rejected_in_the_url() {
+    let provider = CustomProvider::new(
+        "https://auth.example.com/authorize".to_string(),
+        "https://auth.example.com/token".to_string(),
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        "https://app.example.com/callback".to_string(),
+    );
+    assert!(matches!(
+        provider.authorize(["openid profile"]).await,
+        Err(OauthError::InvalidScope)
+    ));
+    assert!(matches!(
+        provider.authorize(["open\"id"]).await,
+        Err(OauthError::InvalidScope)
+    ));
+    assert!(provider.authorize(["openid", "profile"]).await.is_ok());
+}
+
+/// Scope of the authorization URL
+fn url_scope(url: &str) -> Option<String> {
+    Url::parse(url)
+        .unwrap()
+        .query_pairs()
+        .find(|(name, _)| name == "scope")
+        .map(|(_, value)| value.into_owned())
+}
+
+#[tokio::test]
+async fn openid_provider_adds_openid_to_the_default_scopes() {
+    let provider = CustomProvider::new(
+        "https://auth.example.com/authorize".to_string(),
+        "https://auth.example.com/token".to_string(),
+        "test-client".to_string(),
+        "test-secret".to_string(),
+        "https://app.example.com/callback".to_string(),
+    )
+    .set_default_scopes(["email", "profile"])
+    .into_openid("https://auth.example.com", "https://auth.example.com/jwks");
+
+    let pending = provider.authorize(Scopes::new()).await.unwrap();
+    assert_eq!(
+        url_scope(pending.url()).as_deref(),
+        Some("email profile openid")
+    );
+    let pending = provider.authorize(["email"]).await.unwrap();
+    assert_eq!(url_scope(pending.url()).as_deref(), Some("email openid"));
+}
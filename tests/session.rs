@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use oauth_axum::error::OauthError;
+use oauth_axum::login::LoginOutcome;
+use oauth_axum::session::{SessionIssuer, SigningKey};
+use oauth_axum::token::TokenSet;
+use oauth_axum::user::OAuthUser;
+use oauth_axum::validation::ValidationOptions;
+use serde_json::{json, Map, Value};
+
+const RSA_KEY: &[u8] = include_bytes!("data/rsa2048-pkcs8.der");
+
+fn outcome() -> LoginOutcome {
+    LoginOutcome {
+        token: TokenSet {
+            access_token: "access".to_string(),
+            token_type: "bearer".to_string(),
+            refresh_token: None,
+            expires_in: Some(3600),
+            scopes: None,
+            extra: HashMap::new(),
+        },
+        user: Some(OAuthUser {
+            id: "42".to_string(),
+            email: Some("user@example.com".to_string()),
+            name: Some("User".to_string()),
+            avatar_url: None,
+            raw: Value::Null,
+        }),
+        user_id: None,
+        return_to: None,
+    }
+}
+
+fn round_trip(key: SigningKey) {
+    let issuer = SessionIssuer::new(key, "https://app.example.com").set_audience("api");
+    let token = issuer.issue("github", &outcome()).unwrap();
+    let claims = issuer
+        .verify(&token, &ValidationOptions::default())
+        .unwrap();
+    assert_eq!(claims["iss"], "https://app.example.com");
+    assert_eq!(claims["sub"], "github:42");
+    assert_eq!(claims["aud"], "api");
+    assert_eq!(claims["provider"], "github");
+    assert_eq!(claims["email"], "user@example.com");
+    assert_eq!(claims["name"], "User");
+}
+
+#[test]
+fn hs256_round_trip() {
+    round_trip(SigningKey::hs256(b"a-secret-of-the-session-tokens"));
+}
+
+#[test]
+fn rs256_round_trip() {
+    round_trip(SigningKey::rs256_pkcs8(RSA_KEY).unwrap());
+}
+
+#[test]
+fn rejects_token_of_other_key_or_issuer() {
+    let issuer = SessionIssuer::new(SigningKey::hs256(b"secret-1"), "https://app.example.com");
+    let token = issuer.issue("github", &outcome()).unwrap();
+
+    let other_key = SessionIssuer::new(SigningKey::hs256(b"secret-2"), "https://app.example.com");
+    let result = other_key.verify(&token, &ValidationOptions::default());
+    assert!(matches!(result, Err(OauthError::InvalidAccessToken)));
+
+    let other_issuer =
+        SessionIssuer::new(SigningKey::hs256(b"secret-1"), "https://other.example.com");
+    let result = other_issuer.verify(&token, &ValidationOptions::default());
+    assert!(matches!(result, Err(OauthError::InvalidAccessToken)));
+
+    let rs256 = SessionIssuer::new(
+        SigningKey::rs256_pkcs8(RSA_KEY).unwrap(),
+        "https://app.example.com",
+    );
+    let result = rs256.verify(&token, &ValidationOptions::default());
+    assert!(matches!(result, Err(OauthError::InvalidAccessToken)));
+}
+
+#[test]
+fn custom_claims_cannot_replace_reserved_claims() {
+    let issuer = SessionIssuer::new(SigningKey::hs256(b"secret"), "https://app.example.com")
+        .set_claims(|_, _| {
+            let mut claims = Map::new();
+            for (name, value) in [
+                ("iss", json!("https://evil.example.com")),
+                ("sub", json!("admin")),
+                ("exp", json!(u64::MAX)),
+                ("iat", json!(0)),
+                ("aud", json!("other-api")),
+                ("name", json!("Custom name")),
+                ("roles", json!(["admin"])),
+            ] {
+                claims.insert(name.to_string(), value);
+            }
+            claims
+        });
+    let token = issuer.issue("github", &outcome()).unwrap();
+    let claims = issuer
+        .verify(&token, &ValidationOptions::default())
+        .unwrap();
+    assert_eq!(claims["iss"], "https://app.example.com");
+    assert_eq!(claims["sub"], "github:42");
+    assert!(claims["exp"].as_u64().unwrap() < u64::MAX);
+    assert!(claims["iat"].as_u64().unwrap() > 0);
+    assert!(!claims.contains_key("aud"));
+    assert_eq!(claims["name"], "Custom name");
+    assert_eq!(claims["roles"], json!(["admin"]));
+}
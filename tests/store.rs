@@ -0,0 +1,165 @@
+use oauth_axum::error::OauthError;
+use oauth_axum::store::{state_hash, MemoryStateStore, Namespaced, StateStore};
+use oauth_axum::StateAuth;
+
+fn state(value: &str, verifier: &str) -> StateAuth {
+    StateAuth::restore(value.to_string(), verifier.to_string()).unwrap()
+}
+
+/// Two apps that share the same store
+fn apps() -> (
+    MemoryStateStore,
+    Namespaced<MemoryStateStore>,
+    Namespaced<MemoryStateStore>,
+) {
+    let shared = MemoryStateStore::default();
+    (
+        shared.clone(),
+        Namespaced::new("app-a", shared.clone()).unwrap(),
+        Namespaced::new("app-b", shared).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn namespace_cant_take_the_state_of_other() {
+    let (_, a, b) = apps();
+    a.save("github", state("state-1", "verifier-a"))
+        .await
+        .unwrap();
+
+    assert!(b.take("state-1").await.unwrap().is_none());
+    assert!(b.take("app-a:state-1").await.unwrap().is_none());
+    // The failed attempts of the other namespace don't consume the state
+    let item = a.take("state-1").await.unwrap().unwrap();
+    assert_eq!(item.state.state(), "state-1");
+    assert_eq!(item.state.verifier(), "verifier-a");
+    assert!(a.take("state-1").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn namespace_cant_be_the_prefix_of_other() {
+    let shared = MemoryStateStore::default();
+    for namespace in ["", "app:b", ":", "app:"] {
+        assert!(
+            matches!(
+                Namespaced::new(namespace, shared.clone()),
+                Err(OauthError::InvalidConfig)
+            ),
+            "{namespace}"
+        );
+    }
+
+    // `app:b` is rejected, so the state `b:state-1` of `app` is only listed by `app`
+    let app = Namespaced::new("app", shared.clone()).unwrap();
+    app.save("github", state("b:state-1", "verifier"))
+        .await
+        .unwrap();
+    let pending = app.list_pending().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].state_hash, state_hash("b:state-1"));
+    let item = app.take("b:state-1").await.unwrap().unwrap();
+    assert_eq!(item.state.state(), "b:state-1");
+}
+
+#[tokio::test]
+async fn same_state_in_two_namespaces() {
+    let (shared, a, b) = apps();
+    a.save("github", state("state-1", "verifier-a"))
+        .await
+        .unwrap();
+    b.save("google", state("state-1", "verifier-b"))
+        .await
+        .unwrap();
+    assert_eq!(shared.list_pending().await.unwrap().len(), 2);
+
+    let item = b.take("state-1").await.unwrap().unwrap();
+    assert_eq!(item.provider, "google");
+    assert_eq!(item.state.verifier(), "verifier-b");
+    let item = a.take_for("github", "state-1").await.unwrap().unwrap();
+    assert_eq!(item.state.verifier(), "verifier-a");
+}
+
+#[tokio::test]
+async fn namespace_lists_only_its_states() {
+    let (shared, a, b) = apps();
+    a.save_many(
+        "github",
+        vec![state("state-1", "verifier"), state("state-2", "verifier")],
+    )
+    .await
+    .unwrap();
+    b.save("google", state("state-3", "verifier"))
+        .await
+        .unwrap();
+    shared
+        .save("github", state("state-4", "verifier"))
+        .await
+        .unwrap();
+
+    let mut hashes: Vec<String> = a
+        .list_pending()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|item| item.state_hash)
+        .collect();
+    hashes.sort();
+    let mut expected = vec![state_hash("state-1"), state_hash("state-2")];
+    expected.sort();
+    assert_eq!(hashes, expected);
+
+    let stats = b.stats().await.unwrap();
+    assert_eq!(stats.count, 1);
+    assert_eq!(stats.per_provider.get("google"), Some(&1));
+    assert_eq!(shared.stats().await.unwrap().count, 4);
+}
+
+#[tokio::test]
+async fn namespace_cant_remove_the_states_of_other() {
+    let (shared, a, b) = apps();
+    a.save("github", state("state-1", "verifier"))
+        .await
+        .unwrap();
+    b.save("github", state("state-2", "verifier"))
+        .await
+        .unwrap();
+
+    b.remove_many(&["state-1".to_string(), "state-2".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(shared.list_pending().await.unwrap().len(), 1);
+    assert!(a.take("state-1").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn namespaced_state_is_not_found_without_the_namespace() {
+    let (shared, a, _) = apps();
+    a.save("github", state("state-1", "verifier"))
+        .await
+        .unwrap();
+
+    assert!(shared.take("state-1").await.unwrap().is_none());
+    assert!(a.take("state-1").await.unwrap().is_some());
+}
+
+#[test]
+fn state_is_restored_with_the_saved_values() {
+    let restored = StateAuth::restore("state-1".to_string(), "verifier-a".to_string())
+        .unwrap()
+        .with_return_to(Some("/dashboard".to_string()))
+        .with_fingerprint(Some("fingerprint".to_string()))
+        .with_nonce(Some("nonce".to_string()));
+    assert_eq!(restored.state(), "state-1");
+    assert_eq!(restored.verifier(), "verifier-a");
+    assert_eq!(restored.url_generated(), None);
+    assert_eq!(restored.return_to(), Some("/dashboard"));
+    assert_eq!(restored.fingerprint(), Some("fingerprint"));
+    assert_eq!(restored.nonce(), Some("nonce"));
+
+    for (state, verifier) in [("", "verifier-a"), ("state-1", "")] {
+        assert!(matches!(
+            StateAuth::restore(state.to_string(), verifier.to_string()),
+            Err(OauthError::VerifierNotFound)
+        ));
+    }
+}